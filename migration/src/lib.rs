@@ -8,6 +8,38 @@ mod m20260116_085306_create_transfer_table;
 mod m20260116_085316_create_cycle_table;
 mod m20260116_085326_create_pick_table;
 mod m20260116_085335_create_reservation_table;
+mod m20260120_091500_alter_cycle_count_line_counted_quantity;
+mod m20260121_100000_add_cycle_count_controls;
+mod m20260122_083000_create_held_movement_table;
+mod m20260123_094500_create_item_abc_class_table;
+mod m20260124_101500_create_adjustment_table;
+mod m20260125_090000_create_inventory_balance_table;
+mod m20260126_080000_alter_reservation_expires_at;
+mod m20260127_083000_add_warehouse_allow_negative_stock;
+mod m20260128_090000_add_ledger_reversal;
+mod m20260129_100000_add_ledger_hash_chain;
+mod m20260130_090000_allow_balance_after_repair;
+mod m20260131_090000_create_receipt_tables;
+mod m20260201_090000_create_purchase_order_tables;
+mod m20260202_090000_tune_bulk_receipt_queries;
+mod m20260203_090000_create_idempotency_key_table;
+mod m20260204_090000_create_import_job_tables;
+mod m20260205_090000_create_mapping_profile_tables;
+mod m20260206_090000_create_putaway_task_table;
+mod m20260207_090000_create_putaway_rule_tables;
+mod m20260208_090000_add_held_movement_resolution;
+mod m20260209_090000_add_ledger_txid;
+mod m20260210_090000_create_ledger_chain_head_table;
+mod m20260211_090000_add_idempotency_key_heartbeat;
+mod m20260212_090000_add_import_job_column_mapping;
+mod m20260213_090000_add_purchase_order_closed_manually;
+mod m20260214_090000_split_putaway_tasks;
+mod m20260215_090000_add_ledger_item_location_index;
+mod m20260216_090000_add_cycle_count_open_location_index;
+mod m20260217_090000_drop_ledger_txid;
+mod m20260218_090000_create_import_job_row_table;
+mod m20260219_090000_add_ledger_repair_function;
+mod m20260220_090000_restrict_ledger_privileges;
 
 pub struct Migrator;
 
@@ -23,6 +55,38 @@ impl MigratorTrait for Migrator {
             Box::new(m20260116_085316_create_cycle_table::Migration),
             Box::new(m20260116_085326_create_pick_table::Migration),
             Box::new(m20260116_085335_create_reservation_table::Migration),
+            Box::new(m20260120_091500_alter_cycle_count_line_counted_quantity::Migration),
+            Box::new(m20260121_100000_add_cycle_count_controls::Migration),
+            Box::new(m20260122_083000_create_held_movement_table::Migration),
+            Box::new(m20260123_094500_create_item_abc_class_table::Migration),
+            Box::new(m20260124_101500_create_adjustment_table::Migration),
+            Box::new(m20260125_090000_create_inventory_balance_table::Migration),
+            Box::new(m20260126_080000_alter_reservation_expires_at::Migration),
+            Box::new(m20260127_083000_add_warehouse_allow_negative_stock::Migration),
+            Box::new(m20260128_090000_add_ledger_reversal::Migration),
+            Box::new(m20260129_100000_add_ledger_hash_chain::Migration),
+            Box::new(m20260130_090000_allow_balance_after_repair::Migration),
+            Box::new(m20260131_090000_create_receipt_tables::Migration),
+            Box::new(m20260201_090000_create_purchase_order_tables::Migration),
+            Box::new(m20260202_090000_tune_bulk_receipt_queries::Migration),
+            Box::new(m20260203_090000_create_idempotency_key_table::Migration),
+            Box::new(m20260204_090000_create_import_job_tables::Migration),
+            Box::new(m20260205_090000_create_mapping_profile_tables::Migration),
+            Box::new(m20260206_090000_create_putaway_task_table::Migration),
+            Box::new(m20260207_090000_create_putaway_rule_tables::Migration),
+            Box::new(m20260208_090000_add_held_movement_resolution::Migration),
+            Box::new(m20260209_090000_add_ledger_txid::Migration),
+            Box::new(m20260210_090000_create_ledger_chain_head_table::Migration),
+            Box::new(m20260211_090000_add_idempotency_key_heartbeat::Migration),
+            Box::new(m20260212_090000_add_import_job_column_mapping::Migration),
+            Box::new(m20260213_090000_add_purchase_order_closed_manually::Migration),
+            Box::new(m20260214_090000_split_putaway_tasks::Migration),
+            Box::new(m20260215_090000_add_ledger_item_location_index::Migration),
+            Box::new(m20260216_090000_add_cycle_count_open_location_index::Migration),
+            Box::new(m20260217_090000_drop_ledger_txid::Migration),
+            Box::new(m20260218_090000_create_import_job_row_table::Migration),
+            Box::new(m20260219_090000_add_ledger_repair_function::Migration),
+            Box::new(m20260220_090000_restrict_ledger_privileges::Migration),
         ]
     }
 }
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A line is created when the count is opened, before anyone has counted it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCountLine::Table)
+                    .modify_column(ColumnDef::new(CycleCountLine::CountedQuantity).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE cycle_count_line SET counted_quantity = 0 WHERE counted_quantity IS NULL",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCountLine::Table)
+                    .modify_column(
+                        ColumnDef::new(CycleCountLine::CountedQuantity)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CycleCountLine {
+    Table,
+    CountedQuantity,
+}
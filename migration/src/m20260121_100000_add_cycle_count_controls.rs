@@ -0,0 +1,144 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Unit cost is needed to value count variances
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .add_column(ColumnDef::new(Item::UnitCost).decimal_len(14, 4).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCount::Table)
+                    .add_column(
+                        ColumnDef::new(CycleCount::IsBlind)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(CycleCount::RecountThresholdPercent)
+                            .integer()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(CycleCount::RecountThresholdUnits)
+                            .integer()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(CycleCount::ApprovalValueLimit)
+                            .decimal_len(14, 4)
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCountLine::Table)
+                    .add_column(
+                        ColumnDef::new(CycleCountLine::Status)
+                            .string()
+                            .not_null()
+                            .default("PENDING"),
+                    )
+                    .add_column(
+                        ColumnDef::new(CycleCountLine::CountAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(CycleCountLine::ApprovedBy).string().null())
+                    .add_column(
+                        ColumnDef::new(CycleCountLine::ApprovedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Lines counted before this migration are treated as plain counts
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE cycle_count_line SET status = 'COUNTED', count_attempts = 1 \
+                 WHERE counted_quantity IS NOT NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCountLine::Table)
+                    .drop_column(CycleCountLine::Status)
+                    .drop_column(CycleCountLine::CountAttempts)
+                    .drop_column(CycleCountLine::ApprovedBy)
+                    .drop_column(CycleCountLine::ApprovedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCount::Table)
+                    .drop_column(CycleCount::IsBlind)
+                    .drop_column(CycleCount::RecountThresholdPercent)
+                    .drop_column(CycleCount::RecountThresholdUnits)
+                    .drop_column(CycleCount::ApprovalValueLimit)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Item::Table)
+                    .drop_column(Item::UnitCost)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    UnitCost,
+}
+
+#[derive(DeriveIden)]
+enum CycleCount {
+    Table,
+    IsBlind,
+    RecountThresholdPercent,
+    RecountThresholdUnits,
+    ApprovalValueLimit,
+}
+
+#[derive(DeriveIden)]
+enum CycleCountLine {
+    Table,
+    Status,
+    CountAttempts,
+    ApprovedBy,
+    ApprovedAt,
+}
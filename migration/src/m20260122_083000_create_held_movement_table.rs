@@ -0,0 +1,135 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // REJECT | QUEUE - what happens to movements into a location with an open cycle count
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .add_column(
+                        ColumnDef::new(Warehouse::CountFreezeMode)
+                            .string()
+                            .not_null()
+                            .default("REJECT"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(HeldMovement::Table)
+                    .if_not_exists()
+                    .col(pk_auto(HeldMovement::Id))
+                    .col(integer(HeldMovement::CycleCountId).not_null())
+                    .col(integer(HeldMovement::ItemId).not_null())
+                    .col(integer(HeldMovement::LocationId).not_null())
+                    .col(integer(HeldMovement::QuantityChange).not_null())
+                    .col(string(HeldMovement::ReasonType).not_null())
+                    .col(string_null(HeldMovement::ReferenceType))
+                    .col(integer_null(HeldMovement::ReferenceId))
+                    .col(string(HeldMovement::Status).not_null())
+                    .col(integer_null(HeldMovement::LedgerEntryId))
+                    .col(
+                        timestamp_with_time_zone(HeldMovement::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(HeldMovement::AppliedAt))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_held_movement_cycle_count")
+                            .from(HeldMovement::Table, HeldMovement::CycleCountId)
+                            .to(CycleCount::Table, CycleCount::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_held_movement_item")
+                            .from(HeldMovement::Table, HeldMovement::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_held_movement_location")
+                            .from(HeldMovement::Table, HeldMovement::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_held_movement_ledger")
+                            .from(HeldMovement::Table, HeldMovement::LedgerEntryId)
+                            .to(InventoryLedger::Table, InventoryLedger::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(HeldMovement::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .drop_column(Warehouse::CountFreezeMode)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    CountFreezeMode,
+}
+
+#[derive(DeriveIden)]
+enum HeldMovement {
+    Table,
+    Id,
+    CycleCountId,
+    ItemId,
+    LocationId,
+    QuantityChange,
+    ReasonType,
+    ReferenceType,
+    ReferenceId,
+    Status,
+    LedgerEntryId,
+    CreatedAt,
+    AppliedAt,
+}
+
+#[derive(DeriveIden)]
+enum CycleCount {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    Id,
+}
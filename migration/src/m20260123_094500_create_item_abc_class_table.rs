@@ -0,0 +1,134 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ItemAbcClass::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ItemAbcClass::Id))
+                    .col(integer(ItemAbcClass::ItemId).not_null())
+                    .col(integer(ItemAbcClass::WarehouseId).not_null())
+                    .col(string(ItemAbcClass::Class).not_null())
+                    .col(integer(ItemAbcClass::PickCount).not_null())
+                    .col(integer(ItemAbcClass::PickQuantity).not_null())
+                    .col(
+                        timestamp_with_time_zone(ItemAbcClass::ClassifiedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_item_abc_class_item")
+                            .from(ItemAbcClass::Table, ItemAbcClass::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_item_abc_class_warehouse")
+                            .from(ItemAbcClass::Table, ItemAbcClass::WarehouseId)
+                            .to(Warehouse::Table, Warehouse::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_item_abc_class_item_warehouse")
+                    .table(ItemAbcClass::Table)
+                    .col(ItemAbcClass::ItemId)
+                    .col(ItemAbcClass::WarehouseId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Days of the week the scheduler may place cycle counts on
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .add_column(
+                        ColumnDef::new(Warehouse::WorkingDays)
+                            .string()
+                            .not_null()
+                            .default("MON,TUE,WED,THU,FRI"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCount::Table)
+                    .add_column(ColumnDef::new(CycleCount::ScheduledFor).date().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CycleCount::Table)
+                    .drop_column(CycleCount::ScheduledFor)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .drop_column(Warehouse::WorkingDays)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ItemAbcClass::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ItemAbcClass {
+    Table,
+    Id,
+    ItemId,
+    WarehouseId,
+    Class,
+    PickCount,
+    PickQuantity,
+    ClassifiedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+    WorkingDays,
+}
+
+#[derive(DeriveIden)]
+enum CycleCount {
+    Table,
+    ScheduledFor,
+}
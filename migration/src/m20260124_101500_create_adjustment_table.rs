@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Adjustment::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Adjustment::Id))
+                    .col(integer(Adjustment::ItemId).not_null())
+                    .col(integer(Adjustment::LocationId).not_null())
+                    .col(integer(Adjustment::QuantityChange).not_null())
+                    .col(string(Adjustment::ReasonCode).not_null())
+                    .col(text_null(Adjustment::Note))
+                    .col(
+                        timestamp_with_time_zone(Adjustment::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_adjustment_item")
+                            .from(Adjustment::Table, Adjustment::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_adjustment_location")
+                            .from(Adjustment::Table, Adjustment::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Adjustment::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Adjustment {
+    Table,
+    Id,
+    ItemId,
+    LocationId,
+    QuantityChange,
+    ReasonCode,
+    Note,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(InventoryBalance::Table)
+                    .if_not_exists()
+                    .col(pk_auto(InventoryBalance::Id))
+                    .col(integer(InventoryBalance::ItemId).not_null())
+                    .col(integer(InventoryBalance::LocationId).not_null())
+                    .col(integer(InventoryBalance::OnHand).not_null().default(0))
+                    .col(
+                        timestamp_with_time_zone(InventoryBalance::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_inventory_balance_item")
+                            .from(InventoryBalance::Table, InventoryBalance::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_inventory_balance_location")
+                            .from(InventoryBalance::Table, InventoryBalance::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Ledger inserts upsert on this index
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_balance_item_location")
+                    .table(InventoryBalance::Table)
+                    .col(InventoryBalance::ItemId)
+                    .col(InventoryBalance::LocationId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Seed the projection from the existing ledger
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO inventory_balance (item_id, location_id, on_hand) \
+                 SELECT item_id, location_id, SUM(quantity_change) \
+                 FROM inventory_ledger GROUP BY item_id, location_id",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(InventoryBalance::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryBalance {
+    Table,
+    Id,
+    ItemId,
+    LocationId,
+    OnHand,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
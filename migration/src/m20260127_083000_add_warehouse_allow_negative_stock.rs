@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Ledger writes that would take on-hand below zero are rejected unless this is set
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .add_column(
+                        ColumnDef::new(Warehouse::AllowNegativeStock)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .drop_column(Warehouse::AllowNegativeStock)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    AllowNegativeStock,
+}
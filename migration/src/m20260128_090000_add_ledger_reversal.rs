@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A REVERSAL entry points at the entry it cancels out
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .add_column(
+                        ColumnDef::new(InventoryLedger::ReversesEntryId)
+                            .integer()
+                            .null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_inventory_ledger_reverses_entry")
+                            .from_tbl(InventoryLedger::Table)
+                            .from_col(InventoryLedger::ReversesEntryId)
+                            .to_tbl(InventoryLedger::Table)
+                            .to_col(InventoryLedger::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // An entry can only be reversed once
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_ledger_reverses_entry")
+                    .table(InventoryLedger::Table)
+                    .col(InventoryLedger::ReversesEntryId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_inventory_ledger_reverses_entry")
+                    .table(InventoryLedger::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .drop_foreign_key(Alias::new("fk_inventory_ledger_reverses_entry"))
+                    .drop_column(InventoryLedger::ReversesEntryId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    Id,
+    ReversesEntryId,
+}
@@ -0,0 +1,266 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .add_column(
+                        ColumnDef::new(InventoryLedger::WarehouseId)
+                            .integer()
+                            .null(),
+                    )
+                    .add_column(ColumnDef::new(InventoryLedger::ChainSeq).integer().null())
+                    .add_column(ColumnDef::new(InventoryLedger::PrevHash).string().null())
+                    .add_column(ColumnDef::new(InventoryLedger::EntryHash).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The one definition of an entry's hash, shared by the insert trigger and verification.
+        // balance_after is a cache and deliberately not covered
+        db.execute_unprepared(
+            r#"
+            CREATE FUNCTION inventory_ledger_entry_hash(
+                id INTEGER,
+                warehouse_id INTEGER,
+                chain_seq INTEGER,
+                item_id INTEGER,
+                location_id INTEGER,
+                quantity_change INTEGER,
+                reason_type VARCHAR,
+                reference_type VARCHAR,
+                reference_id INTEGER,
+                reverses_entry_id INTEGER,
+                created_at TIMESTAMPTZ,
+                prev_hash VARCHAR
+            ) RETURNS VARCHAR
+            LANGUAGE SQL IMMUTABLE AS $$
+                SELECT encode(sha256(convert_to(concat_ws('|',
+                    id, warehouse_id, chain_seq, item_id, location_id, quantity_change,
+                    reason_type, COALESCE(reference_type, ''), COALESCE(reference_id::TEXT, ''),
+                    COALESCE(reverses_entry_id::TEXT, ''),
+                    (EXTRACT(EPOCH FROM created_at) * 1000000)::BIGINT,
+                    COALESCE(prev_hash, '')
+                ), 'UTF8')), 'hex')
+            $$
+            "#,
+        )
+        .await?;
+
+        // Ledger writers take this before touching stock, so each warehouse's chain is appended
+        // to by one transaction at a time
+        db.execute_unprepared(
+            r#"
+            CREATE FUNCTION inventory_ledger_chain_lock(warehouse_id INTEGER) RETURNS VOID
+            LANGUAGE SQL AS $$
+                SELECT pg_advisory_xact_lock(hashtextextended('inventory_ledger_chain', warehouse_id))
+            $$
+            "#,
+        )
+        .await?;
+
+        // Chain the existing rows in id order
+        db.execute_unprepared(
+            r#"
+            DO $$
+            DECLARE
+                entry RECORD;
+                last_warehouse INTEGER := NULL;
+                seq INTEGER;
+                previous VARCHAR;
+            BEGIN
+                UPDATE inventory_ledger l SET warehouse_id = loc.warehouse_id
+                FROM location loc WHERE loc.id = l.location_id;
+
+                FOR entry IN SELECT * FROM inventory_ledger ORDER BY warehouse_id, id LOOP
+                    IF last_warehouse IS DISTINCT FROM entry.warehouse_id THEN
+                        last_warehouse := entry.warehouse_id;
+                        seq := 0;
+                        previous := NULL;
+                    END IF;
+                    seq := seq + 1;
+
+                    UPDATE inventory_ledger SET
+                        chain_seq = seq,
+                        prev_hash = previous,
+                        entry_hash = inventory_ledger_entry_hash(
+                            entry.id, entry.warehouse_id, seq, entry.item_id, entry.location_id,
+                            entry.quantity_change, entry.reason_type, entry.reference_type,
+                            entry.reference_id, entry.reverses_entry_id, entry.created_at, previous
+                        )
+                    WHERE id = entry.id
+                    RETURNING entry_hash INTO previous;
+                END LOOP;
+            END
+            $$
+            "#,
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .modify_column(
+                        ColumnDef::new(InventoryLedger::WarehouseId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .modify_column(
+                        ColumnDef::new(InventoryLedger::ChainSeq)
+                            .integer()
+                            .not_null(),
+                    )
+                    .modify_column(
+                        ColumnDef::new(InventoryLedger::EntryHash)
+                            .string()
+                            .not_null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_inventory_ledger_warehouse")
+                            .from_tbl(InventoryLedger::Table)
+                            .from_col(InventoryLedger::WarehouseId)
+                            .to_tbl(Warehouse::Table)
+                            .to_col(Warehouse::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // A second writer picking the same link fails here instead of forking the chain
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_ledger_warehouse_chain_seq")
+                    .table(InventoryLedger::Table)
+                    .col(InventoryLedger::WarehouseId)
+                    .col(InventoryLedger::ChainSeq)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // New rows are placed on their warehouse's chain whoever inserts them
+        db.execute_unprepared(
+            r#"
+            CREATE FUNCTION inventory_ledger_chain() RETURNS TRIGGER
+            LANGUAGE plpgsql AS $$
+            DECLARE
+                head RECORD;
+            BEGIN
+                SELECT warehouse_id INTO NEW.warehouse_id FROM location WHERE id = NEW.location_id;
+                PERFORM inventory_ledger_chain_lock(NEW.warehouse_id);
+
+                SELECT chain_seq, entry_hash INTO head FROM inventory_ledger
+                WHERE warehouse_id = NEW.warehouse_id
+                ORDER BY chain_seq DESC LIMIT 1;
+
+                NEW.chain_seq := COALESCE(head.chain_seq, 0) + 1;
+                NEW.prev_hash := head.entry_hash;
+                NEW.entry_hash := inventory_ledger_entry_hash(
+                    NEW.id, NEW.warehouse_id, NEW.chain_seq, NEW.item_id, NEW.location_id,
+                    NEW.quantity_change, NEW.reason_type, NEW.reference_type, NEW.reference_id,
+                    NEW.reverses_entry_id, NEW.created_at, NEW.prev_hash
+                );
+                RETURN NEW;
+            END
+            $$;
+
+            CREATE TRIGGER inventory_ledger_chain
+            BEFORE INSERT ON inventory_ledger
+            FOR EACH ROW EXECUTE FUNCTION inventory_ledger_chain();
+            "#,
+        )
+        .await?;
+
+        // The ledger is the source of truth: corrections are new entries, never edits
+        db.execute_unprepared(
+            r#"
+            CREATE FUNCTION inventory_ledger_append_only() RETURNS TRIGGER
+            LANGUAGE plpgsql AS $$
+            BEGIN
+                RAISE EXCEPTION 'inventory_ledger is append-only: % is not allowed', TG_OP
+                    USING ERRCODE = 'insufficient_privilege';
+            END
+            $$;
+
+            CREATE TRIGGER inventory_ledger_append_only
+            BEFORE UPDATE OR DELETE ON inventory_ledger
+            FOR EACH ROW EXECUTE FUNCTION inventory_ledger_append_only();
+
+            CREATE TRIGGER inventory_ledger_no_truncate
+            BEFORE TRUNCATE ON inventory_ledger
+            FOR EACH STATEMENT EXECUTE FUNCTION inventory_ledger_append_only();
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TRIGGER inventory_ledger_no_truncate ON inventory_ledger;
+                DROP TRIGGER inventory_ledger_append_only ON inventory_ledger;
+                DROP FUNCTION inventory_ledger_append_only();
+                DROP TRIGGER inventory_ledger_chain ON inventory_ledger;
+                DROP FUNCTION inventory_ledger_chain();
+                DROP FUNCTION inventory_ledger_chain_lock(INTEGER);
+                DROP FUNCTION inventory_ledger_entry_hash(
+                    INTEGER, INTEGER, INTEGER, INTEGER, INTEGER, INTEGER, VARCHAR, VARCHAR,
+                    INTEGER, INTEGER, TIMESTAMPTZ, VARCHAR
+                );
+                "#,
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_inventory_ledger_warehouse_chain_seq")
+                    .table(InventoryLedger::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .drop_foreign_key(Alias::new("fk_inventory_ledger_warehouse"))
+                    .drop_column(InventoryLedger::WarehouseId)
+                    .drop_column(InventoryLedger::ChainSeq)
+                    .drop_column(InventoryLedger::PrevHash)
+                    .drop_column(InventoryLedger::EntryHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    WarehouseId,
+    ChainSeq,
+    PrevHash,
+    EntryHash,
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // balance_after is a cache outside the hash chain. A transaction that sets
+        // inventory_ledger.repair_balance_after may rewrite it, and nothing else
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION inventory_ledger_append_only() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                DECLARE
+                    unchanged inventory_ledger%ROWTYPE;
+                BEGIN
+                    IF TG_OP = 'UPDATE'
+                        AND current_setting('inventory_ledger.repair_balance_after', true) = 'on' THEN
+                        unchanged := NEW;
+                        unchanged.balance_after := OLD.balance_after;
+                        IF unchanged IS NOT DISTINCT FROM OLD THEN
+                            RETURN NEW;
+                        END IF;
+                    END IF;
+
+                    RAISE EXCEPTION 'inventory_ledger is append-only: % is not allowed', TG_OP
+                        USING ERRCODE = 'insufficient_privilege';
+                END
+                $$
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION inventory_ledger_append_only() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                BEGIN
+                    RAISE EXCEPTION 'inventory_ledger is append-only: % is not allowed', TG_OP
+                        USING ERRCODE = 'insufficient_privilege';
+                END
+                $$
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
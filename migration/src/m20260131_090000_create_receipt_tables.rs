@@ -0,0 +1,208 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Receipt::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Receipt::Id))
+                    .col(string_uniq(Receipt::ReceiptNumber))
+                    .col(string_null(Receipt::Supplier))
+                    .col(string_null(Receipt::ReferenceNumber))
+                    .col(string_null(Receipt::ReceivedBy))
+                    .col(string(Receipt::Status).not_null().default("POSTED"))
+                    .col(
+                        timestamp_with_time_zone(Receipt::ReceivedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(Receipt::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(Receipt::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ReceiptLine::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ReceiptLine::Id))
+                    .col(integer(ReceiptLine::ReceiptId).not_null())
+                    .col(integer(ReceiptLine::ItemId).not_null())
+                    .col(integer(ReceiptLine::LocationId).not_null())
+                    .col(integer(ReceiptLine::Quantity).not_null())
+                    .col(integer_null(ReceiptLine::LedgerEntryId))
+                    .col(integer_null(ReceiptLine::HeldMovementId))
+                    .col(
+                        timestamp_with_time_zone(ReceiptLine::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_receipt_line_receipt")
+                            .from(ReceiptLine::Table, ReceiptLine::ReceiptId)
+                            .to(Receipt::Table, Receipt::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_receipt_line_item")
+                            .from(ReceiptLine::Table, ReceiptLine::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_receipt_line_location")
+                            .from(ReceiptLine::Table, ReceiptLine::LocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_receipt_line_ledger_entry")
+                            .from(ReceiptLine::Table, ReceiptLine::LedgerEntryId)
+                            .to(InventoryLedger::Table, InventoryLedger::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_receipt_line_held_movement")
+                            .from(ReceiptLine::Table, ReceiptLine::HeldMovementId)
+                            .to(HeldMovement::Table, HeldMovement::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_receipt_line_receipt_id")
+                    .table(ReceiptLine::Table)
+                    .col(ReceiptLine::ReceiptId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Receipts posted before this table existed only left a hash of their UUID in
+        // reference_id, and the ledger is append-only, so those entries keep it. Each hash gets a
+        // LEGACY-<hash> receipt whose lines point at its entries (or still-held movements)
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                INSERT INTO receipt (receipt_number, status, received_at, created_at, updated_at)
+                SELECT 'LEGACY-' || reference_id, 'POSTED', MIN(created_at), MIN(created_at), MAX(created_at)
+                FROM (
+                    SELECT reference_id, created_at FROM inventory_ledger
+                    WHERE reason_type = 'RECEIPT' AND reference_type = 'receipt'
+                    UNION ALL
+                    SELECT reference_id, created_at FROM held_movement
+                    WHERE reason_type = 'RECEIPT' AND reference_type = 'receipt' AND status = 'HELD'
+                ) legacy
+                WHERE reference_id IS NOT NULL
+                GROUP BY reference_id;
+
+                INSERT INTO receipt_line
+                    (receipt_id, item_id, location_id, quantity, ledger_entry_id, held_movement_id, created_at)
+                SELECT r.id, l.item_id, l.location_id, l.quantity_change, l.id, NULL, l.created_at
+                FROM inventory_ledger l
+                JOIN receipt r ON r.receipt_number = 'LEGACY-' || l.reference_id
+                WHERE l.reason_type = 'RECEIPT' AND l.reference_type = 'receipt'
+                UNION ALL
+                SELECT r.id, h.item_id, h.location_id, h.quantity_change, NULL, h.id, h.created_at
+                FROM held_movement h
+                JOIN receipt r ON r.receipt_number = 'LEGACY-' || h.reference_id
+                WHERE h.reason_type = 'RECEIPT' AND h.reference_type = 'receipt' AND h.status = 'HELD';
+
+                UPDATE receipt r SET status = 'REVERSED'
+                WHERE r.receipt_number LIKE 'LEGACY-%'
+                  AND NOT EXISTS (
+                      SELECT 1 FROM receipt_line rl
+                      WHERE rl.receipt_id = r.id
+                        AND (rl.ledger_entry_id IS NULL OR NOT EXISTS (
+                            SELECT 1 FROM inventory_ledger rv
+                            WHERE rv.reverses_entry_id = rl.ledger_entry_id
+                        ))
+                  );
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ReceiptLine::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Receipt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Receipt {
+    Table,
+    Id,
+    ReceiptNumber,
+    Supplier,
+    ReferenceNumber,
+    ReceivedBy,
+    Status,
+    ReceivedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ReceiptLine {
+    Table,
+    Id,
+    ReceiptId,
+    ItemId,
+    LocationId,
+    Quantity,
+    LedgerEntryId,
+    HeldMovementId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum HeldMovement {
+    Table,
+    Id,
+}
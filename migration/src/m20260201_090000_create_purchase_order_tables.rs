@@ -0,0 +1,247 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Supplier::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Supplier::Id))
+                    .col(string_uniq(Supplier::Code))
+                    .col(string(Supplier::Name).not_null())
+                    .col(boolean(Supplier::IsActive).not_null().default(true))
+                    .col(
+                        integer(Supplier::OverReceiptTolerancePercent)
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        integer(Supplier::UnderReceiptTolerancePercent)
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        timestamp_with_time_zone(Supplier::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(Supplier::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PurchaseOrder::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PurchaseOrder::Id))
+                    .col(string_uniq(PurchaseOrder::PoNumber))
+                    .col(integer(PurchaseOrder::SupplierId).not_null())
+                    .col(string(PurchaseOrder::Status).not_null())
+                    .col(integer(PurchaseOrder::OverReceiptTolerancePercent).not_null())
+                    .col(integer(PurchaseOrder::UnderReceiptTolerancePercent).not_null())
+                    .col(date_null(PurchaseOrder::ExpectedAt))
+                    .col(
+                        timestamp_with_time_zone(PurchaseOrder::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(PurchaseOrder::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_purchase_order_supplier")
+                            .from(PurchaseOrder::Table, PurchaseOrder::SupplierId)
+                            .to(Supplier::Table, Supplier::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PoLine::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PoLine::Id))
+                    .col(integer(PoLine::PurchaseOrderId).not_null())
+                    .col(integer(PoLine::ItemId).not_null())
+                    .col(integer(PoLine::QuantityOrdered).not_null())
+                    .col(integer(PoLine::QuantityReceived).not_null().default(0))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_po_line_purchase_order")
+                            .from(PoLine::Table, PoLine::PurchaseOrderId)
+                            .to(PurchaseOrder::Table, PurchaseOrder::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_po_line_item")
+                            .from(PoLine::Table, PoLine::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // One line per item, so receipts can name the line by SKU
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_po_line_purchase_order_item")
+                    .table(PoLine::Table)
+                    .col(PoLine::PurchaseOrderId)
+                    .col(PoLine::ItemId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Receipt::Table)
+                    .add_column(ColumnDef::new(Receipt::SupplierId).integer().null())
+                    .add_column(ColumnDef::new(Receipt::PurchaseOrderId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_receipt_supplier")
+                            .from_tbl(Receipt::Table)
+                            .from_col(Receipt::SupplierId)
+                            .to_tbl(Supplier::Table)
+                            .to_col(Supplier::Id),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_receipt_purchase_order")
+                            .from_tbl(Receipt::Table)
+                            .from_col(Receipt::PurchaseOrderId)
+                            .to_tbl(PurchaseOrder::Table)
+                            .to_col(PurchaseOrder::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReceiptLine::Table)
+                    .add_column(ColumnDef::new(ReceiptLine::PoLineId).integer().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_receipt_line_po_line")
+                            .from_tbl(ReceiptLine::Table)
+                            .from_col(ReceiptLine::PoLineId)
+                            .to_tbl(PoLine::Table)
+                            .to_col(PoLine::Id),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ReceiptLine::Table)
+                    .drop_foreign_key(Alias::new("fk_receipt_line_po_line"))
+                    .drop_column(ReceiptLine::PoLineId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Receipt::Table)
+                    .drop_foreign_key(Alias::new("fk_receipt_purchase_order"))
+                    .drop_foreign_key(Alias::new("fk_receipt_supplier"))
+                    .drop_column(Receipt::PurchaseOrderId)
+                    .drop_column(Receipt::SupplierId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PoLine::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(PurchaseOrder::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Supplier::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Supplier {
+    Table,
+    Id,
+    Code,
+    Name,
+    IsActive,
+    OverReceiptTolerancePercent,
+    UnderReceiptTolerancePercent,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PurchaseOrder {
+    Table,
+    Id,
+    PoNumber,
+    SupplierId,
+    Status,
+    OverReceiptTolerancePercent,
+    UnderReceiptTolerancePercent,
+    ExpectedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PoLine {
+    Table,
+    Id,
+    PurchaseOrderId,
+    ItemId,
+    QuantityOrdered,
+    QuantityReceived,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Receipt {
+    Table,
+    SupplierId,
+    PurchaseOrderId,
+}
+
+#[derive(DeriveIden)]
+enum ReceiptLine {
+    Table,
+    PoLineId,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Bulk receipts resolve a batch of SKUs and location codes per query. Not unique: nothing
+        // has enforced that so far, and existing data may have duplicates
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_item_sku")
+                    .table(Item::Table)
+                    .col(Item::Sku)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_location_code")
+                    .table(Location::Table)
+                    .col(Location::Code)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_location_code")
+                    .table(Location::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_item_sku")
+                    .table(Item::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Sku,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Code,
+}
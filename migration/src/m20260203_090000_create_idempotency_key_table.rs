@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdempotencyKey::Table)
+                    .if_not_exists()
+                    .col(pk_auto(IdempotencyKey::Id))
+                    .col(string_uniq(IdempotencyKey::Key))
+                    .col(string(IdempotencyKey::RequestMethod).not_null())
+                    .col(text(IdempotencyKey::RequestPath).not_null())
+                    .col(string_null(IdempotencyKey::RequestFingerprint))
+                    .col(integer_null(IdempotencyKey::ResponseStatus))
+                    .col(string_null(IdempotencyKey::ResponseContentType))
+                    .col(binary_null(IdempotencyKey::ResponseBody))
+                    .col(
+                        timestamp_with_time_zone(IdempotencyKey::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(IdempotencyKey::CompletedAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_key_created_at")
+                    .table(IdempotencyKey::Table)
+                    .col(IdempotencyKey::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IdempotencyKey::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IdempotencyKey {
+    Table,
+    Id,
+    Key,
+    RequestMethod,
+    RequestPath,
+    RequestFingerprint,
+    ResponseStatus,
+    ResponseContentType,
+    ResponseBody,
+    CreatedAt,
+    CompletedAt,
+}
@@ -0,0 +1,147 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportJob::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ImportJob::Id))
+                    .col(string(ImportJob::Kind).not_null())
+                    .col(string(ImportJob::Status).not_null())
+                    .col(string(ImportJob::FileFormat).not_null())
+                    .col(big_integer(ImportJob::FileSize).not_null())
+                    .col(boolean(ImportJob::Partial).not_null().default(false))
+                    .col(string_null(ImportJob::ReceiptNumber))
+                    .col(string_null(ImportJob::Supplier))
+                    .col(string_null(ImportJob::ReferenceNumber))
+                    .col(string_null(ImportJob::ReceivedBy))
+                    .col(integer(ImportJob::Attempts).not_null().default(0))
+                    .col(integer(ImportJob::RowsProcessed).not_null().default(0))
+                    .col(integer_null(ImportJob::TotalRows))
+                    .col(integer_null(ImportJob::SuccessfulRows))
+                    .col(json_binary_null(ImportJob::Errors))
+                    .col(text_null(ImportJob::RejectedCsv))
+                    .col(integer_null(ImportJob::ReceiptId))
+                    .col(text_null(ImportJob::Error))
+                    .col(
+                        timestamp_with_time_zone(ImportJob::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(ImportJob::StartedAt))
+                    .col(timestamp_with_time_zone_null(ImportJob::HeartbeatAt))
+                    .col(timestamp_with_time_zone_null(ImportJob::FinishedAt))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_import_job_receipt")
+                            .from(ImportJob::Table, ImportJob::ReceiptId)
+                            .to(Receipt::Table, Receipt::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_import_job_status")
+                    .table(ImportJob::Table)
+                    .col(ImportJob::Status)
+                    .col(ImportJob::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        // The uploaded file, split into chunks so it never has to be held in memory whole
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportJobChunk::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ImportJobChunk::Id))
+                    .col(integer(ImportJobChunk::ImportJobId).not_null())
+                    .col(integer(ImportJobChunk::Sequence).not_null())
+                    .col(binary(ImportJobChunk::Data).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_import_job_chunk_import_job")
+                            .from(ImportJobChunk::Table, ImportJobChunk::ImportJobId)
+                            .to(ImportJob::Table, ImportJob::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_import_job_chunk_sequence")
+                    .table(ImportJobChunk::Table)
+                    .col(ImportJobChunk::ImportJobId)
+                    .col(ImportJobChunk::Sequence)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImportJobChunk::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ImportJob::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportJob {
+    Table,
+    Id,
+    Kind,
+    Status,
+    FileFormat,
+    FileSize,
+    Partial,
+    ReceiptNumber,
+    Supplier,
+    ReferenceNumber,
+    ReceivedBy,
+    Attempts,
+    RowsProcessed,
+    TotalRows,
+    SuccessfulRows,
+    Errors,
+    RejectedCsv,
+    ReceiptId,
+    Error,
+    CreatedAt,
+    StartedAt,
+    HeartbeatAt,
+    FinishedAt,
+}
+
+#[derive(DeriveIden)]
+enum ImportJobChunk {
+    Table,
+    Id,
+    ImportJobId,
+    Sequence,
+    Data,
+}
+
+#[derive(DeriveIden)]
+enum Receipt {
+    Table,
+    Id,
+}
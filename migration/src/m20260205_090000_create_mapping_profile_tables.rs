@@ -0,0 +1,129 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MappingProfile::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MappingProfile::Id))
+                    .col(string_uniq(MappingProfile::Name))
+                    .col(text_null(MappingProfile::Description))
+                    .col(
+                        timestamp_with_time_zone(MappingProfile::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp_with_time_zone(MappingProfile::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MappingProfileField::Table)
+                    .if_not_exists()
+                    .col(pk_auto(MappingProfileField::Id))
+                    .col(integer(MappingProfileField::ProfileId).not_null())
+                    .col(string(MappingProfileField::Field).not_null())
+                    .col(string_null(MappingProfileField::SourceColumn))
+                    .col(string_null(MappingProfileField::DefaultValue))
+                    .col(boolean(MappingProfileField::Trim).not_null().default(true))
+                    .col(
+                        boolean(MappingProfileField::Uppercase)
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(integer_null(MappingProfileField::Multiplier))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_mapping_profile_field_profile")
+                            .from(MappingProfileField::Table, MappingProfileField::ProfileId)
+                            .to(MappingProfile::Table, MappingProfile::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mapping_profile_field_profile_field")
+                    .table(MappingProfileField::Table)
+                    .col(MappingProfileField::ProfileId)
+                    .col(MappingProfileField::Field)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Queued imports read their file with the profile named at upload
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportJob::Table)
+                    .add_column(ColumnDef::new(ImportJob::MappingProfile).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportJob::Table)
+                    .drop_column(ImportJob::MappingProfile)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MappingProfileField::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MappingProfile::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MappingProfile {
+    Table,
+    Id,
+    Name,
+    Description,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MappingProfileField {
+    Table,
+    Id,
+    ProfileId,
+    Field,
+    SourceColumn,
+    DefaultValue,
+    Trim,
+    Uppercase,
+    Multiplier,
+}
+
+#[derive(DeriveIden)]
+enum ImportJob {
+    Table,
+    MappingProfile,
+}
@@ -0,0 +1,185 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The dock or staging location receipts can be posted into before putaway
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .add_column(
+                        ColumnDef::new(Warehouse::ReceivingLocationId)
+                            .integer()
+                            .null(),
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_warehouse_receiving_location")
+                            .from_tbl(Warehouse::Table)
+                            .from_col(Warehouse::ReceivingLocationId)
+                            .to_tbl(Location::Table)
+                            .to_col(Location::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PutawayTask::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PutawayTask::Id))
+                    .col(integer(PutawayTask::WarehouseId).not_null())
+                    .col(integer(PutawayTask::ReceiptId).not_null())
+                    .col(integer_uniq(PutawayTask::ReceiptLineId).not_null())
+                    .col(integer(PutawayTask::ItemId).not_null())
+                    .col(integer(PutawayTask::FromLocationId).not_null())
+                    .col(integer_null(PutawayTask::SuggestedLocationId))
+                    .col(integer_null(PutawayTask::ToLocationId))
+                    .col(integer(PutawayTask::Quantity).not_null())
+                    .col(string(PutawayTask::Status).not_null())
+                    .col(
+                        timestamp_with_time_zone(PutawayTask::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(timestamp_with_time_zone_null(PutawayTask::CompletedAt))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_warehouse")
+                            .from(PutawayTask::Table, PutawayTask::WarehouseId)
+                            .to(Warehouse::Table, Warehouse::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_receipt")
+                            .from(PutawayTask::Table, PutawayTask::ReceiptId)
+                            .to(Receipt::Table, Receipt::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_receipt_line")
+                            .from(PutawayTask::Table, PutawayTask::ReceiptLineId)
+                            .to(ReceiptLine::Table, ReceiptLine::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_item")
+                            .from(PutawayTask::Table, PutawayTask::ItemId)
+                            .to(Item::Table, Item::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_from_location")
+                            .from(PutawayTask::Table, PutawayTask::FromLocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_suggested_location")
+                            .from(PutawayTask::Table, PutawayTask::SuggestedLocationId)
+                            .to(Location::Table, Location::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_task_to_location")
+                            .from(PutawayTask::Table, PutawayTask::ToLocationId)
+                            .to(Location::Table, Location::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_putaway_task_warehouse_status")
+                    .table(PutawayTask::Table)
+                    .col(PutawayTask::WarehouseId)
+                    .col(PutawayTask::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_putaway_task_receipt")
+                    .table(PutawayTask::Table)
+                    .col(PutawayTask::ReceiptId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PutawayTask::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Warehouse::Table)
+                    .drop_foreign_key(Alias::new("fk_warehouse_receiving_location"))
+                    .drop_column(Warehouse::ReceivingLocationId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+    ReceivingLocationId,
+}
+
+#[derive(DeriveIden)]
+enum PutawayTask {
+    Table,
+    Id,
+    WarehouseId,
+    ReceiptId,
+    ReceiptLineId,
+    ItemId,
+    FromLocationId,
+    SuggestedLocationId,
+    ToLocationId,
+    Quantity,
+    Status,
+    CreatedAt,
+    CompletedAt,
+}
+
+#[derive(DeriveIden)]
+enum Receipt {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ReceiptLine {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+}
@@ -0,0 +1,165 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Putaway skips blocked locations and locations without room for the stock
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Location::Table)
+                    .add_column(
+                        ColumnDef::new(Location::IsBlocked)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(ColumnDef::new(Location::Capacity).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The ordered rules a warehouse's putaway suggestions are made with
+        manager
+            .create_table(
+                Table::create()
+                    .table(PutawayRule::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PutawayRule::Id))
+                    .col(integer(PutawayRule::WarehouseId).not_null())
+                    .col(integer(PutawayRule::Sequence).not_null())
+                    .col(string(PutawayRule::RuleType).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_putaway_rule_warehouse")
+                            .from(PutawayRule::Table, PutawayRule::WarehouseId)
+                            .to(Warehouse::Table, Warehouse::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_putaway_rule_warehouse_sequence")
+                    .table(PutawayRule::Table)
+                    .col(PutawayRule::WarehouseId)
+                    .col(PutawayRule::Sequence)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // An item's home pick face in a warehouse, filled up to max_quantity
+        manager
+            .create_table(
+                Table::create()
+                    .table(PickFace::Table)
+                    .if_not_exists()
+                    .col(pk_auto(PickFace::Id))
+                    .col(integer(PickFace::ItemId).not_null())
+                    .col(integer(PickFace::WarehouseId).not_null())
+                    .col(integer(PickFace::LocationId).not_null())
+                    .col(integer(PickFace::MaxQuantity).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_pick_face_item")
+                            .from(PickFace::Table, PickFace::ItemId)
+                            .to(Item::Table, Item::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_pick_face_warehouse")
+                            .from(PickFace::Table, PickFace::WarehouseId)
+                            .to(Warehouse::Table, Warehouse::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_pick_face_location")
+                            .from(PickFace::Table, PickFace::LocationId)
+                            .to(Location::Table, Location::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pick_face_item_warehouse")
+                    .table(PickFace::Table)
+                    .col(PickFace::ItemId)
+                    .col(PickFace::WarehouseId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PickFace::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PutawayRule::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Location::Table)
+                    .drop_column(Location::IsBlocked)
+                    .drop_column(Location::Capacity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Location {
+    Table,
+    Id,
+    IsBlocked,
+    Capacity,
+}
+
+#[derive(DeriveIden)]
+enum PutawayRule {
+    Table,
+    Id,
+    WarehouseId,
+    Sequence,
+    RuleType,
+}
+
+#[derive(DeriveIden)]
+enum PickFace {
+    Table,
+    Id,
+    ItemId,
+    WarehouseId,
+    LocationId,
+    MaxQuantity,
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Item {
+    Table,
+    Id,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Why a held movement was REJECTED when its count closed, or DISCARDED before it did
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HeldMovement::Table)
+                    .add_column(ColumnDef::new(HeldMovement::ResolutionNote).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(HeldMovement::Table)
+                    .drop_column(HeldMovement::ResolutionNote)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum HeldMovement {
+    Table,
+    ResolutionNote,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The transaction that wrote each entry. Ids are taken in insert order but become visible in
+        // commit order, so ledger readers page on (txid, id) up to the oldest transaction still
+        // running instead. Entries written before this migration keep 0 and stay in id order.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .add_column(
+                        ColumnDef::new(InventoryLedger::Txid)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE inventory_ledger \
+                 ALTER COLUMN txid SET DEFAULT (pg_current_xact_id()::TEXT)::BIGINT",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_ledger_txid_id")
+                    .table(InventoryLedger::Table)
+                    .col(InventoryLedger::Txid)
+                    .col(InventoryLedger::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_inventory_ledger_txid_id")
+                    .table(InventoryLedger::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .drop_column(InventoryLedger::Txid)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    Id,
+    Txid,
+}
@@ -0,0 +1,138 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The last entry of each warehouse's chain, so appending reads one row by key instead of
+        // looking for the highest chain_seq in the ledger
+        manager
+            .create_table(
+                Table::create()
+                    .table(InventoryLedgerChainHead::Table)
+                    .if_not_exists()
+                    .col(
+                        integer(InventoryLedgerChainHead::WarehouseId)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(integer(InventoryLedgerChainHead::ChainSeq).not_null())
+                    .col(string(InventoryLedgerChainHead::EntryHash).not_null())
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_inventory_ledger_chain_head_warehouse")
+                            .from(
+                                InventoryLedgerChainHead::Table,
+                                InventoryLedgerChainHead::WarehouseId,
+                            )
+                            .to(Warehouse::Table, Warehouse::Id),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            r#"
+            LOCK TABLE inventory_ledger IN SHARE ROW EXCLUSIVE MODE;
+
+            INSERT INTO inventory_ledger_chain_head (warehouse_id, chain_seq, entry_hash)
+            SELECT DISTINCT ON (warehouse_id) warehouse_id, chain_seq, entry_hash
+            FROM inventory_ledger
+            ORDER BY warehouse_id, chain_seq DESC;
+            "#,
+        )
+        .await?;
+
+        db.execute_unprepared(
+            r#"
+            CREATE OR REPLACE FUNCTION inventory_ledger_chain() RETURNS TRIGGER
+            LANGUAGE plpgsql AS $$
+            DECLARE
+                head RECORD;
+            BEGIN
+                SELECT warehouse_id INTO NEW.warehouse_id FROM location WHERE id = NEW.location_id;
+                PERFORM inventory_ledger_chain_lock(NEW.warehouse_id);
+
+                SELECT chain_seq, entry_hash INTO head FROM inventory_ledger_chain_head
+                WHERE warehouse_id = NEW.warehouse_id;
+
+                NEW.chain_seq := COALESCE(head.chain_seq, 0) + 1;
+                NEW.prev_hash := head.entry_hash;
+                NEW.entry_hash := inventory_ledger_entry_hash(
+                    NEW.id, NEW.warehouse_id, NEW.chain_seq, NEW.item_id, NEW.location_id,
+                    NEW.quantity_change, NEW.reason_type, NEW.reference_type, NEW.reference_id,
+                    NEW.reverses_entry_id, NEW.created_at, NEW.prev_hash
+                );
+
+                INSERT INTO inventory_ledger_chain_head (warehouse_id, chain_seq, entry_hash)
+                VALUES (NEW.warehouse_id, NEW.chain_seq, NEW.entry_hash)
+                ON CONFLICT (warehouse_id) DO UPDATE
+                SET chain_seq = EXCLUDED.chain_seq, entry_hash = EXCLUDED.entry_hash;
+
+                RETURN NEW;
+            END
+            $$;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION inventory_ledger_chain() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                DECLARE
+                    head RECORD;
+                BEGIN
+                    SELECT warehouse_id INTO NEW.warehouse_id FROM location WHERE id = NEW.location_id;
+                    PERFORM inventory_ledger_chain_lock(NEW.warehouse_id);
+
+                    SELECT chain_seq, entry_hash INTO head FROM inventory_ledger
+                    WHERE warehouse_id = NEW.warehouse_id
+                    ORDER BY chain_seq DESC LIMIT 1;
+
+                    NEW.chain_seq := COALESCE(head.chain_seq, 0) + 1;
+                    NEW.prev_hash := head.entry_hash;
+                    NEW.entry_hash := inventory_ledger_entry_hash(
+                        NEW.id, NEW.warehouse_id, NEW.chain_seq, NEW.item_id, NEW.location_id,
+                        NEW.quantity_change, NEW.reason_type, NEW.reference_type, NEW.reference_id,
+                        NEW.reverses_entry_id, NEW.created_at, NEW.prev_hash
+                    );
+                    RETURN NEW;
+                END
+                $$;
+                "#,
+            )
+            .await?;
+
+        manager
+            .drop_table(
+                Table::drop()
+                    .table(InventoryLedgerChainHead::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedgerChainHead {
+    Table,
+    WarehouseId,
+    ChainSeq,
+    EntryHash,
+}
+
+#[derive(DeriveIden)]
+enum Warehouse {
+    Table,
+    Id,
+}
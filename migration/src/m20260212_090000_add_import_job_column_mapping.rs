@@ -0,0 +1,70 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The mapping profile's fields as they were at upload, so a job reads its file the same
+        // way however the profile changes before it runs
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportJob::Table)
+                    .add_column(
+                        ColumnDef::new(ImportJob::ColumnMapping)
+                            .json_binary()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE import_job
+                SET column_mapping = jsonb_build_object(
+                    'profile', mapping_profile.name,
+                    'fields', (
+                        SELECT COALESCE(jsonb_agg(jsonb_build_object(
+                            'field', field.field,
+                            'source_column', field.source_column,
+                            'default_value', field.default_value,
+                            'trim', field.trim,
+                            'uppercase', field.uppercase,
+                            'multiplier', field.multiplier
+                        ) ORDER BY field.id), '[]'::jsonb)
+                        FROM mapping_profile_field field
+                        WHERE field.profile_id = mapping_profile.id
+                    )
+                )
+                FROM mapping_profile
+                WHERE mapping_profile.name = import_job.mapping_profile
+                  AND import_job.status IN ('QUEUED', 'RUNNING')
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ImportJob::Table)
+                    .drop_column(ImportJob::ColumnMapping)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportJob {
+    Table,
+    ColumnMapping,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A CLOSED order closed short by hand stays closed when a receipt against it is reversed
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PurchaseOrder::Table)
+                    .add_column(
+                        ColumnDef::new(PurchaseOrder::ClosedManually)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Orders that closed with a line below its under-receipt threshold can only have been
+        // closed by hand
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE purchase_order
+                SET closed_manually = TRUE
+                WHERE status = 'CLOSED'
+                  AND EXISTS (
+                      SELECT 1 FROM po_line
+                      WHERE po_line.purchase_order_id = purchase_order.id
+                        AND po_line.quantity_received * 100
+                            < po_line.quantity_ordered
+                              * (100 - purchase_order.under_receipt_tolerance_percent)
+                  )
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(PurchaseOrder::Table)
+                    .drop_column(PurchaseOrder::ClosedManually)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PurchaseOrder {
+    Table,
+    ClosedManually,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // A line whose stock no single location has room for is split over several tasks
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE putaway_task DROP CONSTRAINT putaway_task_receipt_line_id_key",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_putaway_task_receipt_line")
+                    .table(PutawayTask::Table)
+                    .col(PutawayTask::ReceiptLineId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Room at a location counts the stock OPEN tasks are bringing it
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_putaway_task_suggested_location_status")
+                    .table(PutawayTask::Table)
+                    .col(PutawayTask::SuggestedLocationId)
+                    .col(PutawayTask::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_putaway_task_suggested_location_status")
+                    .table(PutawayTask::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_putaway_task_receipt_line")
+                    .table(PutawayTask::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Fails if a line has been split since
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE putaway_task ADD CONSTRAINT putaway_task_receipt_line_id_key UNIQUE (receipt_line_id)",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PutawayTask {
+    Table,
+    ReceiptLineId,
+    SuggestedLocationId,
+    Status,
+}
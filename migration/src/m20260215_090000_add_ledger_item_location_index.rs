@@ -0,0 +1,41 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // As-of stock sums the ledger rows of an item and location up to an instant
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_ledger_item_location_created")
+                    .table(InventoryLedger::Table)
+                    .col(InventoryLedger::ItemId)
+                    .col(InventoryLedger::LocationId)
+                    .col(InventoryLedger::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_inventory_ledger_item_location_created")
+                    .table(InventoryLedger::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    ItemId,
+    LocationId,
+    CreatedAt,
+}
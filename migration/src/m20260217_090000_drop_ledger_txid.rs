@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Ledger readers page on id again: holding entries back until every older transaction
+        // had finished stalled the feed behind any long-running transaction
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_inventory_ledger_txid_id")
+                    .table(InventoryLedger::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .drop_column(InventoryLedger::Txid)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The transactions that wrote existing entries are gone; they keep 0 and stay in id order
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InventoryLedger::Table)
+                    .add_column(
+                        ColumnDef::new(InventoryLedger::Txid)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "ALTER TABLE inventory_ledger \
+                 ALTER COLUMN txid SET DEFAULT (pg_current_xact_id()::TEXT)::BIGINT",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_inventory_ledger_txid_id")
+                    .table(InventoryLedger::Table)
+                    .col(InventoryLedger::Txid)
+                    .col(InventoryLedger::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum InventoryLedger {
+    Table,
+    Id,
+    Txid,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The parsed rows of a job's file, stored while it is read so that the import
+        // transaction only runs once the whole file is in
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportJobRow::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ImportJobRow::Id))
+                    .col(integer(ImportJobRow::ImportJobId).not_null())
+                    .col(integer(ImportJobRow::Line).not_null())
+                    .col(json_binary_null(ImportJobRow::Fields))
+                    .col(json_binary_null(ImportJobRow::Row))
+                    .col(text_null(ImportJobRow::Error))
+                    .foreign_key(
+                        &mut ForeignKey::create()
+                            .name("fk_import_job_row_import_job")
+                            .from(ImportJobRow::Table, ImportJobRow::ImportJobId)
+                            .to(ImportJob::Table, ImportJob::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_import_job_row_line")
+                    .table(ImportJobRow::Table)
+                    .col(ImportJobRow::ImportJobId)
+                    .col(ImportJobRow::Line)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImportJobRow::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportJob {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ImportJobRow {
+    Table,
+    Id,
+    ImportJobId,
+    Line,
+    Fields,
+    Row,
+    Error,
+}
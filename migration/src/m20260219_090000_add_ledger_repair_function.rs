@@ -0,0 +1,117 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // balance_after was rewritable by any transaction that set a setting anyone may set. Only
+        // inventory_ledger_repair may now rewrite it, a role nobody logs in as or is a member of:
+        // it owns the one function that does, which sets balance_after to the running sum and
+        // nothing else. The migration role is made a member just long enough to hand over the
+        // function, and keeps the right to call it. A superuser is not held back by any of this.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DO $$
+                BEGIN
+                    IF NOT EXISTS (
+                        SELECT 1 FROM pg_roles WHERE rolname = 'inventory_ledger_repair'
+                    ) THEN
+                        CREATE ROLE inventory_ledger_repair NOLOGIN;
+                    END IF;
+                END
+                $$;
+
+                GRANT SELECT, UPDATE (balance_after) ON inventory_ledger
+                    TO inventory_ledger_repair;
+
+                CREATE FUNCTION inventory_ledger_repair_balance_after() RETURNS BIGINT
+                LANGUAGE plpgsql SECURITY DEFINER SET search_path FROM CURRENT AS $$
+                DECLARE
+                    repaired BIGINT;
+                BEGIN
+                    WITH running AS (
+                        SELECT id, SUM(quantity_change) OVER (
+                                   PARTITION BY item_id, location_id ORDER BY created_at, id
+                               ) AS expected
+                        FROM inventory_ledger
+                    )
+                    UPDATE inventory_ledger l SET balance_after = r.expected
+                    FROM running r
+                    WHERE l.id = r.id AND l.balance_after IS DISTINCT FROM r.expected;
+
+                    GET DIAGNOSTICS repaired = ROW_COUNT;
+                    RETURN repaired;
+                END
+                $$;
+
+                GRANT inventory_ledger_repair TO CURRENT_USER;
+                ALTER FUNCTION inventory_ledger_repair_balance_after() OWNER TO inventory_ledger_repair;
+                REVOKE inventory_ledger_repair FROM CURRENT_USER;
+
+                REVOKE EXECUTE ON FUNCTION inventory_ledger_repair_balance_after() FROM PUBLIC;
+                GRANT EXECUTE ON FUNCTION inventory_ledger_repair_balance_after() TO CURRENT_USER;
+
+                CREATE OR REPLACE FUNCTION inventory_ledger_append_only() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                DECLARE
+                    unchanged inventory_ledger%ROWTYPE;
+                BEGIN
+                    IF TG_OP = 'UPDATE' AND current_user = 'inventory_ledger_repair' THEN
+                        unchanged := NEW;
+                        unchanged.balance_after := OLD.balance_after;
+                        IF unchanged IS NOT DISTINCT FROM OLD THEN
+                            RETURN NEW;
+                        END IF;
+                    END IF;
+
+                    RAISE EXCEPTION 'inventory_ledger is append-only: % is not allowed', TG_OP
+                        USING ERRCODE = 'insufficient_privilege';
+                END
+                $$;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The role is left in place, as other databases on the server may use it
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE OR REPLACE FUNCTION inventory_ledger_append_only() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                DECLARE
+                    unchanged inventory_ledger%ROWTYPE;
+                BEGIN
+                    IF TG_OP = 'UPDATE'
+                        AND current_setting('inventory_ledger.repair_balance_after', true) = 'on' THEN
+                        unchanged := NEW;
+                        unchanged.balance_after := OLD.balance_after;
+                        IF unchanged IS NOT DISTINCT FROM OLD THEN
+                            RETURN NEW;
+                        END IF;
+                    END IF;
+
+                    RAISE EXCEPTION 'inventory_ledger is append-only: % is not allowed', TG_OP
+                        USING ERRCODE = 'insufficient_privilege';
+                END
+                $$;
+
+                DROP FUNCTION inventory_ledger_repair_balance_after();
+
+                REVOKE SELECT, UPDATE (balance_after) ON inventory_ledger
+                    FROM inventory_ledger_repair;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // The ledger and its chain heads can only be appended to through the ledger's insert
+        // trigger. Privileges are revoked from everyone, the migration role included, on top of
+        // the append-only triggers; the chain heads only take writes made by the ledger trigger,
+        // each moving a chain on by one entry.
+        //
+        // The trust boundary is the role that owns the tables: it can grant itself the privileges
+        // back or disable the triggers, as can a superuser. This guards against the application's
+        // own statements going wrong. To have the database hold the line against a compromised
+        // application as well, run the migrations as the owner and the application as another
+        // role granted only SELECT and INSERT on inventory_ledger.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                REVOKE UPDATE, DELETE, TRUNCATE ON inventory_ledger FROM PUBLIC, CURRENT_USER;
+                REVOKE DELETE, TRUNCATE ON inventory_ledger_chain_head FROM PUBLIC, CURRENT_USER;
+
+                CREATE FUNCTION inventory_ledger_chain_head_guard() RETURNS TRIGGER
+                LANGUAGE plpgsql AS $$
+                BEGIN
+                    -- Depth 1 is a statement on the table itself; the ledger trigger's writes are
+                    -- one level further down. Its upsert fires the insert check before turning
+                    -- into an update of an existing head, which is checked in turn
+                    IF TG_OP = 'INSERT' AND pg_trigger_depth() > 1 AND (
+                        NEW.chain_seq = 1 OR EXISTS (
+                            SELECT 1 FROM inventory_ledger_chain_head
+                            WHERE warehouse_id = NEW.warehouse_id
+                        )
+                    ) THEN
+                        RETURN NEW;
+                    END IF;
+                    IF TG_OP = 'UPDATE' AND pg_trigger_depth() > 1
+                        AND NEW.warehouse_id = OLD.warehouse_id
+                        AND NEW.chain_seq = OLD.chain_seq + 1 THEN
+                        RETURN NEW;
+                    END IF;
+
+                    RAISE EXCEPTION 'inventory_ledger_chain_head is only written by the ledger: % is not allowed', TG_OP
+                        USING ERRCODE = 'insufficient_privilege';
+                END
+                $$;
+
+                CREATE TRIGGER inventory_ledger_chain_head_guard
+                BEFORE INSERT OR UPDATE OR DELETE ON inventory_ledger_chain_head
+                FOR EACH ROW EXECUTE FUNCTION inventory_ledger_chain_head_guard();
+
+                CREATE TRIGGER inventory_ledger_chain_head_no_truncate
+                BEFORE TRUNCATE ON inventory_ledger_chain_head
+                FOR EACH STATEMENT EXECUTE FUNCTION inventory_ledger_chain_head_guard();
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DROP TRIGGER inventory_ledger_chain_head_no_truncate ON inventory_ledger_chain_head;
+                DROP TRIGGER inventory_ledger_chain_head_guard ON inventory_ledger_chain_head;
+                DROP FUNCTION inventory_ledger_chain_head_guard();
+
+                GRANT DELETE, TRUNCATE ON inventory_ledger_chain_head TO CURRENT_USER;
+                GRANT UPDATE, DELETE, TRUNCATE ON inventory_ledger TO CURRENT_USER;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
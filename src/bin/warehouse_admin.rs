@@ -0,0 +1,150 @@
+use warehouse_api::db;
+use warehouse_api::services::{idempotency, inventory, ledger};
+
+const USAGE: &str = "Usage: warehouse_admin <command> [options]
+
+Commands:
+  prune-idempotency-keys [--dry-run] Delete Idempotency-Keys past their retention
+  reconcile-balances [--dry-run]     Rebuild inventory_balance from inventory_ledger and report drift
+  repair-balance-after [--dry-run]   Recompute the ledger's cached balance_after and report mismatches
+  verify-ledger [--warehouse <id>]   Walk the ledger hash chains and report the first broken link";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Load .env file if it exists (ignores errors if file doesn't exist)
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some(command) = args.first() else {
+        eprintln!("{}", USAGE);
+        std::process::exit(2);
+    };
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let db = db::create_connection(&database_url).await?;
+
+    match command.as_str() {
+        "prune-idempotency-keys" => {
+            let pruned = idempotency::prune_keys(&db, dry_run).await?;
+
+            if dry_run {
+                println!(
+                    "{} idempotency key(s) older than {} hours (dry run, nothing changed)",
+                    pruned,
+                    idempotency::KEY_RETENTION_HOURS
+                );
+            } else {
+                println!(
+                    "{} idempotency key(s) older than {} hours deleted",
+                    pruned,
+                    idempotency::KEY_RETENTION_HOURS
+                );
+            }
+        }
+        "reconcile-balances" => {
+            let drift = inventory::reconcile_balances(&db, dry_run).await?;
+
+            for row in &drift {
+                println!(
+                    "item_id={} location_id={} ledger={} balance={}",
+                    row.item_id,
+                    row.location_id,
+                    row.ledger_on_hand,
+                    row.balance_on_hand
+                        .map(|on_hand| on_hand.to_string())
+                        .unwrap_or_else(|| "missing".to_string())
+                );
+            }
+
+            match (drift.is_empty(), dry_run) {
+                (true, _) => println!("inventory_balance matches the ledger"),
+                (false, true) => println!(
+                    "{} balance(s) drifted (dry run, nothing changed)",
+                    drift.len()
+                ),
+                (false, false) => println!("{} balance(s) drifted and were rebuilt", drift.len()),
+            }
+        }
+        "repair-balance-after" => {
+            let mismatches = ledger::repair_balance_after(&db, dry_run).await?;
+
+            for row in &mismatches {
+                println!(
+                    "entry_id={} item_id={} location_id={} recorded={} expected={}",
+                    row.entry_id,
+                    row.item_id,
+                    row.location_id,
+                    row.recorded
+                        .map(|balance| balance.to_string())
+                        .unwrap_or_else(|| "missing".to_string()),
+                    row.expected
+                );
+            }
+
+            match (mismatches.is_empty(), dry_run) {
+                (true, _) => println!("balance_after matches the ledger"),
+                (false, true) => println!(
+                    "{} ledger entries mismatched (dry run, nothing changed)",
+                    mismatches.len()
+                ),
+                (false, false) => println!(
+                    "{} ledger entries mismatched and were repaired",
+                    mismatches.len()
+                ),
+            }
+        }
+        "verify-ledger" => {
+            let warehouse_id = match args.iter().position(|arg| arg == "--warehouse") {
+                Some(index) => match args.get(index + 1).and_then(|id| id.parse().ok()) {
+                    Some(id) => Some(id),
+                    None => {
+                        eprintln!("--warehouse needs a warehouse id\n\n{}", USAGE);
+                        std::process::exit(2);
+                    }
+                },
+                None => None,
+            };
+
+            let chains = ledger::verify_chains(&db, warehouse_id).await?;
+
+            let mut broken = 0;
+            for chain in &chains {
+                match &chain.first_broken {
+                    None => println!(
+                        "warehouse_id={} entries={} head_seq={} head_hash={} ok",
+                        chain.warehouse_id,
+                        chain.entries_checked,
+                        chain.head_seq.unwrap_or(0),
+                        chain.head_hash.as_deref().unwrap_or("-")
+                    ),
+                    Some(link) => {
+                        broken += 1;
+                        println!(
+                            "warehouse_id={} entries={} BROKEN at entry_id={} chain_seq={} field={} expected={} actual={}",
+                            chain.warehouse_id,
+                            chain.entries_checked,
+                            link.entry_id,
+                            link.chain_seq,
+                            link.field,
+                            link.expected.as_deref().unwrap_or("-"),
+                            link.actual.as_deref().unwrap_or("-")
+                        );
+                    }
+                }
+            }
+
+            if broken > 0 {
+                println!("{} of {} chain(s) broken", broken, chains.len());
+                std::process::exit(1);
+            }
+            println!("{} chain(s) verified", chains.len());
+        }
+        _ => {
+            eprintln!("Unknown command '{}'\n\n{}", command, USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,9 @@
 pub use sea_orm::DatabaseConnection;
-use sea_orm::{Database, DbErr};
+use sea_orm::{ConnectOptions, Database, DbErr};
 
 pub async fn create_connection(database_url: &str) -> Result<DatabaseConnection, DbErr> {
-    Database::connect(database_url).await
+    let mut options = ConnectOptions::new(database_url);
+    // Bulk imports send statements with thousands of parameters, too costly to log at info
+    options.sqlx_logging_level(log::LevelFilter::Debug);
+    Database::connect(options).await
 }
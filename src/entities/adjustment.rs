@@ -0,0 +1,20 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "adjustment")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub item_id: i32,
+    pub location_id: i32,
+    pub quantity_change: i32, // +5 found, -2 damaged
+    pub reason_code: String,  // DAMAGED | FOUND | LOST | EXPIRED | DATA_ENTRY_ERROR | OTHER
+    pub note: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
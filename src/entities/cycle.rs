@@ -7,7 +7,12 @@ pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub location_id: i32,
-    pub status: String, // OPEN | CLOSED
+    pub status: String, // SCHEDULED | OPEN | CLOSED
+    pub is_blind: bool, // hide expected_quantity from counters while OPEN
+    pub recount_threshold_percent: Option<i32>,
+    pub recount_threshold_units: Option<i32>,
+    pub approval_value_limit: Option<Decimal>,
+    pub scheduled_for: Option<Date>, // set for counts created by the scheduler
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
@@ -9,7 +9,11 @@ pub struct Model {
     pub cycle_count_id: i32,
     pub item_id: i32,
     pub expected_quantity: i32,
-    pub counted_quantity: i32,
+    pub counted_quantity: Option<i32>, // None until the item has been counted
+    pub status: String,                // PENDING | COUNTED | RECOUNT | PENDING_APPROVAL | APPROVED
+    pub count_attempts: i32,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "held_movement")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub cycle_count_id: i32, // count that froze the location
+    pub item_id: i32,
+    pub location_id: i32,
+    pub quantity_change: i32,
+    pub reason_type: String,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub status: String,               // HELD | APPLIED | DISCARDED
+    pub ledger_entry_id: Option<i32>, // set once applied
+    pub created_at: DateTimeWithTimeZone,
+    pub applied_at: Option<DateTimeWithTimeZone>, // when applied, rejected or discarded
+    pub resolution_note: Option<String>,          // why it was rejected or discarded
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "idempotency_key")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub key: String, // client-supplied Idempotency-Key header
+    pub request_method: String,
+    pub request_path: String, // path and query of the first request
+    pub request_fingerprint: Option<String>, // SHA-256 of method, path and body (multipart by field); null while in progress
+    pub response_status: Option<i32>,        // null while the first request is in progress
+    pub response_content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+    pub heartbeat_at: DateTimeWithTimeZone, // refreshed while the first request is in progress
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
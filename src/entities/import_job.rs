@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "import_job")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub kind: String,        // BULK_RECEIPT
+    pub status: String,      // QUEUED | RUNNING | SUCCEEDED | FAILED
+    pub file_format: String, // CSV | JSON | NDJSON | XLSX
+    pub file_size: i64,
+    pub partial: bool,                  // partial-commit mode
+    pub receipt_number: Option<String>, // as sent; set to the new receipt's number once committed
+    pub supplier: Option<String>,
+    pub reference_number: Option<String>,
+    pub received_by: Option<String>,
+    pub mapping_profile: Option<String>, // name of the profile the file is read with
+    #[serde(skip)]
+    pub column_mapping: Option<Json>, // the profile's fields as at upload; null for the standard layout
+    pub attempts: i32,
+    pub rows_processed: i32,
+    pub total_rows: Option<i32>, // null until finished
+    pub successful_rows: Option<i32>,
+    pub errors: Option<Json>, // [{ row, error }]
+    #[serde(skip)]
+    pub rejected_csv: Option<String>, // downloaded from GET /v1/jobs/:id/rejected-rows
+    pub receipt_id: Option<i32>,
+    pub error: Option<String>, // why a FAILED job imported nothing
+    pub created_at: DateTimeWithTimeZone,
+    pub started_at: Option<DateTimeWithTimeZone>,
+    pub heartbeat_at: Option<DateTimeWithTimeZone>, // last progress while RUNNING
+    pub finished_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
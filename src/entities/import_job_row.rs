@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "import_job_row")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub import_job_id: i32,
+    pub line: i32,             // row number in the uploaded file
+    pub fields: Option<Json>,  // the row's fields as written, kept for the rejected rows CSV
+    pub row: Option<Json>,     // the parsed row; null if it could not be read
+    pub error: Option<String>, // why the row could not be read
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
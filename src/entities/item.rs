@@ -11,6 +11,7 @@ pub struct Model {
     pub unit_of_measure: String,
     pub barcode: Option<String>,
     pub is_serialized: bool,
+    pub unit_cost: Option<Decimal>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,6 +21,7 @@ pub struct CreateItem {
     pub unit_of_measure: String,
     pub barcode: Option<String>,
     pub is_serialized: Option<bool>,
+    pub unit_cost: Option<Decimal>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -29,6 +31,7 @@ pub struct UpdateItem {
     pub unit_of_measure: Option<String>,
     pub barcode: Option<String>,
     pub is_serialized: Option<bool>,
+    pub unit_cost: Option<Decimal>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -10,10 +10,16 @@ pub struct Model {
     pub location_id: i32,
     pub quantity_change: i32,           // +10, -3
     pub balance_after: Option<i32>,     // optional (cached)
-    pub reason_type: String,            // RECEIPT | PICK | TRANSFER | ADJUSTMENT | COUNT
+    pub reason_type: String, // RECEIPT | PICK | TRANSFER | PUTAWAY | ADJUSTMENT | COUNT | REVERSAL
     pub reference_type: Option<String>, // order_id, transfer_id, count_id
     pub reference_id: Option<i32>,
+    pub reverses_entry_id: Option<i32>, // set on REVERSAL entries
     pub created_at: DateTimeWithTimeZone,
+    // Hash chain per warehouse, filled in by the database on insert
+    pub warehouse_id: i32,
+    pub chain_seq: i32,
+    pub prev_hash: Option<String>,
+    pub entry_hash: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
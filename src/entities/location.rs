@@ -13,6 +13,8 @@ pub struct Model {
     pub shelf: String,
     pub is_pickable: bool,
     pub is_bulk: bool,
+    pub is_blocked: bool,      // skipped by putaway
+    pub capacity: Option<i32>, // most units the location holds, across all items
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,6 +26,8 @@ pub struct CreateLocation {
     pub shelf: String,
     pub is_pickable: bool,
     pub is_bulk: bool,
+    pub is_blocked: bool,
+    pub capacity: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,6 +39,8 @@ pub struct UpdateLocation {
     pub shelf: Option<String>,
     pub is_pickable: Option<bool>,
     pub is_bulk: Option<bool>,
+    pub is_blocked: Option<bool>,
+    pub capacity: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
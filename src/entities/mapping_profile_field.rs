@@ -0,0 +1,21 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "mapping_profile_field")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub profile_id: i32,
+    pub field: String, // bulk receipt field: sku, location_code, quantity, ...
+    pub source_column: Option<String>, // file column it is read from
+    pub default_value: Option<String>, // used when the column is missing or empty
+    pub trim: bool,
+    pub uppercase: bool,
+    pub multiplier: Option<i32>, // quantity only
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
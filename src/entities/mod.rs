@@ -1,23 +1,59 @@
+pub mod adjustment;
 pub mod cycle;
 pub mod cycle_count_line;
+pub mod held_movement;
+pub mod idempotency_key;
+pub mod import_job;
+pub mod import_job_chunk;
+pub mod import_job_row;
+pub mod inventory_balance;
 pub mod item;
+pub mod item_abc_class;
 pub mod ledger;
 pub mod location;
+pub mod mapping_profile;
+pub mod mapping_profile_field;
 pub mod pick;
+pub mod pick_face;
 pub mod pick_line;
+pub mod po_line;
+pub mod purchase_order;
+pub mod putaway_rule;
+pub mod putaway_task;
+pub mod receipt;
+pub mod receipt_line;
 pub mod reservation;
+pub mod supplier;
 pub mod transfer;
 pub mod transfer_line;
 pub mod warehouse;
 
+pub use adjustment::Entity as Adjustment;
 pub use cycle::Entity as CycleCount;
 pub use cycle_count_line::Entity as CycleCountLine;
+pub use held_movement::Entity as HeldMovement;
+pub use idempotency_key::Entity as IdempotencyKey;
+pub use import_job::Entity as ImportJob;
+pub use import_job_chunk::Entity as ImportJobChunk;
+pub use import_job_row::Entity as ImportJobRow;
+pub use inventory_balance::Entity as InventoryBalance;
 pub use item::Entity as Item;
+pub use item_abc_class::Entity as ItemAbcClass;
 pub use ledger::Entity as InventoryLedger;
 pub use location::Entity as Location;
+pub use mapping_profile::Entity as MappingProfile;
+pub use mapping_profile_field::Entity as MappingProfileField;
 pub use pick::Entity as PickWave;
+pub use pick_face::Entity as PickFace;
 pub use pick_line::Entity as PickLine;
+pub use po_line::Entity as PoLine;
+pub use purchase_order::Entity as PurchaseOrder;
+pub use putaway_rule::Entity as PutawayRule;
+pub use putaway_task::Entity as PutawayTask;
+pub use receipt::Entity as Receipt;
+pub use receipt_line::Entity as ReceiptLine;
 pub use reservation::Entity as Reservation;
+pub use supplier::Entity as Supplier;
 pub use transfer::Entity as Transfer;
 pub use transfer_line::Entity as TransferLine;
 pub use warehouse::Entity as Warehouse;
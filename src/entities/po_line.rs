@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "po_line")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub purchase_order_id: i32,
+    pub item_id: i32,
+    pub quantity_ordered: i32,
+    pub quantity_received: i32, // net of reversed receipts
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,23 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "purchase_order")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub po_number: String,
+    pub supplier_id: i32,
+    pub status: String, // DRAFT | OPEN | PARTIALLY_RECEIVED | CLOSED | CANCELLED
+    pub closed_manually: bool, // CLOSED by close rather than by receiving
+    pub over_receipt_tolerance_percent: i32, // a line may receive up to ordered * (100 + this) / 100
+    pub under_receipt_tolerance_percent: i32, // a line is complete from ordered * (100 - this) / 100
+    pub expected_at: Option<Date>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
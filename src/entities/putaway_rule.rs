@@ -0,0 +1,18 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One of the ordered rules a warehouse's putaway suggestions are made with
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "putaway_rule")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub warehouse_id: i32,
+    pub sequence: i32,     // rules are applied in ascending sequence
+    pub rule_type: String, // PICK_FACE | CONSOLIDATE | BULK
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
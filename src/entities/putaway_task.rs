@@ -0,0 +1,26 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Moving a received line from the receiving location to where it is stored
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "putaway_task")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub warehouse_id: i32,
+    pub receipt_id: i32,
+    pub receipt_line_id: i32,
+    pub item_id: i32,
+    pub from_location_id: i32, // the warehouse's receiving location
+    pub suggested_location_id: Option<i32>, // None when no destination could be suggested
+    pub to_location_id: Option<i32>, // set once confirmed
+    pub quantity: i32,
+    pub status: String, // OPEN | COMPLETED | CANCELLED | REVERSED
+    pub created_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
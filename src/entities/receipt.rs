@@ -0,0 +1,24 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "receipt")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub receipt_number: String, // client-supplied or generated UUID; LEGACY-<hash> for old receipts
+    pub supplier: Option<String>,
+    pub supplier_id: Option<i32>,
+    pub purchase_order_id: Option<i32>, // set for receipts posted against a purchase order
+    pub reference_number: Option<String>, // supplier's delivery note, ASN, etc.
+    pub received_by: Option<String>,
+    pub status: String, // POSTED | REVERSED
+    pub received_at: DateTimeWithTimeZone,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "supplier")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub code: String,
+    pub name: String,
+    pub is_active: bool,
+    pub over_receipt_tolerance_percent: i32, // defaults for new purchase orders
+    pub under_receipt_tolerance_percent: i32,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSupplier {
+    pub code: String,
+    pub name: String,
+    pub is_active: Option<bool>,
+    pub over_receipt_tolerance_percent: Option<i32>,
+    pub under_receipt_tolerance_percent: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSupplier {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub is_active: Option<bool>,
+    pub over_receipt_tolerance_percent: Option<i32>,
+    pub under_receipt_tolerance_percent: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -8,7 +8,7 @@ pub struct Model {
     pub id: i32,
     pub from_location_id: i32,
     pub to_location_id: i32,
-    pub status: String, // DRAFT | IN_TRANSIT | COMPLETED | CANCELLED
+    pub status: String, // DRAFT | IN_TRANSIT | COMPLETED | CANCELLED | REVERSED
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
@@ -11,6 +11,10 @@ pub struct Model {
     pub timezone: String,
     #[sea_orm(column_name = "is_active")]
     pub is_active: bool,
+    pub count_freeze_mode: String, // REJECT | QUEUE
+    pub working_days: String,      // e.g. MON,TUE,WED,THU,FRI
+    pub allow_negative_stock: bool,
+    pub receiving_location_id: Option<i32>, // where receipts sent to the warehouse are posted
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,6 +23,9 @@ pub struct CreateWarehouse {
     pub address: String,
     pub timezone: String,
     pub is_active: Option<bool>,
+    pub count_freeze_mode: Option<String>,
+    pub working_days: Option<String>,
+    pub allow_negative_stock: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -27,6 +34,9 @@ pub struct UpdateWarehouse {
     pub address: Option<String>,
     pub timezone: Option<String>,
     pub is_active: Option<bool>,
+    pub count_freeze_mode: Option<String>,
+    pub working_days: Option<String>,
+    pub allow_negative_stock: Option<bool>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -3,13 +3,39 @@ pub mod v1;
 
 pub use health::health_check;
 // Re-export v1 handlers for backward compatibility
+pub use v1::abc_classification::{classify_items, get_abc_classes};
+pub use v1::adjustment::create_adjustment;
+pub use v1::cycle_count::{
+    approve_line, close_cycle_count, create_cycle_count, discard_held_movement, get_cycle_count,
+    get_held_movements, list_cycle_counts, record_counts, reject_line, review_cycle_count,
+    schedule_cycle_counts, start_cycle_count,
+};
+pub use v1::inventory::{export_snapshot, get_inventory};
 pub use v1::item::{create_item, delete_item, get_item, get_items, update_item};
+pub use v1::job::{get_job, get_rejected_rows};
+pub use v1::ledger::{get_ledger, verify_ledger};
 pub use v1::location::{
     create_location, delete_location, get_location, get_locations, update_location,
 };
-pub use v1::pick::{allocate_pick_wave, confirm_pick, create_pick_wave};
-pub use v1::receipt::{bulk_receipt, create_receipt, generate_sample};
-pub use v1::transfer::{complete_transfer, create_transfer};
+pub use v1::mapping_profile::{
+    create_mapping_profile, delete_mapping_profile, get_mapping_profile, get_mapping_profiles,
+    update_mapping_profile,
+};
+pub use v1::pick::{allocate_pick_wave, confirm_pick, create_pick_wave, reverse_pick_wave};
+pub use v1::purchase_order::{
+    cancel_purchase_order, close_purchase_order, create_purchase_order, get_purchase_order,
+    get_purchase_orders, open_purchase_order, receive_purchase_order,
+};
+pub use v1::putaway::{
+    confirm_putaway_task, create_pick_face, delete_pick_face, get_pick_faces, get_putaway_rules,
+    get_putaway_suggestions, get_putaway_task, get_putaway_tasks, update_pick_face,
+    update_putaway_rules,
+};
+pub use v1::receipt::{
+    bulk_receipt, create_receipt, generate_sample, get_receipt, get_receipts, reverse_receipt,
+};
+pub use v1::supplier::{create_supplier, get_supplier, get_suppliers, update_supplier};
+pub use v1::transfer::{complete_transfer, create_transfer, reverse_transfer};
 pub use v1::warehouse::{
     create_warehouse, delete_warehouse, get_warehouse, get_warehouses, update_warehouse,
 };
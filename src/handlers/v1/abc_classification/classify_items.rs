@@ -0,0 +1,68 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::abc_classification::{self, AbcOptions};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ClassifyItemsRequest {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: i32,
+
+    #[validate(range(min = 1, message = "lookback_days must be a positive integer"))]
+    pub lookback_days: Option<i64>,
+
+    #[validate(range(
+        min = 1,
+        max = 100,
+        message = "a_cutoff_percent must be between 1 and 100"
+    ))]
+    pub a_cutoff_percent: Option<i32>,
+
+    #[validate(range(
+        min = 1,
+        max = 100,
+        message = "b_cutoff_percent must be between 1 and 100"
+    ))]
+    pub b_cutoff_percent: Option<i32>,
+}
+
+pub async fn classify_items(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<ClassifyItemsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let defaults = AbcOptions::default();
+    let options = AbcOptions {
+        lookback_days: payload.lookback_days.unwrap_or(defaults.lookback_days),
+        a_cutoff_percent: payload
+            .a_cutoff_percent
+            .unwrap_or(defaults.a_cutoff_percent),
+        b_cutoff_percent: payload
+            .b_cutoff_percent
+            .unwrap_or(defaults.b_cutoff_percent),
+    };
+
+    let classes = abc_classification::classify_items(&db, payload.warehouse_id, options).await?;
+
+    let count_of = |class: &str| classes.iter().filter(|c| c.class == class).count();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "warehouse_id": payload.warehouse_id,
+            "summary": {
+                "A": count_of("A"),
+                "B": count_of("B"),
+                "C": count_of("C")
+            },
+            "items": classes
+        })),
+    ))
+}
@@ -0,0 +1,32 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::abc_classification;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetAbcClassesQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: i32,
+}
+
+pub async fn get_abc_classes(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetAbcClassesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let classes = abc_classification::get_classes(&db, query.warehouse_id).await?;
+
+    Ok((StatusCode::OK, Json(json!(classes))))
+}
@@ -0,0 +1,5 @@
+pub mod classify_items;
+pub mod get_abc_classes;
+
+pub use classify_items::classify_items;
+pub use get_abc_classes::get_abc_classes;
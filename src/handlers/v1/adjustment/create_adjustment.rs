@@ -0,0 +1,99 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::adjustment;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+use crate::utils::validation::validate_adjustment_reason_code;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateAdjustmentRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: String,
+
+    pub quantity: i32,
+
+    #[validate(custom(function = "validate_adjustment_reason_code"))]
+    pub reason_code: String,
+
+    #[validate(length(max = 1000, message = "Note must be at most 1000 characters"))]
+    pub note: Option<String>,
+}
+
+impl CreateAdjustmentRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.sku = self.sku.trim().to_string();
+        self.location_code = self.location_code.trim().to_string();
+        self.reason_code = self.reason_code.trim().to_uppercase();
+        self.note = self
+            .note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+        self
+    }
+}
+
+pub async fn create_adjustment(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreateAdjustmentRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let item = receipt::find_item_by_sku(&db, &request.sku)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Item with SKU '{}' not found", request.sku)))?;
+
+    let location = receipt::find_location_by_code(&db, &request.location_code)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Location with code '{}' not found",
+                request.location_code
+            ))
+        })?;
+
+    let result = adjustment::create_adjustment(
+        &db,
+        adjustment::NewAdjustment {
+            item_id: item.id,
+            location_id: location.id,
+            quantity_change: request.quantity,
+            reason_code: request.reason_code,
+            note: request.note,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "adjustment_id": result.adjustment.id,
+            "item_id": result.adjustment.item_id,
+            "location_id": result.adjustment.location_id,
+            "quantity": result.adjustment.quantity_change,
+            "reason_code": result.adjustment.reason_code,
+            "note": result.adjustment.note,
+            "ledger_entry_id": result.posted.ledger_entry_id(),
+            "held_movement_id": result.posted.held_movement_id(),
+            "created_at": result.adjustment.created_at
+        })),
+    ))
+}
@@ -0,0 +1,3 @@
+pub mod create_adjustment;
+
+pub use create_adjustment::create_adjustment;
@@ -0,0 +1,52 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::line_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ApproveLineRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Approved by must be between 1 and 100 characters"
+    ))]
+    pub approved_by: String,
+}
+
+impl ApproveLineRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.approved_by = self.approved_by.trim().to_string();
+        self
+    }
+}
+
+pub async fn approve_line(
+    State(db): State<DatabaseConnection>,
+    Path((id, line_id)): Path<(i32, i32)>,
+    Json(payload): Json<ApproveLineRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 || line_id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID and line ID must be positive integers",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let line = cycle_count::approve_line(&db, id, line_id, &request.approved_by).await?;
+
+    Ok((StatusCode::OK, Json(line_json(&line, false))))
+}
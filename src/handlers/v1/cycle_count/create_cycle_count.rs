@@ -0,0 +1,73 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+use validator::Validate;
+
+use super::cycle_count_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+use crate::utils::validation::validate_non_negative_amount;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateCycleCountRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: String,
+
+    pub is_blind: Option<bool>,
+
+    #[validate(range(min = 0, message = "Recount threshold percent cannot be negative"))]
+    pub recount_threshold_percent: Option<i32>,
+
+    #[validate(range(min = 0, message = "Recount threshold units cannot be negative"))]
+    pub recount_threshold_units: Option<i32>,
+
+    #[validate(custom(function = "validate_non_negative_amount"))]
+    pub approval_value_limit: Option<Decimal>,
+}
+
+impl CreateCycleCountRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.location_code = self.location_code.trim().to_string();
+        self
+    }
+}
+
+pub async fn create_cycle_count(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreateCycleCountRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let location = receipt::find_location_by_code(&db, &request.location_code)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Location with code '{}' not found",
+                request.location_code
+            ))
+        })?;
+
+    let options = cycle_count::CycleCountOptions {
+        is_blind: request.is_blind.unwrap_or(false),
+        recount_threshold_percent: request.recount_threshold_percent,
+        recount_threshold_units: request.recount_threshold_units,
+        approval_value_limit: request.approval_value_limit,
+    };
+
+    let result = cycle_count::open_cycle_count(&db, location.id, options).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(cycle_count_json(&result.cycle_count, &result.lines)),
+    ))
+}
@@ -0,0 +1,60 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+pub struct DiscardHeldMovementRequest {
+    #[validate(length(max = 1000, message = "Note must be at most 1000 characters"))]
+    pub note: Option<String>,
+}
+
+impl DiscardHeldMovementRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut note) = self.note {
+            *note = note.trim().to_string();
+        }
+        self
+    }
+}
+
+/// Drop a movement held by an open cycle count so it is not applied when the count closes
+pub async fn discard_held_movement(
+    State(db): State<DatabaseConnection>,
+    Path((id, movement_id)): Path<(i32, i32)>,
+    payload: Option<Json<DiscardHeldMovementRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 || movement_id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID and held movement ID must be positive integers",
+        ));
+    }
+
+    let request = payload
+        .map(|Json(payload)| payload)
+        .unwrap_or_default()
+        .trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let movement = cycle_count::discard_held_movement(
+        &db,
+        id,
+        movement_id,
+        request.note.filter(|note| !note.is_empty()),
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(json!(movement))))
+}
@@ -0,0 +1,29 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::cycle_count_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+pub async fn get_cycle_count(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID must be a positive integer",
+        ));
+    }
+
+    let result = cycle_count::get_cycle_count(&db, id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(cycle_count_json(&result.cycle_count, &result.lines)),
+    ))
+}
@@ -0,0 +1,29 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+pub async fn get_held_movements(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID must be a positive integer",
+        ));
+    }
+
+    let held_movements = cycle_count::list_held_movements(&db, id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "held_movements": held_movements })),
+    ))
+}
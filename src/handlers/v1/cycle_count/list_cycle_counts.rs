@@ -0,0 +1,49 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count_schedule;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ListCycleCountsQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: i32,
+
+    pub status: Option<String>,
+}
+
+pub async fn list_cycle_counts(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<ListCycleCountsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let status = query.status.map(|status| status.trim().to_uppercase());
+    let counts = cycle_count_schedule::list_cycle_counts(&db, query.warehouse_id, status).await?;
+
+    let counts: Vec<_> = counts
+        .iter()
+        .map(|count| {
+            json!({
+                "cycle_count_id": count.id,
+                "location_id": count.location_id,
+                "status": count.status,
+                "scheduled_for": count.scheduled_for,
+                "created_at": count.created_at,
+                "updated_at": count.updated_at
+            })
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(json!(counts))))
+}
@@ -0,0 +1,71 @@
+pub mod approve_line;
+pub mod close_cycle_count;
+pub mod create_cycle_count;
+pub mod discard_held_movement;
+pub mod get_cycle_count;
+pub mod get_held_movements;
+pub mod list_cycle_counts;
+pub mod record_counts;
+pub mod reject_line;
+pub mod review_cycle_count;
+pub mod schedule_cycle_counts;
+pub mod start_cycle_count;
+
+pub use approve_line::approve_line;
+pub use close_cycle_count::close_cycle_count;
+pub use create_cycle_count::create_cycle_count;
+pub use discard_held_movement::discard_held_movement;
+pub use get_cycle_count::get_cycle_count;
+pub use get_held_movements::get_held_movements;
+pub use list_cycle_counts::list_cycle_counts;
+pub use record_counts::record_counts;
+pub use reject_line::reject_line;
+pub use review_cycle_count::review_cycle_count;
+pub use schedule_cycle_counts::schedule_cycle_counts;
+pub use start_cycle_count::start_cycle_count;
+
+use serde_json::{Value, json};
+
+use crate::entities::{cycle, cycle_count_line};
+
+/// Shared JSON shape for a cycle count and its lines
+/// Blind counts hide expected quantities and variances until the count is closed
+pub(crate) fn cycle_count_json(
+    cycle_count: &cycle::Model,
+    lines: &[cycle_count_line::Model],
+) -> Value {
+    let hide_expected = cycle_count.is_blind && cycle_count.status == "OPEN";
+
+    json!({
+        "cycle_count_id": cycle_count.id,
+        "location_id": cycle_count.location_id,
+        "status": cycle_count.status,
+        "is_blind": cycle_count.is_blind,
+        "scheduled_for": cycle_count.scheduled_for,
+        "lines": lines.iter().map(|line| line_json(line, hide_expected)).collect::<Vec<_>>(),
+        "created_at": cycle_count.created_at,
+        "updated_at": cycle_count.updated_at
+    })
+}
+
+pub(crate) fn line_json(line: &cycle_count_line::Model, hide_expected: bool) -> Value {
+    let mut body = json!({
+        "id": line.id,
+        "item_id": line.item_id,
+        "counted_quantity": line.counted_quantity,
+        "status": line.status,
+        "count_attempts": line.count_attempts,
+        "approved_by": line.approved_by,
+        "approved_at": line.approved_at
+    });
+
+    if !hide_expected {
+        body["expected_quantity"] = line.expected_quantity.into();
+        body["variance"] = line
+            .counted_quantity
+            .map(|counted| counted - line.expected_quantity)
+            .into();
+    }
+
+    body
+}
@@ -0,0 +1,75 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::cycle_count_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct CountItemRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(range(min = 0, message = "Counted quantity cannot be negative"))]
+    pub counted_quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RecordCountsRequest {
+    #[validate(length(min = 1, message = "At least one item is required"))]
+    pub items: Vec<CountItemRequest>,
+}
+
+impl RecordCountsRequest {
+    pub fn trim_fields(mut self) -> Self {
+        for item in &mut self.items {
+            item.sku = item.sku.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn record_counts(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<RecordCountsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let count_items: Vec<cycle_count::CountItem> = request
+        .items
+        .iter()
+        .map(|item| cycle_count::CountItem {
+            sku: item.sku.clone(),
+            counted_quantity: item.counted_quantity,
+        })
+        .collect();
+
+    let result = cycle_count::record_counts(&db, id, count_items).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(cycle_count_json(&result.cycle_count, &result.lines)),
+    ))
+}
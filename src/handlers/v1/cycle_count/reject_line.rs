@@ -0,0 +1,26 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::line_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+pub async fn reject_line(
+    State(db): State<DatabaseConnection>,
+    Path((id, line_id)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 || line_id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID and line ID must be positive integers",
+        ));
+    }
+
+    let line = cycle_count::reject_line(&db, id, line_id).await?;
+
+    Ok((StatusCode::OK, Json(line_json(&line, false))))
+}
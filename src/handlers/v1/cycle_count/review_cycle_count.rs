@@ -0,0 +1,58 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::prelude::Decimal;
+use serde_json::json;
+
+use super::line_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+
+/// Supervisor view of a cycle count: expected quantities and variance values are always shown
+pub async fn review_cycle_count(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID must be a positive integer",
+        ));
+    }
+
+    let result = cycle_count::review_cycle_count(&db, id).await?;
+
+    let lines: Vec<serde_json::Value> = result
+        .lines
+        .iter()
+        .map(|review| {
+            let mut body = line_json(&review.line, false);
+            body["unit_cost"] = json!(review.unit_cost);
+            body["variance_value"] = json!(review.line.counted_quantity.zip(review.unit_cost).map(
+                |(counted, unit_cost)| {
+                    Decimal::from(counted - review.line.expected_quantity) * unit_cost
+                }
+            ));
+            body
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "cycle_count_id": result.cycle_count.id,
+            "location_id": result.cycle_count.location_id,
+            "status": result.cycle_count.status,
+            "is_blind": result.cycle_count.is_blind,
+            "recount_threshold_percent": result.cycle_count.recount_threshold_percent,
+            "recount_threshold_units": result.cycle_count.recount_threshold_units,
+            "approval_value_limit": result.cycle_count.approval_value_limit,
+            "lines": lines,
+            "created_at": result.cycle_count.created_at,
+            "updated_at": result.cycle_count.updated_at
+        })),
+    ))
+}
@@ -0,0 +1,67 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count_schedule::{self, ScheduleOptions};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ScheduleCycleCountsRequest {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: i32,
+
+    #[validate(range(min = 1, message = "interval_days_a must be a positive integer"))]
+    pub interval_days_a: Option<i64>,
+
+    #[validate(range(min = 1, message = "interval_days_b must be a positive integer"))]
+    pub interval_days_b: Option<i64>,
+
+    #[validate(range(min = 1, message = "interval_days_c must be a positive integer"))]
+    pub interval_days_c: Option<i64>,
+
+    #[validate(range(min = 1, max = 366, message = "horizon_days must be between 1 and 366"))]
+    pub horizon_days: Option<i64>,
+}
+
+pub async fn schedule_cycle_counts(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<ScheduleCycleCountsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let defaults = ScheduleOptions::default();
+    let options = ScheduleOptions {
+        interval_days_a: payload.interval_days_a.unwrap_or(defaults.interval_days_a),
+        interval_days_b: payload.interval_days_b.unwrap_or(defaults.interval_days_b),
+        interval_days_c: payload.interval_days_c.unwrap_or(defaults.interval_days_c),
+        horizon_days: payload.horizon_days.unwrap_or(defaults.horizon_days),
+    };
+
+    let scheduled =
+        cycle_count_schedule::schedule_cycle_counts(&db, payload.warehouse_id, options).await?;
+
+    let counts: Vec<_> = scheduled
+        .iter()
+        .map(|count| {
+            json!({
+                "cycle_count_id": count.id,
+                "location_id": count.location_id,
+                "status": count.status,
+                "scheduled_for": count.scheduled_for
+            })
+        })
+        .collect();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "warehouse_id": payload.warehouse_id,
+            "scheduled": counts.len(),
+            "cycle_counts": counts
+        })),
+    ))
+}
@@ -0,0 +1,61 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::prelude::Decimal;
+use serde::Deserialize;
+use validator::Validate;
+
+use super::cycle_count_json;
+use crate::db::DatabaseConnection;
+use crate::services::cycle_count;
+use crate::utils::error::AppError;
+use crate::utils::validation::validate_non_negative_amount;
+
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+pub struct StartCycleCountRequest {
+    pub is_blind: Option<bool>,
+
+    #[validate(range(min = 0, message = "Recount threshold percent cannot be negative"))]
+    pub recount_threshold_percent: Option<i32>,
+
+    #[validate(range(min = 0, message = "Recount threshold units cannot be negative"))]
+    pub recount_threshold_units: Option<i32>,
+
+    #[validate(custom(function = "validate_non_negative_amount"))]
+    pub approval_value_limit: Option<Decimal>,
+}
+
+pub async fn start_cycle_count(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    payload: Option<Json<StartCycleCountRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Cycle count ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.map(|Json(request)| request).unwrap_or_default();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let options = cycle_count::CycleCountOptions {
+        is_blind: request.is_blind.unwrap_or(false),
+        recount_threshold_percent: request.recount_threshold_percent,
+        recount_threshold_units: request.recount_threshold_units,
+        approval_value_limit: request.approval_value_limit,
+    };
+
+    let result = cycle_count::start_cycle_count(&db, id, options).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(cycle_count_json(&result.cycle_count, &result.lines)),
+    ))
+}
@@ -0,0 +1,111 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use sea_orm::EntityTrait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::entities::warehouse;
+use crate::services::inventory::{self, StockFilter};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportSnapshotQuery {
+    pub as_of: Option<DateTimeWithTimeZone>,
+    pub format: Option<String>,
+}
+
+/// Export on-hand per item and location for a whole warehouse at an instant (default: now)
+pub async fn export_snapshot(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Query(query): Query<ExportSnapshotQuery>,
+) -> Result<Response, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Warehouse ID must be a positive integer",
+        ));
+    }
+
+    let format = query
+        .format
+        .map(|format| format.trim().to_lowercase())
+        .unwrap_or_else(|| "csv".to_string());
+    if format != "csv" && format != "json" {
+        return Err(AppError::bad_request(
+            "format must be either 'csv' or 'json'",
+        ));
+    }
+
+    warehouse::Entity::find_by_id(id)
+        .one(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Warehouse with id {} not found", id)))?;
+
+    let as_of = query
+        .as_of
+        .unwrap_or_else(|| chrono::Utc::now().fixed_offset());
+
+    let filter = StockFilter {
+        warehouse_id: Some(id),
+        only_nonzero: true,
+        as_of: Some(as_of),
+        ..Default::default()
+    };
+    let lines = inventory::query_stock(&db, &filter).await?;
+
+    if format == "json" {
+        return Ok((
+            StatusCode::OK,
+            Json(json!({
+                "warehouse_id": id,
+                "as_of": as_of,
+                "lines": lines.iter().map(|line| json!({
+                    "sku": line.sku,
+                    "item_name": line.item_name,
+                    "location_code": line.location_code,
+                    "on_hand": line.on_hand
+                })).collect::<Vec<_>>()
+            })),
+        )
+            .into_response());
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["sku", "item_name", "location_code", "on_hand"])
+        .map_err(|e| AppError::internal(format!("Failed to write snapshot: {}", e)))?;
+    for line in &lines {
+        writer
+            .write_record([
+                line.sku.as_str(),
+                line.item_name.as_str(),
+                line.location_code.as_str(),
+                &line.on_hand.to_string(),
+            ])
+            .map_err(|e| AppError::internal(format!("Failed to write snapshot: {}", e)))?;
+    }
+    let csv_content = writer
+        .into_inner()
+        .map_err(|e| AppError::internal(format!("Failed to write snapshot: {}", e)))?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"inventory_snapshot_{}_{}.csv\"",
+                id,
+                as_of.format("%Y%m%dT%H%M%S")
+            ),
+        ),
+    ];
+
+    Ok((StatusCode::OK, headers, csv_content).into_response())
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::inventory::{self, StockFilter};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetInventoryQuery {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: Option<String>,
+
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+
+    pub only_nonzero: Option<bool>,
+
+    pub as_of: Option<DateTimeWithTimeZone>,
+}
+
+impl GetInventoryQuery {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut sku) = self.sku {
+            *sku = sku.trim().to_string();
+        }
+        if let Some(ref mut location_code) = self.location_code {
+            *location_code = location_code.trim().to_string();
+        }
+        self
+    }
+}
+
+struct Totals {
+    on_hand: i64,
+    reserved: Option<i64>,
+    available: Option<i64>,
+}
+
+impl Default for Totals {
+    fn default() -> Self {
+        Self {
+            on_hand: 0,
+            reserved: Some(0),
+            available: Some(0),
+        }
+    }
+}
+
+impl Totals {
+    fn add(&mut self, line: &inventory::StockLine) {
+        self.on_hand += line.on_hand;
+        self.reserved = self.reserved.zip(line.reserved).map(|(a, b)| a + b);
+        self.available = self.available.zip(line.available).map(|(a, b)| a + b);
+    }
+}
+
+pub async fn get_inventory(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetInventoryQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = query.trim_fields();
+
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let filter = StockFilter {
+        sku: query.sku,
+        location_code: query.location_code,
+        warehouse_id: query.warehouse_id,
+        only_nonzero: query.only_nonzero.unwrap_or(false),
+        as_of: query.as_of,
+    };
+
+    let lines = inventory::query_stock(&db, &filter).await?;
+
+    // Roll the per-location lines up per item and per warehouse
+    let mut by_item: BTreeMap<(String, i32), Totals> = BTreeMap::new();
+    let mut by_warehouse: BTreeMap<i32, Totals> = BTreeMap::new();
+    for line in &lines {
+        by_item
+            .entry((line.sku.clone(), line.item_id))
+            .or_default()
+            .add(line);
+        by_warehouse.entry(line.warehouse_id).or_default().add(line);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "as_of": query.as_of,
+            "lines": lines.iter().map(|line| json!({
+                "item_id": line.item_id,
+                "sku": line.sku,
+                "item_name": line.item_name,
+                "location_id": line.location_id,
+                "location_code": line.location_code,
+                "warehouse_id": line.warehouse_id,
+                "on_hand": line.on_hand,
+                "reserved": line.reserved,
+                "available": line.available
+            })).collect::<Vec<_>>(),
+            "totals_by_item": by_item.iter().map(|((sku, item_id), totals)| json!({
+                "item_id": item_id,
+                "sku": sku,
+                "on_hand": totals.on_hand,
+                "reserved": totals.reserved,
+                "available": totals.available
+            })).collect::<Vec<_>>(),
+            "totals_by_warehouse": by_warehouse.iter().map(|(warehouse_id, totals)| json!({
+                "warehouse_id": warehouse_id,
+                "on_hand": totals.on_hand,
+                "reserved": totals.reserved,
+                "available": totals.available
+            })).collect::<Vec<_>>()
+        })),
+    ))
+}
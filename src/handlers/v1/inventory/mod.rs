@@ -0,0 +1,5 @@
+pub mod export_snapshot;
+pub mod get_inventory;
+
+pub use export_snapshot::export_snapshot;
+pub use get_inventory::get_inventory;
@@ -1,4 +1,5 @@
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::prelude::Decimal;
 use sea_orm::{ActiveModelTrait, Set};
 use serde::Deserialize;
 use serde_json::json;
@@ -7,6 +8,7 @@ use validator::Validate;
 use crate::db::DatabaseConnection;
 use crate::entities::item::{ActiveModel, CreateItem};
 use crate::utils::error::AppError;
+use crate::utils::validation::validate_non_negative_amount;
 
 #[derive(Debug, Clone, Deserialize, Validate)]
 pub struct CreateItemRequest {
@@ -35,6 +37,9 @@ pub struct CreateItemRequest {
     pub barcode: Option<String>,
 
     pub is_serialized: Option<bool>,
+
+    #[validate(custom(function = "validate_non_negative_amount"))]
+    pub unit_cost: Option<Decimal>,
 }
 
 impl CreateItemRequest {
@@ -65,6 +70,7 @@ pub async fn create_item(
         unit_of_measure: request.unit_of_measure,
         barcode: request.barcode,
         is_serialized: request.is_serialized,
+        unit_cost: request.unit_cost,
     };
 
     let mut active_model = <ActiveModel as sea_orm::ActiveModelTrait>::default();
@@ -73,6 +79,7 @@ pub async fn create_item(
     active_model.unit_of_measure = Set(create_dto.unit_of_measure);
     active_model.barcode = Set(create_dto.barcode);
     active_model.is_serialized = Set(create_dto.is_serialized.unwrap_or(false));
+    active_model.unit_cost = Set(create_dto.unit_cost);
 
     let item = active_model
         .insert(&db)
@@ -4,6 +4,7 @@ use axum::{
     http::StatusCode,
     response::IntoResponse,
 };
+use sea_orm::prelude::Decimal;
 use sea_orm::{ActiveModelTrait, EntityTrait, Set};
 use serde::Deserialize;
 use serde_json::json;
@@ -12,6 +13,7 @@ use validator::Validate;
 use crate::db::DatabaseConnection;
 use crate::entities::item::{ActiveModel, Entity};
 use crate::utils::error::AppError;
+use crate::utils::validation::validate_non_negative_amount;
 
 #[derive(Debug, Clone, Deserialize, Validate)]
 pub struct UpdateItemRequest {
@@ -40,6 +42,9 @@ pub struct UpdateItemRequest {
     pub barcode: Option<String>,
 
     pub is_serialized: Option<bool>,
+
+    #[validate(custom(function = "validate_non_negative_amount"))]
+    pub unit_cost: Option<Decimal>,
 }
 
 impl UpdateItemRequest {
@@ -98,6 +103,9 @@ pub async fn update_item(
     if let Some(is_serialized) = request.is_serialized {
         active_model.is_serialized = Set(is_serialized);
     }
+    if let Some(unit_cost) = request.unit_cost {
+        active_model.unit_cost = Set(Some(unit_cost));
+    }
 
     let updated = active_model
         .update(&db)
@@ -0,0 +1,31 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::import_job;
+use crate::utils::error::AppError;
+
+pub async fn get_job(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request("Job ID must be a positive integer"));
+    }
+
+    let job = import_job::get_job(&db, id).await?;
+    let rejected_csv_url = job
+        .rejected_csv
+        .as_ref()
+        .map(|_| format!("/v1/jobs/{}/rejected-rows", job.id));
+
+    let mut body = json!(job);
+    body["rejected_csv_url"] = json!(rejected_csv_url);
+
+    Ok((StatusCode::OK, Json(body)))
+}
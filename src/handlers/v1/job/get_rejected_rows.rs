@@ -0,0 +1,34 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::db::DatabaseConnection;
+use crate::services::import_job;
+use crate::utils::error::AppError;
+
+/// Download the rows a partial-mode job rejected, as a CSV to fix and upload again
+pub async fn get_rejected_rows(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<Response, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request("Job ID must be a positive integer"));
+    }
+
+    let job = import_job::get_job(&db, id).await?;
+    let csv_content = job
+        .rejected_csv
+        .ok_or_else(|| AppError::not_found(format!("Job with id {} has no rejected rows", id)))?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"import_job_{}_rejected.csv\"", id),
+        ),
+    ];
+
+    Ok((StatusCode::OK, headers, csv_content).into_response())
+}
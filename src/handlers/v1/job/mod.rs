@@ -0,0 +1,5 @@
+pub mod get_job;
+pub mod get_rejected_rows;
+
+pub use get_job::get_job;
+pub use get_rejected_rows::get_rejected_rows;
@@ -0,0 +1,135 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::ledger::{self, LedgerFilter};
+use crate::services::receipt;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetLedgerQuery {
+    #[validate(range(min = 1, message = "item_id must be a positive integer"))]
+    pub item_id: Option<i32>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: Option<String>,
+
+    #[validate(range(min = 1, message = "location_id must be a positive integer"))]
+    pub location_id: Option<i32>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: Option<String>,
+
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+
+    pub reason_type: Option<String>,
+
+    pub reference_type: Option<String>,
+
+    pub reference_id: Option<i32>,
+
+    pub from: Option<DateTimeWithTimeZone>,
+
+    pub to: Option<DateTimeWithTimeZone>,
+
+    #[validate(range(min = 0, message = "after must not be negative"))]
+    pub after: Option<i32>,
+
+    #[validate(range(min = 1, max = 1000, message = "limit must be between 1 and 1000"))]
+    pub limit: Option<u64>,
+}
+
+impl GetLedgerQuery {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut sku) = self.sku {
+            *sku = sku.trim().to_string();
+        }
+        if let Some(ref mut location_code) = self.location_code {
+            *location_code = location_code.trim().to_string();
+        }
+        if let Some(ref mut reason_type) = self.reason_type {
+            *reason_type = reason_type.trim().to_uppercase();
+        }
+        if let Some(ref mut reference_type) = self.reference_type {
+            *reference_type = reference_type.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn get_ledger(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetLedgerQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = query.trim_fields();
+
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let mut item_id = query.item_id;
+    if let Some(sku) = &query.sku {
+        let item = receipt::find_item_by_sku(&db, sku)
+            .await?
+            .ok_or_else(|| AppError::not_found(format!("Item with SKU '{}' not found", sku)))?;
+        if item_id.is_some_and(|id| id != item.id) {
+            return Err(AppError::bad_request(
+                "item_id and sku refer to different items",
+            ));
+        }
+        item_id = Some(item.id);
+    }
+
+    let mut location_id = query.location_id;
+    if let Some(location_code) = &query.location_code {
+        let location = receipt::find_location_by_code(&db, location_code)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with code '{}' not found", location_code))
+            })?;
+        if location_id.is_some_and(|id| id != location.id) {
+            return Err(AppError::bad_request(
+                "location_id and location_code refer to different locations",
+            ));
+        }
+        location_id = Some(location.id);
+    }
+
+    let filter = LedgerFilter {
+        item_id,
+        location_id,
+        warehouse_id: query.warehouse_id,
+        reason_type: query.reason_type,
+        reference_type: query.reference_type,
+        reference_id: query.reference_id,
+        from: query.from,
+        to: query.to,
+    };
+
+    let page = ledger::find_entries(&db, &filter, query.after, query.limit.unwrap_or(100)).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "entries": page.entries,
+            "next_cursor": page.next_cursor
+        })),
+    ))
+}
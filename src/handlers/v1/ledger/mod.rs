@@ -0,0 +1,5 @@
+pub mod get_ledger;
+pub mod verify_ledger;
+
+pub use get_ledger::get_ledger;
+pub use verify_ledger::verify_ledger;
@@ -0,0 +1,51 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::ledger;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct VerifyLedgerQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+}
+
+/// Walk the ledger hash chains and report the first broken link of each
+pub async fn verify_ledger(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<VerifyLedgerQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let chains = ledger::verify_chains(&db, query.warehouse_id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "valid": chains.iter().all(|chain| chain.first_broken.is_none()),
+            "chains": chains.iter().map(|chain| json!({
+                "warehouse_id": chain.warehouse_id,
+                "entries_checked": chain.entries_checked,
+                "head_seq": chain.head_seq,
+                "head_hash": chain.head_hash,
+                "first_broken": chain.first_broken.as_ref().map(|link| json!({
+                    "entry_id": link.entry_id,
+                    "chain_seq": link.chain_seq,
+                    "field": link.field,
+                    "expected": link.expected,
+                    "actual": link.actual
+                }))
+            })).collect::<Vec<_>>()
+        })),
+    ))
+}
@@ -39,6 +39,10 @@ pub struct CreateLocationRequest {
 
     pub is_pickable: bool,
     pub is_bulk: bool,
+    pub is_blocked: Option<bool>,
+
+    #[validate(range(min = 1, message = "Capacity must be a positive integer"))]
+    pub capacity: Option<i32>,
 }
 
 impl CreateLocationRequest {
@@ -69,6 +73,8 @@ pub async fn create_location(
         shelf: request.shelf,
         is_pickable: request.is_pickable,
         is_bulk: request.is_bulk,
+        is_blocked: request.is_blocked.unwrap_or(false),
+        capacity: request.capacity,
     };
 
     let mut active_model = <ActiveModel as sea_orm::ActiveModelTrait>::default();
@@ -79,6 +85,8 @@ pub async fn create_location(
     active_model.shelf = Set(create_dto.shelf);
     active_model.is_pickable = Set(create_dto.is_pickable);
     active_model.is_bulk = Set(create_dto.is_bulk);
+    active_model.is_blocked = Set(create_dto.is_blocked);
+    active_model.capacity = Set(create_dto.capacity);
 
     let location = active_model
         .insert(&db)
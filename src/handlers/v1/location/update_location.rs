@@ -44,6 +44,13 @@ pub struct UpdateLocationRequest {
 
     pub is_pickable: Option<bool>,
     pub is_bulk: Option<bool>,
+    pub is_blocked: Option<bool>,
+
+    #[validate(range(min = 1, message = "Capacity must be a positive integer"))]
+    pub capacity: Option<i32>,
+
+    /// Removes the capacity, leaving the location unlimited
+    pub clear_capacity: Option<bool>,
 }
 
 impl UpdateLocationRequest {
@@ -81,6 +88,13 @@ pub async fn update_location(
         .validate()
         .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
 
+    let clear_capacity = request.clear_capacity.unwrap_or(false);
+    if clear_capacity && request.capacity.is_some() {
+        return Err(AppError::bad_request(
+            "Send either capacity or clear_capacity, not both",
+        ));
+    }
+
     let location = Entity::find_by_id(id)
         .one(&db)
         .await
@@ -110,6 +124,15 @@ pub async fn update_location(
     if let Some(is_bulk) = request.is_bulk {
         active_model.is_bulk = Set(is_bulk);
     }
+    if let Some(is_blocked) = request.is_blocked {
+        active_model.is_blocked = Set(is_blocked);
+    }
+    if let Some(capacity) = request.capacity {
+        active_model.capacity = Set(Some(capacity));
+    }
+    if clear_capacity {
+        active_model.capacity = Set(None);
+    }
 
     let updated = active_model
         .update(&db)
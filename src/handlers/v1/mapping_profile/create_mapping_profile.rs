@@ -0,0 +1,60 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::{MappingFieldRequest, mapping_profile_json};
+use crate::db::DatabaseConnection;
+use crate::services::mapping_profile;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateMappingProfileRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[validate(length(max = 1000, message = "Description must be at most 1000 characters"))]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1, message = "At least one field is required"))]
+    pub fields: Vec<MappingFieldRequest>,
+}
+
+impl CreateMappingProfileRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.name = self.name.trim().to_string();
+        if let Some(ref mut description) = self.description {
+            *description = description.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn create_mapping_profile(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreateMappingProfileRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let fields = request
+        .fields
+        .into_iter()
+        .map(MappingFieldRequest::into_field_mapping)
+        .collect();
+    let profile = mapping_profile::create_profile(
+        &db,
+        request.name,
+        request.description.filter(|d| !d.is_empty()),
+        fields,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(mapping_profile_json(&profile))))
+}
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::db::DatabaseConnection;
+use crate::services::mapping_profile;
+use crate::utils::error::AppError;
+
+pub async fn delete_mapping_profile(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Mapping profile ID must be a positive integer",
+        ));
+    }
+
+    mapping_profile::delete_profile(&db, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,26 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::mapping_profile_json;
+use crate::db::DatabaseConnection;
+use crate::services::mapping_profile;
+use crate::utils::error::AppError;
+
+pub async fn get_mapping_profile(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Mapping profile ID must be a positive integer",
+        ));
+    }
+
+    let profile = mapping_profile::get_profile(&db, id).await?;
+
+    Ok((StatusCode::OK, Json(mapping_profile_json(&profile))))
+}
@@ -0,0 +1,17 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::mapping_profile;
+use crate::utils::error::AppError;
+
+pub async fn get_mapping_profiles(
+    State(db): State<DatabaseConnection>,
+) -> Result<impl IntoResponse, AppError> {
+    let profiles = mapping_profile::list_profiles(&db).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "mapping_profiles": profiles })),
+    ))
+}
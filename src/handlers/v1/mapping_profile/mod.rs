@@ -0,0 +1,74 @@
+pub mod create_mapping_profile;
+pub mod delete_mapping_profile;
+pub mod get_mapping_profile;
+pub mod get_mapping_profiles;
+pub mod update_mapping_profile;
+
+pub use create_mapping_profile::create_mapping_profile;
+pub use delete_mapping_profile::delete_mapping_profile;
+pub use get_mapping_profile::get_mapping_profile;
+pub use get_mapping_profiles::get_mapping_profiles;
+pub use update_mapping_profile::update_mapping_profile;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::services::mapping_profile::MappingProfileWithFields;
+use crate::services::receipt_file::FieldMapping;
+
+/// How a profile reads one bulk receipt field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingFieldRequest {
+    pub field: String,
+    pub source_column: Option<String>,
+    pub default_value: Option<String>,
+    pub trim: Option<bool>,      // default true
+    pub uppercase: Option<bool>, // default false
+    pub multiplier: Option<i32>,
+}
+
+impl MappingFieldRequest {
+    pub fn into_field_mapping(self) -> FieldMapping {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        FieldMapping {
+            field: self.field.trim().to_lowercase(),
+            source_column: non_empty(self.source_column),
+            default_value: non_empty(self.default_value),
+            trim: self.trim.unwrap_or(true),
+            uppercase: self.uppercase.unwrap_or(false),
+            multiplier: self.multiplier,
+        }
+    }
+}
+
+/// Shared JSON shape for a mapping profile and its fields
+pub(crate) fn mapping_profile_json(profile: &MappingProfileWithFields) -> Value {
+    let fields: Vec<Value> = profile
+        .fields
+        .iter()
+        .map(|field| {
+            json!({
+                "field": field.field,
+                "source_column": field.source_column,
+                "default_value": field.default_value,
+                "trim": field.trim,
+                "uppercase": field.uppercase,
+                "multiplier": field.multiplier
+            })
+        })
+        .collect();
+
+    json!({
+        "id": profile.profile.id,
+        "name": profile.profile.name,
+        "description": profile.profile.description,
+        "fields": fields,
+        "created_at": profile.profile.created_at,
+        "updated_at": profile.profile.updated_at
+    })
+}
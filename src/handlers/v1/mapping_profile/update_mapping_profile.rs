@@ -0,0 +1,71 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::{MappingFieldRequest, mapping_profile_json};
+use crate::db::DatabaseConnection;
+use crate::services::mapping_profile;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateMappingProfileRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: Option<String>,
+
+    #[validate(length(max = 1000, message = "Description must be at most 1000 characters"))]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1, message = "At least one field is required"))]
+    pub fields: Option<Vec<MappingFieldRequest>>, // replaces all of the profile's fields
+}
+
+impl UpdateMappingProfileRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut name) = self.name {
+            *name = name.trim().to_string();
+        }
+        if let Some(ref mut description) = self.description {
+            *description = description.trim().to_string();
+        }
+        self
+    }
+}
+
+/// Update a mapping profile; imports queued with it use the profile as it is when they run
+pub async fn update_mapping_profile(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateMappingProfileRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Mapping profile ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let fields = request.fields.map(|fields| {
+        fields
+            .into_iter()
+            .map(MappingFieldRequest::into_field_mapping)
+            .collect()
+    });
+    let profile =
+        mapping_profile::update_profile(&db, id, request.name, request.description, fields).await?;
+
+    Ok((StatusCode::OK, Json(mapping_profile_json(&profile))))
+}
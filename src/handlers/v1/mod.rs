@@ -1,17 +1,53 @@
+pub mod abc_classification;
+pub mod adjustment;
+pub mod cycle_count;
+pub mod inventory;
 pub mod item;
+pub mod job;
+pub mod ledger;
 pub mod location;
+pub mod mapping_profile;
 pub mod pick;
+pub mod purchase_order;
+pub mod putaway;
 pub mod receipt;
+pub mod supplier;
 pub mod transfer;
 pub mod warehouse;
 
+pub use abc_classification::{classify_items, get_abc_classes};
+pub use adjustment::create_adjustment;
+pub use cycle_count::{
+    approve_line, close_cycle_count, create_cycle_count, discard_held_movement, get_cycle_count,
+    get_held_movements, list_cycle_counts, record_counts, reject_line, review_cycle_count,
+    schedule_cycle_counts, start_cycle_count,
+};
+pub use inventory::{export_snapshot, get_inventory};
 pub use item::{create_item, delete_item, get_item, get_items, update_item};
+pub use job::{get_job, get_rejected_rows};
+pub use ledger::{get_ledger, verify_ledger};
 pub use location::{
     create_location, delete_location, get_location, get_locations, update_location,
 };
-pub use pick::{allocate_pick_wave, confirm_pick, create_pick_wave};
-pub use receipt::{bulk_receipt, create_receipt, generate_sample};
-pub use transfer::{complete_transfer, create_transfer};
+pub use mapping_profile::{
+    create_mapping_profile, delete_mapping_profile, get_mapping_profile, get_mapping_profiles,
+    update_mapping_profile,
+};
+pub use pick::{allocate_pick_wave, confirm_pick, create_pick_wave, reverse_pick_wave};
+pub use purchase_order::{
+    cancel_purchase_order, close_purchase_order, create_purchase_order, get_purchase_order,
+    get_purchase_orders, open_purchase_order, receive_purchase_order,
+};
+pub use putaway::{
+    confirm_putaway_task, create_pick_face, delete_pick_face, get_pick_faces, get_putaway_rules,
+    get_putaway_suggestions, get_putaway_task, get_putaway_tasks, update_pick_face,
+    update_putaway_rules,
+};
+pub use receipt::{
+    bulk_receipt, create_receipt, generate_sample, get_receipt, get_receipts, reverse_receipt,
+};
+pub use supplier::{create_supplier, get_supplier, get_suppliers, update_supplier};
+pub use transfer::{complete_transfer, create_transfer, reverse_transfer};
 pub use warehouse::{
     create_warehouse, delete_warehouse, get_warehouse, get_warehouses, update_warehouse,
 };
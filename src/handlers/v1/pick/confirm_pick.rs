@@ -35,6 +35,7 @@ pub async fn confirm_pick(
                 "status": line.status
             })).collect::<Vec<_>>(),
             "ledger_entries": result.ledger_entries,
+            "held_movements": result.held_movements,
             "updated_at": result.wave.updated_at
         })),
     ))
@@ -1,7 +1,9 @@
 pub mod create_pick_wave;
 pub mod allocate_pick_wave;
 pub mod confirm_pick;
+pub mod reverse_pick_wave;
 
 pub use create_pick_wave::create_pick_wave;
 pub use allocate_pick_wave::allocate_pick_wave;
 pub use confirm_pick::confirm_pick;
+pub use reverse_pick_wave::reverse_pick_wave;
@@ -0,0 +1,40 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::pick;
+use crate::utils::error::AppError;
+
+pub async fn reverse_pick_wave(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Pick wave ID must be a positive integer",
+        ));
+    }
+
+    let result = pick::reverse_pick_wave(&db, id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "pick_wave_id": result.wave.id,
+            "status": result.wave.status,
+            "reversal_entries": result.reversals.iter().map(|entry| json!({
+                "ledger_entry_id": entry.id,
+                "reverses_entry_id": entry.reverses_entry_id,
+                "item_id": entry.item_id,
+                "location_id": entry.location_id,
+                "quantity_change": entry.quantity_change
+            })).collect::<Vec<_>>(),
+            "updated_at": result.wave.updated_at
+        })),
+    ))
+}
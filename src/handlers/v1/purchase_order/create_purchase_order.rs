@@ -0,0 +1,96 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::prelude::Date;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::purchase_order_json;
+use crate::db::DatabaseConnection;
+use crate::services::purchase_order::{self, NewPurchaseOrder, PurchaseOrderItem};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct PurchaseOrderItemRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(range(min = 1, message = "Quantity must be a positive integer"))]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreatePurchaseOrderRequest {
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "PO number must be between 1 and 50 characters"
+    ))]
+    pub po_number: String,
+
+    #[validate(range(min = 1, message = "supplier_id must be a positive integer"))]
+    pub supplier_id: i32,
+
+    pub expected_at: Option<Date>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Over-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub over_receipt_tolerance_percent: Option<i32>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Under-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub under_receipt_tolerance_percent: Option<i32>,
+
+    #[validate(length(min = 1, message = "At least one item is required"))]
+    pub items: Vec<PurchaseOrderItemRequest>,
+}
+
+impl CreatePurchaseOrderRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.po_number = self.po_number.trim().to_string();
+        for item in &mut self.items {
+            item.sku = item.sku.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn create_purchase_order(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreatePurchaseOrderRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let order = NewPurchaseOrder {
+        po_number: request.po_number,
+        supplier_id: request.supplier_id,
+        expected_at: request.expected_at,
+        over_receipt_tolerance_percent: request.over_receipt_tolerance_percent,
+        under_receipt_tolerance_percent: request.under_receipt_tolerance_percent,
+    };
+
+    let items: Vec<PurchaseOrderItem> = request
+        .items
+        .iter()
+        .map(|item| PurchaseOrderItem {
+            sku: item.sku.clone(),
+            quantity: item.quantity,
+        })
+        .collect();
+
+    let result = purchase_order::create_purchase_order(&db, order, items).await?;
+
+    Ok((StatusCode::CREATED, Json(purchase_order_json(&result))))
+}
@@ -0,0 +1,26 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::purchase_order_json;
+use crate::db::DatabaseConnection;
+use crate::services::purchase_order;
+use crate::utils::error::AppError;
+
+pub async fn get_purchase_order(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Purchase order ID must be a positive integer",
+        ));
+    }
+
+    let result = purchase_order::get_purchase_order(&db, id).await?;
+
+    Ok((StatusCode::OK, Json(purchase_order_json(&result))))
+}
@@ -0,0 +1,50 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::purchase_order;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetPurchaseOrdersQuery {
+    pub status: Option<String>,
+
+    #[validate(range(min = 1, message = "supplier_id must be a positive integer"))]
+    pub supplier_id: Option<i32>,
+}
+
+pub async fn get_purchase_orders(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetPurchaseOrdersQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let status = query.status.map(|status| status.trim().to_uppercase());
+    let orders = purchase_order::list_purchase_orders(&db, status, query.supplier_id).await?;
+
+    let orders: Vec<_> = orders
+        .iter()
+        .map(|order| {
+            json!({
+                "purchase_order_id": order.id,
+                "po_number": order.po_number,
+                "supplier_id": order.supplier_id,
+                "status": order.status,
+                "expected_at": order.expected_at,
+                "created_at": order.created_at,
+                "updated_at": order.updated_at
+            })
+        })
+        .collect();
+
+    Ok((StatusCode::OK, Json(json!({ "purchase_orders": orders }))))
+}
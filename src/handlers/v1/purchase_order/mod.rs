@@ -0,0 +1,45 @@
+pub mod cancel_purchase_order;
+pub mod close_purchase_order;
+pub mod create_purchase_order;
+pub mod get_purchase_order;
+pub mod get_purchase_orders;
+pub mod open_purchase_order;
+pub mod receive_purchase_order;
+
+pub use cancel_purchase_order::cancel_purchase_order;
+pub use close_purchase_order::close_purchase_order;
+pub use create_purchase_order::create_purchase_order;
+pub use get_purchase_order::get_purchase_order;
+pub use get_purchase_orders::get_purchase_orders;
+pub use open_purchase_order::open_purchase_order;
+pub use receive_purchase_order::receive_purchase_order;
+
+use serde_json::{Value, json};
+
+use crate::services::purchase_order::{self, PurchaseOrderWithLines};
+
+/// Shared JSON shape for a purchase order and its lines
+pub(crate) fn purchase_order_json(order: &PurchaseOrderWithLines) -> Value {
+    let purchase_order = &order.purchase_order;
+
+    json!({
+        "purchase_order_id": purchase_order.id,
+        "po_number": purchase_order.po_number,
+        "supplier_id": purchase_order.supplier_id,
+        "status": purchase_order.status,
+        "closed_manually": purchase_order.closed_manually,
+        "over_receipt_tolerance_percent": purchase_order.over_receipt_tolerance_percent,
+        "under_receipt_tolerance_percent": purchase_order.under_receipt_tolerance_percent,
+        "expected_at": purchase_order.expected_at,
+        "lines": order.lines.iter().map(|line| json!({
+            "id": line.id,
+            "item_id": line.item_id,
+            "quantity_ordered": line.quantity_ordered,
+            "quantity_received": line.quantity_received,
+            "quantity_outstanding": (line.quantity_ordered - line.quantity_received).max(0),
+            "max_receivable": purchase_order::max_receivable(purchase_order, line)
+        })).collect::<Vec<_>>(),
+        "created_at": purchase_order.created_at,
+        "updated_at": purchase_order.updated_at
+    })
+}
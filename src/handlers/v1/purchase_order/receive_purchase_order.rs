@@ -0,0 +1,148 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use validator::Validate;
+
+use super::purchase_order_json;
+use crate::db::DatabaseConnection;
+use crate::services::purchase_order::{self, PurchaseOrderReceiptItem};
+use crate::services::receipt::ReceiptHeader;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Serialize, Validate)]
+pub struct ReceiveItemRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: Option<String>, // defaults to the receiving location of warehouse_id
+
+    #[validate(range(min = 1, message = "Quantity must be a positive integer"))]
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct ReceivePurchaseOrderRequest {
+    // Posting again under an existing receipt number adds lines to that receipt
+    #[validate(length(
+        min = 1,
+        max = 36,
+        message = "Receipt number must be between 1 and 36 characters"
+    ))]
+    pub receipt_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Reference number must be between 1 and 100 characters"
+    ))]
+    pub reference_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "received_by must be between 1 and 100 characters"
+    ))]
+    pub received_by: Option<String>,
+
+    // Items sent without a location_code are received into this warehouse's receiving location
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+
+    #[validate(length(min = 1, message = "At least one item is required"))]
+    pub items: Vec<ReceiveItemRequest>,
+}
+
+impl ReceivePurchaseOrderRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut receipt_number) = self.receipt_number {
+            *receipt_number = receipt_number.trim().to_string();
+        }
+        if let Some(ref mut reference_number) = self.reference_number {
+            *reference_number = reference_number.trim().to_string();
+        }
+        if let Some(ref mut received_by) = self.received_by {
+            *received_by = received_by.trim().to_string();
+        }
+        for item in &mut self.items {
+            item.sku = item.sku.trim().to_string();
+            if let Some(ref mut location_code) = item.location_code {
+                *location_code = location_code.trim().to_string();
+            }
+        }
+        self
+    }
+}
+
+pub async fn receive_purchase_order(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<ReceivePurchaseOrderRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Purchase order ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let header = ReceiptHeader {
+        receipt_number: request.receipt_number,
+        reference_number: request.reference_number,
+        received_by: request.received_by,
+        ..ReceiptHeader::default()
+    };
+
+    let items: Vec<PurchaseOrderReceiptItem> = request
+        .items
+        .iter()
+        .map(|item| PurchaseOrderReceiptItem {
+            sku: item.sku.clone(),
+            location_code: item.location_code.clone(),
+            warehouse_id: request.warehouse_id,
+            quantity: item.quantity,
+        })
+        .collect();
+
+    let result = purchase_order::receive_purchase_order(&db, id, header, items).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "receipt_id": result.receipt.id,
+            "receipt_number": result.receipt.receipt_number,
+            "lines": result.received.iter().map(|received| json!({
+                "receipt_line_id": received.line.id,
+                "po_line_id": received.line.po_line_id,
+                "item_id": received.line.item_id,
+                "location_id": received.line.location_id,
+                "quantity": received.line.quantity,
+                "ledger_entry_id": received.posted.ledger_entry_id(),
+                "held_movement_id": received.posted.held_movement_id(),
+                "putaway_task_ids": result.putaway_tasks.iter()
+                    .filter(|task| task.receipt_line_id == received.line.id)
+                    .map(|task| task.id)
+                    .collect::<Vec<_>>()
+            })).collect::<Vec<_>>(),
+            "purchase_order": purchase_order_json(&result.purchase_order)
+        })),
+    ))
+}
@@ -0,0 +1,73 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Default, Deserialize, Validate)]
+pub struct ConfirmPutawayTaskRequest {
+    // Where the stock was actually put; defaults to the suggested location
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: Option<String>,
+}
+
+impl ConfirmPutawayTaskRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut location_code) = self.location_code {
+            *location_code = location_code.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn confirm_putaway_task(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    payload: Option<Json<ConfirmPutawayTaskRequest>>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Putaway task ID must be a positive integer",
+        ));
+    }
+
+    let request = payload
+        .map(|Json(payload)| payload)
+        .unwrap_or_default()
+        .trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let result = putaway::confirm_task(&db, id, request.location_code).await?;
+    let (source, dest) = &result.ledger_entries;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "putaway_task_id": result.task.id,
+            "item_id": result.task.item_id,
+            "quantity": result.task.quantity,
+            "from_location_id": result.task.from_location_id,
+            "suggested_location_id": result.task.suggested_location_id,
+            "to_location_id": result.task.to_location_id,
+            "status": result.task.status,
+            "source_ledger_id": source.id,
+            "destination_ledger_id": dest.id,
+            "completed_at": result.task.completed_at
+        })),
+    ))
+}
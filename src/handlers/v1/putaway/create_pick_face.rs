@@ -0,0 +1,58 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreatePickFaceRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: String,
+
+    #[validate(range(min = 1, message = "Max quantity must be a positive integer"))]
+    pub max_quantity: i32,
+}
+
+impl CreatePickFaceRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.sku = self.sku.trim().to_string();
+        self.location_code = self.location_code.trim().to_string();
+        self
+    }
+}
+
+/// Make a location an item's home pick face in the location's warehouse
+pub async fn create_pick_face(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreatePickFaceRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let pick_face = putaway_rule::create_pick_face(
+        &db,
+        &request.sku,
+        &request.location_code,
+        request.max_quantity,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(json!(pick_face))))
+}
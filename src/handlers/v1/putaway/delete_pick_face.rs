@@ -0,0 +1,24 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+pub async fn delete_pick_face(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Pick face ID must be a positive integer",
+        ));
+    }
+
+    putaway_rule::delete_pick_face(&db, id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,35 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetPickFacesQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+
+    #[validate(range(min = 1, message = "item_id must be a positive integer"))]
+    pub item_id: Option<i32>,
+}
+
+pub async fn get_pick_faces(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetPickFacesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let pick_faces = putaway_rule::list_pick_faces(&db, query.warehouse_id, query.item_id).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "pick_faces": pick_faces }))))
+}
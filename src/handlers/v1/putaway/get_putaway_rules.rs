@@ -0,0 +1,26 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use super::putaway_rules_json;
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+pub async fn get_putaway_rules(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Warehouse ID must be a positive integer",
+        ));
+    }
+
+    let rules = putaway_rule::get_rules(&db, id).await?;
+
+    Ok((StatusCode::OK, Json(putaway_rules_json(&rules))))
+}
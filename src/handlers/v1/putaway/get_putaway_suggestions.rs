@@ -0,0 +1,88 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetPutawaySuggestionsQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: i32,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "SKU must be between 1 and 100 characters"
+    ))]
+    pub sku: String,
+
+    #[validate(range(min = 1, message = "Quantity must be a positive integer"))]
+    pub quantity: i32,
+
+    #[validate(range(min = 1, max = 100, message = "limit must be between 1 and 100"))]
+    pub limit: Option<usize>,
+}
+
+/// Ranked locations to put stock of an item away to, by the warehouse's putaway rules
+pub async fn get_putaway_suggestions(
+    State(db): State<DatabaseConnection>,
+    Query(mut query): Query<GetPutawaySuggestionsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query.sku = query.sku.trim().to_string();
+
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let item = receipt::find_item_by_sku(&db, &query.sku)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Item with SKU '{}' not found", query.sku)))?;
+
+    let candidates = putaway_rule::suggest_locations(
+        &db,
+        query.warehouse_id,
+        item.id,
+        query.quantity,
+        query.limit.unwrap_or(10),
+    )
+    .await?;
+
+    let candidates: Vec<Value> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(i, candidate)| {
+            json!({
+                "rank": i + 1,
+                "location_id": candidate.location.id,
+                "location_code": candidate.location.code,
+                "rule": candidate.rule,
+                "reason": candidate.reason,
+                "quantity": candidate.quantity,
+                "on_hand": candidate.on_hand,
+                "item_on_hand": candidate.item_on_hand,
+                "incoming": candidate.incoming,
+                "capacity": candidate.location.capacity
+            })
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "warehouse_id": query.warehouse_id,
+            "item_id": item.id,
+            "sku": item.sku,
+            "quantity": query.quantity,
+            "candidates": candidates
+        })),
+    ))
+}
@@ -0,0 +1,26 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway;
+use crate::utils::error::AppError;
+
+pub async fn get_putaway_task(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Putaway task ID must be a positive integer",
+        ));
+    }
+
+    let task = putaway::get_task(&db, id).await?;
+
+    Ok((StatusCode::OK, Json(json!(task))))
+}
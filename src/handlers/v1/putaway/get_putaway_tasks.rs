@@ -0,0 +1,43 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway::{self, PutawayTaskFilter};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetPutawayTasksQuery {
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
+
+    #[validate(range(min = 1, message = "receipt_id must be a positive integer"))]
+    pub receipt_id: Option<i32>,
+
+    pub status: Option<String>,
+}
+
+pub async fn get_putaway_tasks(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetPutawayTasksQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let filter = PutawayTaskFilter {
+        warehouse_id: query.warehouse_id,
+        receipt_id: query.receipt_id,
+        status: query.status.map(|status| status.trim().to_uppercase()),
+    };
+
+    let tasks = putaway::list_tasks(&db, &filter).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "putaway_tasks": tasks }))))
+}
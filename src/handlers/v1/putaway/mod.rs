@@ -0,0 +1,41 @@
+pub mod confirm_putaway_task;
+pub mod create_pick_face;
+pub mod delete_pick_face;
+pub mod get_pick_faces;
+pub mod get_putaway_rules;
+pub mod get_putaway_suggestions;
+pub mod get_putaway_task;
+pub mod get_putaway_tasks;
+pub mod update_pick_face;
+pub mod update_putaway_rules;
+
+pub use confirm_putaway_task::confirm_putaway_task;
+pub use create_pick_face::create_pick_face;
+pub use delete_pick_face::delete_pick_face;
+pub use get_pick_faces::get_pick_faces;
+pub use get_putaway_rules::get_putaway_rules;
+pub use get_putaway_suggestions::get_putaway_suggestions;
+pub use get_putaway_task::get_putaway_task;
+pub use get_putaway_tasks::get_putaway_tasks;
+pub use update_pick_face::update_pick_face;
+pub use update_putaway_rules::update_putaway_rules;
+
+use serde_json::{Value, json};
+
+use crate::services::putaway_rule::WarehouseRules;
+
+/// Shared JSON shape for a warehouse's putaway rules
+pub(crate) fn putaway_rules_json(rules: &WarehouseRules) -> Value {
+    let list: Vec<Value> = rules
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(i, rule_type)| json!({ "sequence": i + 1, "rule_type": rule_type }))
+        .collect();
+
+    json!({
+        "warehouse_id": rules.warehouse_id,
+        "rules": list,
+        "is_default": rules.is_default
+    })
+}
@@ -0,0 +1,59 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdatePickFaceRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Location code must be between 1 and 100 characters"
+    ))]
+    pub location_code: Option<String>,
+
+    #[validate(range(min = 1, message = "Max quantity must be a positive integer"))]
+    pub max_quantity: Option<i32>,
+}
+
+impl UpdatePickFaceRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut location_code) = self.location_code {
+            *location_code = location_code.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn update_pick_face(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdatePickFaceRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Pick face ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let pick_face =
+        putaway_rule::update_pick_face(&db, id, request.location_code, request.max_quantity)
+            .await?;
+
+    Ok((StatusCode::OK, Json(json!(pick_face))))
+}
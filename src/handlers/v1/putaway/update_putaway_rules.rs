@@ -0,0 +1,61 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::putaway_rules_json;
+use crate::db::DatabaseConnection;
+use crate::services::putaway_rule;
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PutawayRuleRequest {
+    pub rule_type: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdatePutawayRulesRequest {
+    #[validate(length(max = 10, message = "At most 10 rules are allowed"))]
+    pub rules: Vec<PutawayRuleRequest>, // in the order they are applied; empty for the defaults
+}
+
+impl UpdatePutawayRulesRequest {
+    pub fn trim_fields(mut self) -> Self {
+        for rule in &mut self.rules {
+            rule.rule_type = rule.rule_type.trim().to_uppercase();
+        }
+        self
+    }
+}
+
+/// Replace a warehouse's putaway rules
+pub async fn update_putaway_rules(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdatePutawayRulesRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Warehouse ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let rules = request
+        .rules
+        .into_iter()
+        .map(|rule| rule.rule_type)
+        .collect();
+    let rules = putaway_rule::set_rules(&db, id, rules).await?;
+
+    Ok((StatusCode::OK, Json(putaway_rules_json(&rules))))
+}
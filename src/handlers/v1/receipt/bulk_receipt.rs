@@ -1,21 +1,178 @@
 use axum::{
     Json,
-    extract::{Multipart, State},
-    http::StatusCode,
+    body::Bytes,
+    extract::{FromRequest, Multipart, Query, Request, State},
+    http::{
+        StatusCode,
+        header::{CONTENT_TYPE, LOCATION},
+    },
     response::IntoResponse,
 };
-use csv::ReaderBuilder;
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
 use serde_json::json;
+use tokio::sync::mpsc;
+use validator::Validate;
 
 use crate::db::DatabaseConnection;
-use crate::services::receipt::{BulkReceiptRow, process_bulk_receipt};
+use crate::services::import_job::JobUpload;
+use crate::services::mapping_profile;
+use crate::services::receipt::{BulkReceiptError, BulkReceiptPreview, ReceiptHeader};
+use crate::services::receipt_file::{self, ColumnMapping, FileFormat};
 use crate::utils::error::AppError;
+use crate::utils::validation::validate_bulk_receipt_mode;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkReceiptQuery {
+    pub dry_run: Option<bool>,
+
+    #[validate(custom(function = "validate_bulk_receipt_mode"))]
+    pub mode: Option<String>, // "all" (the default) or "partial"
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Profile must be between 1 and 100 characters"
+    ))]
+    pub profile: Option<String>, // mapping profile the file's columns are read with
+
+    // Receipt header, for files sent as the request body; form fields override these
+    #[validate(length(
+        min = 1,
+        max = 36,
+        message = "Receipt number must be between 1 and 36 characters"
+    ))]
+    pub receipt_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Supplier must be between 1 and 255 characters"
+    ))]
+    pub supplier: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Reference number must be between 1 and 100 characters"
+    ))]
+    pub reference_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "received_by must be between 1 and 100 characters"
+    ))]
+    pub received_by: Option<String>,
+}
+
+impl BulkReceiptQuery {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut mode) = self.mode {
+            *mode = mode.trim().to_lowercase();
+        }
+        for field in [
+            &mut self.profile,
+            &mut self.receipt_number,
+            &mut self.supplier,
+            &mut self.reference_number,
+            &mut self.received_by,
+        ] {
+            *field = field
+                .take()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty());
+        }
+        self
+    }
+}
+
+/// A file read to the end: checked in a dry run, or stored for an import job but not yet queued
+enum Outcome {
+    Preview(BulkReceiptPreview),
+    Queued(JobUpload),
+}
 
 pub async fn bulk_receipt(
     State(db): State<DatabaseConnection>,
-    mut multipart: Multipart,
+    Query(query): Query<BulkReceiptQuery>,
+    request: Request,
 ) -> Result<impl IntoResponse, AppError> {
-    let mut csv_data: Option<Vec<u8>> = None;
+    let query = query.trim_fields();
+
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let dry_run = query.dry_run.unwrap_or(false);
+    let partial = query.mode.as_deref() == Some("partial");
+    let mapping = match &query.profile {
+        Some(profile) => mapping_profile::find_column_mapping(&db, profile).await?,
+        None => ColumnMapping::standard(),
+    };
+    let mut header = ReceiptHeader {
+        receipt_number: query.receipt_number,
+        supplier: query.supplier,
+        supplier_id: None,
+        purchase_order_id: None,
+        reference_number: query.reference_number,
+        received_by: query.received_by,
+    };
+
+    // A multipart form carries the file as a field; anything else is the file itself
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+    let outcome = if content_type
+        .to_lowercase()
+        .starts_with("multipart/form-data")
+    {
+        let multipart = Multipart::from_request(request, &()).await.map_err(|e| {
+            AppError::bad_request(format!("Failed to read multipart form data: {}", e))
+        })?;
+        read_multipart(&db, &mut header, &mapping, multipart, dry_run, partial).await?
+    } else {
+        let format = FileFormat::from_content_type(&content_type).ok_or_else(|| {
+            AppError::bad_request(format!(
+                "Unsupported Content-Type '{}'. Send multipart/form-data, or the file as \
+                 text/csv, application/json, application/x-ndjson or \
+                 application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                content_type
+            ))
+        })?;
+        let file = request.into_body().into_data_stream();
+        import_upload(&db, &header, &mapping, format, file, dry_run, partial).await?
+    };
+
+    let upload = match outcome {
+        Outcome::Preview(preview) => {
+            return Ok(preview_response(&header, preview).into_response());
+        }
+        Outcome::Queued(upload) => upload,
+    };
+    let job = upload.finish().await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        [(LOCATION, format!("/v1/jobs/{}", job.id))],
+        Json(json!(job)),
+    )
+        .into_response())
+}
+
+/// Read the receipt header fields and the file from a multipart form
+async fn read_multipart(
+    db: &DatabaseConnection,
+    header: &mut ReceiptHeader,
+    mapping: &ColumnMapping,
+    mut multipart: Multipart,
+    dry_run: bool,
+    partial: bool,
+) -> Result<Outcome, AppError> {
+    let mut outcome: Option<Outcome> = None;
 
     while let Some(field) = multipart
         .next_field()
@@ -25,188 +182,141 @@ pub async fn bulk_receipt(
         let name = field.name().unwrap_or("").to_string();
         let file_name = field.file_name().map(|s| s.to_string());
 
-        // Accept any field that:
-        // 1. Has a file_name (indicates file upload)
-        // 2. Has name "file" or "csv"
-        // 3. Or any field if we haven't found one yet (fallback)
-        let is_file_field = file_name.is_some()
-            || name == "file"
-            || name == "csv"
-            || (csv_data.is_none() && !name.is_empty());
-
-        if is_file_field {
-            let data = field
-                .bytes()
-                .await
-                .map_err(|e| AppError::bad_request(format!("Failed to read file data: {}", e)))?;
+        // Receipt header fields may be sent alongside the file
+        if file_name.is_none()
+            && matches!(
+                name.as_str(),
+                "receipt_number" | "supplier" | "reference_number" | "received_by"
+            )
+        {
+            // The file is imported as it arrives, under the header read so far
+            if outcome.is_some() {
+                return Err(AppError::bad_request(format!(
+                    "Field '{}' must be sent before the file",
+                    name
+                )));
+            }
 
-            // Only use this data if it looks like CSV (contains commas or newlines)
-            // This helps avoid using field names as data
-            let data_vec = data.to_vec();
-            if !data_vec.is_empty()
-                && (data_vec.contains(&b',')
-                    || data_vec.contains(&b'\n')
-                    || data_vec.contains(&b'\r'))
-            {
-                // Prefer explicitly named fields
-                if name == "file" || name == "csv" {
-                    csv_data = Some(data_vec);
-                    break;
-                } else if csv_data.is_none() {
-                    csv_data = Some(data_vec);
-                }
+            let value = field
+                .text()
+                .await
+                .map_err(|e| {
+                    AppError::bad_request(format!("Failed to read field '{}': {}", name, e))
+                })?
+                .trim()
+                .to_string();
+            if value.is_empty() {
+                continue;
+            }
+            let (target, max_len) = match name.as_str() {
+                "receipt_number" => (&mut header.receipt_number, 36),
+                "supplier" => (&mut header.supplier, 255),
+                "reference_number" => (&mut header.reference_number, 100),
+                _ => (&mut header.received_by, 100),
+            };
+            if value.len() > max_len {
+                return Err(AppError::validation(format!(
+                    "{}: must be at most {} characters",
+                    name, max_len
+                )));
             }
+            *target = Some(value);
+            continue;
         }
+
+        // The first other field is the file, normally named "file" or "csv"
+        if outcome.is_some() {
+            continue;
+        }
+
+        let format = FileFormat::detect(field.content_type(), file_name.as_deref());
+        outcome = Some(import_upload(db, header, mapping, format, field, dry_run, partial).await?);
     }
 
-    let csv_bytes = csv_data.ok_or_else(|| {
+    outcome.ok_or_else(|| {
         AppError::bad_request(
-            "No CSV file found in multipart form data. Expected field name 'file' or 'csv'",
+            "No receipt file found in multipart form data. Expected field name 'file' or 'csv'",
         )
-    })?;
+    })
+}
 
-    // Remove BOM if present (UTF-8 BOM is EF BB BF)
-    let csv_bytes_clean = if csv_bytes.len() >= 3
-        && csv_bytes[0] == 0xEF
-        && csv_bytes[1] == 0xBB
-        && csv_bytes[2] == 0xBF
-    {
-        &csv_bytes[3..]
-    } else {
-        csv_bytes.as_slice()
+/// Import a file while it uploads: its chunks are streamed into the parser as the parsed
+/// batches are imported
+async fn import_upload<S, E>(
+    db: &DatabaseConnection,
+    header: &ReceiptHeader,
+    mapping: &ColumnMapping,
+    format: FileFormat,
+    file: S,
+    dry_run: bool,
+    partial: bool,
+) -> Result<Outcome, AppError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    let (chunks_tx, chunks_rx) = mpsc::channel(16);
+    let upload = async move {
+        let mut file = std::pin::pin!(file);
+        while let Some(chunk) = file.next().await {
+            let chunk = chunk
+                .map_err(|e| AppError::bad_request(format!("Failed to read file data: {}", e)))?;
+            if chunks_tx.send(chunk.to_vec()).await.is_err() {
+                break;
+            }
+        }
+        Ok::<(), AppError>(())
     };
+    let import = import_file(db, header, mapping, format, chunks_rx, dry_run, partial);
 
-    let mut reader = ReaderBuilder::new()
-        .has_headers(true)
-        .trim(csv::Trim::All)
-        .flexible(true)
-        .from_reader(csv_bytes_clean);
-
-    let mut rows = Vec::new();
-    let mut row_errors = Vec::new();
-
-    let headers = reader
-        .headers()
-        .map_err(|e| AppError::bad_request(format!("Failed to read CSV headers: {}", e)))?;
-
-    let header_map: std::collections::HashMap<String, usize> = headers
-        .iter()
-        .enumerate()
-        .map(|(i, h)| (h.trim().to_lowercase(), i))
-        .collect();
+    let (uploaded, imported) = tokio::join!(upload, import);
+    uploaded?;
+    imported
+}
 
-    let required_columns = ["sku", "location_code", "quantity"];
-    for col in required_columns {
-        if !header_map.contains_key(col) {
-            let found_headers: Vec<String> = header_map.keys().cloned().collect();
-            return Err(AppError::bad_request(format!(
-                "Missing required column: {}. Found columns: {:?}",
-                col, found_headers
-            )));
+/// Read the file batch by batch into a dry-run preview, or store it for an import job
+async fn import_file(
+    db: &DatabaseConnection,
+    header: &ReceiptHeader,
+    mapping: &ColumnMapping,
+    format: FileFormat,
+    mut chunks: mpsc::Receiver<Vec<u8>>,
+    dry_run: bool,
+    partial: bool,
+) -> Result<Outcome, AppError> {
+    if !dry_run {
+        let mut upload =
+            JobUpload::begin_bulk_receipt(db, header, mapping, format, partial).await?;
+        while let Some(chunk) = chunks.recv().await {
+            upload.write(&chunk).await?;
         }
+        return Ok(Outcome::Queued(upload));
     }
 
-    for (row_index, result) in reader.records().enumerate() {
-        let record = match result {
-            Ok(r) => r,
-            Err(e) => {
-                row_errors.push(format!("Row {}: CSV parse error: {}", row_index + 2, e));
-                continue;
+    let mut batches = receipt_file::parse_file(format, mapping.clone(), chunks).await?;
+    let mut preview = BulkReceiptPreview::begin(db, header).await?;
+    while let Some(batch) = batches.next_batch().await? {
+        let mut rows = Vec::with_capacity(batch.len());
+        for parsed in batch {
+            match parsed.row {
+                Ok(row) => rows.push(row),
+                Err(error) => preview.reject(BulkReceiptError {
+                    row: parsed.line,
+                    error,
+                }),
             }
-        };
-
-        let get_field = |col: &str| -> Option<String> {
-            header_map
-                .get(col)
-                .and_then(|&idx| record.get(idx))
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-        };
-
-        let sku = match get_field("sku") {
-            Some(s) => s,
-            None => {
-                row_errors.push(format!(
-                    "Row {}: Missing required field 'sku'",
-                    row_index + 2
-                ));
-                continue;
-            }
-        };
-
-        let location_code = match get_field("location_code") {
-            Some(s) => s,
-            None => {
-                row_errors.push(format!(
-                    "Row {}: Missing required field 'location_code'",
-                    row_index + 2
-                ));
-                continue;
-            }
-        };
-
-        let quantity = match get_field("quantity") {
-            Some(q) => match q.parse::<i32>() {
-                Ok(val) => val,
-                Err(_) => {
-                    row_errors.push(format!("Row {}: Invalid quantity '{}'", row_index + 2, q));
-                    continue;
-                }
-            },
-            None => {
-                row_errors.push(format!(
-                    "Row {}: Missing required field 'quantity'",
-                    row_index + 2
-                ));
-                continue;
-            }
-        };
-
-        let name = get_field("name");
-        let unit_of_measure = get_field("unit_of_measure");
-        let barcode = get_field("barcode");
-        let is_serialized = get_field("is_serialized").and_then(|s| s.parse::<bool>().ok());
-        let warehouse_id = get_field("warehouse_id").and_then(|s| s.parse::<i32>().ok());
-        let aisle = get_field("aisle");
-        let bin = get_field("bin");
-        let shelf = get_field("shelf");
-        let is_pickable = get_field("is_pickable").and_then(|s| s.parse::<bool>().ok());
-        let is_bulk = get_field("is_bulk").and_then(|s| s.parse::<bool>().ok());
-
-        rows.push(BulkReceiptRow {
-            sku,
-            location_code,
-            quantity,
-            name,
-            unit_of_measure,
-            barcode,
-            is_serialized,
-            warehouse_id,
-            aisle,
-            bin,
-            shelf,
-            is_pickable,
-            is_bulk,
-        });
-    }
-
-    if !row_errors.is_empty() {
-        return Ok((
-            StatusCode::BAD_REQUEST,
-            Json(json!({
-                "error": "CSV parsing errors",
-                "errors": row_errors
-            })),
-        ));
-    }
-
-    if rows.is_empty() {
-        return Err(AppError::bad_request("CSV file contains no valid rows"));
+        }
+        preview.add_batch(db, &rows).await?;
     }
+    Ok(Outcome::Preview(preview))
+}
 
-    let result = process_bulk_receipt(&db, rows).await?;
-
-    let errors: Vec<serde_json::Value> = result
+fn preview_response(
+    header: &ReceiptHeader,
+    mut preview: BulkReceiptPreview,
+) -> (StatusCode, Json<serde_json::Value>) {
+    preview.errors.sort_by_key(|e| e.row);
+    let errors: Vec<serde_json::Value> = preview
         .errors
         .iter()
         .map(|e| {
@@ -216,20 +326,29 @@ pub async fn bulk_receipt(
             })
         })
         .collect();
+    let locations_to_create: Vec<serde_json::Value> = preview
+        .locations_to_create
+        .iter()
+        .map(|location| {
+            json!({
+                "code": location.code,
+                "warehouse_id": location.warehouse_id
+            })
+        })
+        .collect();
 
-    let status = if result.errors.is_empty() {
-        StatusCode::CREATED
-    } else {
-        StatusCode::PARTIAL_CONTENT
-    };
-
-    Ok((
-        status,
+    (
+        StatusCode::OK,
         Json(json!({
-            "receipt_id": result.receipt_id,
-            "total_rows": result.total_rows,
-            "successful_rows": result.successful_rows,
-            "errors": errors
+            "dry_run": true,
+            "receipt_id": preview.receipt.as_ref().map(|receipt| receipt.id),
+            "receipt_number": header.receipt_number,
+            "total_rows": preview.total_rows,
+            "valid_rows": preview.valid_rows,
+            "errors": errors,
+            "held_rows": preview.held_rows,
+            "items_to_create": preview.items_to_create,
+            "locations_to_create": locations_to_create
         })),
-    ))
+    )
 }
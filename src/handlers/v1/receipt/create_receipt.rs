@@ -1,11 +1,11 @@
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
 use serde::Deserialize;
 use serde_json::json;
-use uuid::Uuid;
 use validator::Validate;
 
 use crate::db::DatabaseConnection;
-use crate::services::receipt;
+use crate::services::putaway;
+use crate::services::receipt::{self, ReceiptHeader};
 use crate::utils::error::AppError;
 
 #[derive(Debug, Clone, Deserialize, Validate)]
@@ -22,24 +22,63 @@ pub struct CreateReceiptRequest {
         max = 100,
         message = "Location code must be between 1 and 100 characters"
     ))]
-    pub location_code: String,
+    pub location_code: Option<String>,
+
+    // Instead of location_code: receive into the warehouse's receiving location for putaway
+    #[validate(range(min = 1, message = "warehouse_id must be a positive integer"))]
+    pub warehouse_id: Option<i32>,
 
     #[validate(range(min = 1, message = "Quantity must be a positive integer"))]
     pub quantity: i32,
 
+    // Posting again under an existing receipt number adds a line to that receipt
+    #[serde(alias = "receipt_id")]
     #[validate(length(
+        min = 1,
         max = 36,
-        message = "Receipt ID must be at most 36 characters (UUID format)"
+        message = "Receipt number must be between 1 and 36 characters"
     ))]
-    pub receipt_id: Option<String>,
+    pub receipt_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Supplier must be between 1 and 255 characters"
+    ))]
+    pub supplier: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Reference number must be between 1 and 100 characters"
+    ))]
+    pub reference_number: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "received_by must be between 1 and 100 characters"
+    ))]
+    pub received_by: Option<String>,
 }
 
 impl CreateReceiptRequest {
     pub fn trim_fields(mut self) -> Self {
         self.sku = self.sku.trim().to_string();
-        self.location_code = self.location_code.trim().to_string();
-        if let Some(ref mut receipt_id) = self.receipt_id {
-            *receipt_id = receipt_id.trim().to_string();
+        if let Some(ref mut location_code) = self.location_code {
+            *location_code = location_code.trim().to_string();
+        }
+        if let Some(ref mut receipt_number) = self.receipt_number {
+            *receipt_number = receipt_number.trim().to_string();
+        }
+        if let Some(ref mut supplier) = self.supplier {
+            *supplier = supplier.trim().to_string();
+        }
+        if let Some(ref mut reference_number) = self.reference_number {
+            *reference_number = reference_number.trim().to_string();
+        }
+        if let Some(ref mut received_by) = self.received_by {
+            *received_by = received_by.trim().to_string();
         }
         self
     }
@@ -55,40 +94,47 @@ pub async fn create_receipt(
         .validate()
         .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
 
-    let receipt_id = request
-        .receipt_id
-        .unwrap_or_else(|| Uuid::new_v4().to_string());
-
     let item = receipt::find_item_by_sku(&db, &request.sku)
         .await?
         .ok_or_else(|| AppError::not_found(format!("Item with SKU '{}' not found", request.sku)))?;
 
-    let location = receipt::find_location_by_code(&db, &request.location_code)
-        .await?
-        .ok_or_else(|| {
-            AppError::not_found(format!(
-                "Location with code '{}' not found",
-                request.location_code
-            ))
-        })?;
-
-    let ledger_entry_id = receipt::create_receipt_ledger_entry(
-        &db,
-        item.id,
-        location.id,
-        request.quantity,
-        &receipt_id,
-    )
-    .await?;
+    let location = match (&request.location_code, request.warehouse_id) {
+        (Some(location_code), None) => receipt::find_location_by_code(&db, location_code)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with code '{}' not found", location_code))
+            })?,
+        (None, Some(warehouse_id)) => putaway::find_receiving_location(&db, warehouse_id).await?,
+        _ => {
+            return Err(AppError::bad_request(
+                "Exactly one of location_code or warehouse_id is required",
+            ));
+        }
+    };
+
+    let header = ReceiptHeader {
+        receipt_number: request.receipt_number,
+        supplier: request.supplier,
+        reference_number: request.reference_number,
+        received_by: request.received_by,
+        ..ReceiptHeader::default()
+    };
+
+    let (receipt, received, putaway_tasks) =
+        receipt::create_receipt(&db, &header, item.id, location.id, request.quantity).await?;
 
     Ok((
         StatusCode::CREATED,
         Json(json!({
-            "receipt_id": receipt_id,
+            "receipt_id": receipt.id,
+            "receipt_number": receipt.receipt_number,
+            "receipt_line_id": received.line.id,
             "item_id": item.id,
             "location_id": location.id,
             "quantity": request.quantity,
-            "ledger_entry_id": ledger_entry_id
+            "ledger_entry_id": received.posted.ledger_entry_id(),
+            "held_movement_id": received.posted.held_movement_id(),
+            "putaway_task_ids": putaway_tasks.iter().map(|task| task.id).collect::<Vec<_>>()
         })),
     ))
 }
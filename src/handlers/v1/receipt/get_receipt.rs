@@ -0,0 +1,59 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+
+pub async fn get_receipt(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Receipt ID must be a positive integer",
+        ));
+    }
+
+    let result = receipt::get_receipt(&db, id).await?;
+
+    let lines: Vec<_> = result
+        .lines
+        .iter()
+        .map(|line| {
+            json!({
+                "receipt_line_id": line.id,
+                "item_id": line.item_id,
+                "location_id": line.location_id,
+                "quantity": line.quantity,
+                "ledger_entry_id": result.ledger_entry_id(line), // None while held by a cycle count
+                "held_movement_id": line.held_movement_id,
+                "po_line_id": line.po_line_id,
+                "created_at": line.created_at
+            })
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "receipt_id": result.receipt.id,
+            "receipt_number": result.receipt.receipt_number,
+            "supplier": result.receipt.supplier,
+            "supplier_id": result.receipt.supplier_id,
+            "purchase_order_id": result.receipt.purchase_order_id,
+            "reference_number": result.receipt.reference_number,
+            "received_by": result.receipt.received_by,
+            "status": result.receipt.status,
+            "received_at": result.receipt.received_at,
+            "created_at": result.receipt.created_at,
+            "updated_at": result.receipt.updated_at,
+            "lines": lines
+        })),
+    ))
+}
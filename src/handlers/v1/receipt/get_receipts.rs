@@ -0,0 +1,77 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::services::receipt::{self, ReceiptFilter};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetReceiptsQuery {
+    pub status: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Supplier must be between 1 and 255 characters"
+    ))]
+    pub supplier: Option<String>,
+
+    pub from: Option<DateTimeWithTimeZone>,
+
+    pub to: Option<DateTimeWithTimeZone>,
+
+    #[validate(range(min = 0, message = "after must not be negative"))]
+    pub after: Option<i32>,
+
+    #[validate(range(min = 1, max = 1000, message = "limit must be between 1 and 1000"))]
+    pub limit: Option<u64>,
+}
+
+impl GetReceiptsQuery {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut status) = self.status {
+            *status = status.trim().to_uppercase();
+        }
+        if let Some(ref mut supplier) = self.supplier {
+            *supplier = supplier.trim().to_string();
+        }
+        self
+    }
+}
+
+pub async fn get_receipts(
+    State(db): State<DatabaseConnection>,
+    Query(query): Query<GetReceiptsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = query.trim_fields();
+
+    query
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let filter = ReceiptFilter {
+        status: query.status,
+        supplier: query.supplier,
+        from: query.from,
+        to: query.to,
+    };
+
+    let page =
+        receipt::list_receipts(&db, &filter, query.after, query.limit.unwrap_or(100)).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "receipts": page.receipts,
+            "next_cursor": page.next_cursor
+        })),
+    ))
+}
@@ -1,7 +1,13 @@
 pub mod bulk_receipt;
 pub mod create_receipt;
 pub mod generate_sample;
+pub mod get_receipt;
+pub mod get_receipts;
+pub mod reverse_receipt;
 
 pub use bulk_receipt::bulk_receipt;
 pub use create_receipt::create_receipt;
 pub use generate_sample::generate_sample;
+pub use get_receipt::get_receipt;
+pub use get_receipts::get_receipts;
+pub use reverse_receipt::reverse_receipt;
@@ -0,0 +1,97 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::entities::supplier::{ActiveModel, Column, CreateSupplier, Entity};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CreateSupplierRequest {
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Code must be between 1 and 50 characters"
+    ))]
+    pub code: String,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+
+    pub is_active: Option<bool>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Over-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub over_receipt_tolerance_percent: Option<i32>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Under-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub under_receipt_tolerance_percent: Option<i32>,
+}
+
+impl CreateSupplierRequest {
+    pub fn trim_fields(mut self) -> Self {
+        self.code = self.code.trim().to_string();
+        self.name = self.name.trim().to_string();
+        self
+    }
+}
+
+pub async fn create_supplier(
+    State(db): State<DatabaseConnection>,
+    Json(payload): Json<CreateSupplierRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let existing = Entity::find()
+        .filter(Column::Code.eq(request.code.as_str()))
+        .count(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check supplier code: {}", e)))?;
+    if existing > 0 {
+        return Err(AppError::bad_request(format!(
+            "Supplier with code '{}' already exists",
+            request.code
+        )));
+    }
+
+    let create_dto = CreateSupplier {
+        code: request.code,
+        name: request.name,
+        is_active: request.is_active,
+        over_receipt_tolerance_percent: request.over_receipt_tolerance_percent,
+        under_receipt_tolerance_percent: request.under_receipt_tolerance_percent,
+    };
+
+    let mut active_model = <ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.code = Set(create_dto.code);
+    active_model.name = Set(create_dto.name);
+    active_model.is_active = Set(create_dto.is_active.unwrap_or(true));
+    active_model.over_receipt_tolerance_percent =
+        Set(create_dto.over_receipt_tolerance_percent.unwrap_or(0));
+    active_model.under_receipt_tolerance_percent =
+        Set(create_dto.under_receipt_tolerance_percent.unwrap_or(0));
+
+    let supplier = active_model
+        .insert(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create supplier: {}", e)))?;
+
+    Ok((StatusCode::CREATED, Json(json!(supplier))))
+}
@@ -0,0 +1,31 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::EntityTrait;
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::entities::supplier::Entity;
+use crate::utils::error::AppError;
+
+pub async fn get_supplier(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Supplier ID must be a positive integer",
+        ));
+    }
+
+    let supplier = Entity::find_by_id(id)
+        .one(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch supplier: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Supplier with id {} not found", id)))?;
+
+    Ok((StatusCode::OK, Json(json!(supplier))))
+}
@@ -0,0 +1,19 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::{EntityTrait, QueryOrder};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::entities::supplier::{Column, Entity};
+use crate::utils::error::AppError;
+
+pub async fn get_suppliers(
+    State(db): State<DatabaseConnection>,
+) -> Result<impl IntoResponse, AppError> {
+    let suppliers = Entity::find()
+        .order_by_asc(Column::Code)
+        .all(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch suppliers: {}", e)))?;
+
+    Ok((StatusCode::OK, Json(json!({ "suppliers": suppliers }))))
+}
@@ -0,0 +1,9 @@
+pub mod create_supplier;
+pub mod get_supplier;
+pub mod get_suppliers;
+pub mod update_supplier;
+
+pub use create_supplier::create_supplier;
+pub use get_supplier::get_supplier;
+pub use get_suppliers::get_suppliers;
+pub use update_supplier::update_supplier;
@@ -0,0 +1,133 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use serde::Deserialize;
+use serde_json::json;
+use validator::Validate;
+
+use crate::db::DatabaseConnection;
+use crate::entities::supplier::{ActiveModel, Column, Entity, UpdateSupplier};
+use crate::utils::error::AppError;
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateSupplierRequest {
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Code must be between 1 and 50 characters"
+    ))]
+    pub code: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Name must be between 1 and 255 characters"
+    ))]
+    pub name: Option<String>,
+
+    pub is_active: Option<bool>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Over-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub over_receipt_tolerance_percent: Option<i32>,
+
+    #[validate(range(
+        min = 0,
+        max = 100,
+        message = "Under-receipt tolerance must be between 0 and 100 percent"
+    ))]
+    pub under_receipt_tolerance_percent: Option<i32>,
+}
+
+impl UpdateSupplierRequest {
+    pub fn trim_fields(mut self) -> Self {
+        if let Some(ref mut code) = self.code {
+            *code = code.trim().to_string();
+        }
+        if let Some(ref mut name) = self.name {
+            *name = name.trim().to_string();
+        }
+        self
+    }
+}
+
+/// Update a supplier; tolerance changes apply to purchase orders created afterwards
+pub async fn update_supplier(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+    Json(payload): Json<UpdateSupplierRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Supplier ID must be a positive integer",
+        ));
+    }
+
+    let request = payload.trim_fields();
+
+    request
+        .validate()
+        .map_err(|e| AppError::validation(AppError::collect_validation_errors(&e)))?;
+
+    let supplier = Entity::find_by_id(id)
+        .one(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch supplier: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Supplier with id {} not found", id)))?;
+
+    if let Some(code) = &request.code {
+        let existing = Entity::find()
+            .filter(Column::Code.eq(code.as_str()))
+            .filter(Column::Id.ne(id))
+            .count(&db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to check supplier code: {}", e)))?;
+        if existing > 0 {
+            return Err(AppError::bad_request(format!(
+                "Supplier with code '{}' already exists",
+                code
+            )));
+        }
+    }
+
+    let update_dto = UpdateSupplier {
+        code: request.code,
+        name: request.name,
+        is_active: request.is_active,
+        over_receipt_tolerance_percent: request.over_receipt_tolerance_percent,
+        under_receipt_tolerance_percent: request.under_receipt_tolerance_percent,
+    };
+
+    let mut active_model: ActiveModel = supplier.into();
+
+    if let Some(code) = update_dto.code {
+        active_model.code = Set(code);
+    }
+    if let Some(name) = update_dto.name {
+        active_model.name = Set(name);
+    }
+    if let Some(is_active) = update_dto.is_active {
+        active_model.is_active = Set(is_active);
+    }
+    if let Some(percent) = update_dto.over_receipt_tolerance_percent {
+        active_model.over_receipt_tolerance_percent = Set(percent);
+    }
+    if let Some(percent) = update_dto.under_receipt_tolerance_percent {
+        active_model.under_receipt_tolerance_percent = Set(percent);
+    }
+    active_model.updated_at = Set(chrono::Utc::now().into());
+
+    let updated = active_model
+        .update(&db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update supplier: {}", e)))?;
+
+    Ok((StatusCode::OK, Json(json!(updated))))
+}
@@ -34,9 +34,11 @@ pub async fn complete_transfer(
                 "item_id": line.item_id,
                 "quantity": line.quantity
             })).collect::<Vec<_>>(),
-            "ledger_entries": result.ledger_entries.iter().map(|(source_id, dest_id)| json!({
-                "source_ledger_id": source_id,
-                "destination_ledger_id": dest_id
+            "ledger_entries": result.ledger_entries.iter().map(|(source, dest)| json!({
+                "source_ledger_id": source.ledger_entry_id(),
+                "destination_ledger_id": dest.ledger_entry_id(),
+                "source_held_movement_id": source.held_movement_id(),
+                "destination_held_movement_id": dest.held_movement_id()
             })).collect::<Vec<_>>(),
             "updated_at": result.transfer.updated_at
         })),
@@ -1,5 +1,7 @@
 pub mod create_transfer;
 pub mod complete_transfer;
+pub mod reverse_transfer;
 
 pub use create_transfer::create_transfer;
 pub use complete_transfer::complete_transfer;
+pub use reverse_transfer::reverse_transfer;
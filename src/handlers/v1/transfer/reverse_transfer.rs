@@ -0,0 +1,42 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde_json::json;
+
+use crate::db::DatabaseConnection;
+use crate::services::transfer;
+use crate::utils::error::AppError;
+
+pub async fn reverse_transfer(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    if id <= 0 {
+        return Err(AppError::bad_request(
+            "Transfer ID must be a positive integer",
+        ));
+    }
+
+    let result = transfer::reverse_transfer(&db, id).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "transfer_id": result.transfer.id,
+            "from_location_id": result.transfer.from_location_id,
+            "to_location_id": result.transfer.to_location_id,
+            "status": result.transfer.status,
+            "reversal_entries": result.reversals.iter().map(|entry| json!({
+                "ledger_entry_id": entry.id,
+                "reverses_entry_id": entry.reverses_entry_id,
+                "item_id": entry.item_id,
+                "location_id": entry.location_id,
+                "quantity_change": entry.quantity_change
+            })).collect::<Vec<_>>(),
+            "updated_at": result.transfer.updated_at
+        })),
+    ))
+}
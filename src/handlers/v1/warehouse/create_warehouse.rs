@@ -7,7 +7,9 @@ use validator::Validate;
 use crate::db::DatabaseConnection;
 use crate::entities::warehouse::{ActiveModel, CreateWarehouse};
 use crate::utils::error::AppError;
-use crate::utils::validation::validate_timezone;
+use crate::utils::validation::{
+    validate_count_freeze_mode, validate_timezone, validate_working_days,
+};
 
 #[derive(Debug, Clone, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -30,6 +32,14 @@ pub struct CreateWarehouseRequest {
     pub timezone: String,
 
     pub is_active: Option<bool>,
+
+    #[validate(custom(function = "validate_count_freeze_mode"))]
+    pub count_freeze_mode: Option<String>,
+
+    #[validate(custom(function = "validate_working_days"))]
+    pub working_days: Option<String>,
+
+    pub allow_negative_stock: Option<bool>,
 }
 
 impl CreateWarehouseRequest {
@@ -37,6 +47,12 @@ impl CreateWarehouseRequest {
         self.name = self.name.trim().to_string();
         self.address = self.address.trim().to_string();
         self.timezone = self.timezone.trim().to_string();
+        if let Some(ref mut count_freeze_mode) = self.count_freeze_mode {
+            *count_freeze_mode = count_freeze_mode.trim().to_uppercase();
+        }
+        if let Some(ref mut working_days) = self.working_days {
+            *working_days = working_days.replace(' ', "").to_uppercase();
+        }
         self
     }
 }
@@ -56,6 +72,9 @@ pub async fn create_warehouse(
         address: request.address,
         timezone: request.timezone,
         is_active: request.is_active,
+        count_freeze_mode: request.count_freeze_mode,
+        working_days: request.working_days,
+        allow_negative_stock: request.allow_negative_stock,
     };
 
     let mut active_model = <ActiveModel as sea_orm::ActiveModelTrait>::default();
@@ -63,6 +82,13 @@ pub async fn create_warehouse(
     active_model.address = Set(create_dto.address);
     active_model.timezone = Set(create_dto.timezone);
     active_model.is_active = Set(create_dto.is_active.unwrap_or(true));
+    active_model.count_freeze_mode = Set(create_dto
+        .count_freeze_mode
+        .unwrap_or_else(|| "REJECT".to_string()));
+    active_model.working_days = Set(create_dto
+        .working_days
+        .unwrap_or_else(|| "MON,TUE,WED,THU,FRI".to_string()));
+    active_model.allow_negative_stock = Set(create_dto.allow_negative_stock.unwrap_or(false));
 
     let warehouse = active_model
         .insert(&db)
@@ -10,9 +10,12 @@ use serde_json::json;
 use validator::Validate;
 
 use crate::db::DatabaseConnection;
+use crate::entities::location;
 use crate::entities::warehouse::{ActiveModel, Entity};
 use crate::utils::error::AppError;
-use crate::utils::validation::validate_timezone;
+use crate::utils::validation::{
+    validate_count_freeze_mode, validate_timezone, validate_working_days,
+};
 
 #[derive(Debug, Clone, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -35,6 +38,18 @@ pub struct UpdateWarehouseRequest {
     pub timezone: Option<String>,
 
     pub is_active: Option<bool>,
+
+    #[validate(custom(function = "validate_count_freeze_mode"))]
+    pub count_freeze_mode: Option<String>,
+
+    #[validate(custom(function = "validate_working_days"))]
+    pub working_days: Option<String>,
+
+    pub allow_negative_stock: Option<bool>,
+
+    // A location of this warehouse that receipts can be posted into before putaway
+    #[validate(range(min = 1, message = "receivingLocationId must be a positive integer"))]
+    pub receiving_location_id: Option<i32>,
 }
 
 impl UpdateWarehouseRequest {
@@ -48,6 +63,12 @@ impl UpdateWarehouseRequest {
         if let Some(ref mut timezone) = self.timezone {
             *timezone = timezone.trim().to_string();
         }
+        if let Some(ref mut count_freeze_mode) = self.count_freeze_mode {
+            *count_freeze_mode = count_freeze_mode.trim().to_uppercase();
+        }
+        if let Some(ref mut working_days) = self.working_days {
+            *working_days = working_days.replace(' ', "").to_uppercase();
+        }
         self
     }
 }
@@ -75,6 +96,22 @@ pub async fn update_warehouse(
         .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
         .ok_or_else(|| AppError::not_found(format!("Warehouse with id {} not found", id)))?;
 
+    if let Some(location_id) = request.receiving_location_id {
+        let location = location::Entity::find_by_id(location_id)
+            .one(&db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with id {} not found", location_id))
+            })?;
+        if location.warehouse_id != warehouse.id {
+            return Err(AppError::bad_request(format!(
+                "Location '{}' is not in warehouse {}",
+                location.code, warehouse.id
+            )));
+        }
+    }
+
     let mut active_model: ActiveModel = warehouse.into();
 
     if let Some(name) = request.name {
@@ -89,6 +126,18 @@ pub async fn update_warehouse(
     if let Some(is_active) = request.is_active {
         active_model.is_active = Set(is_active);
     }
+    if let Some(count_freeze_mode) = request.count_freeze_mode {
+        active_model.count_freeze_mode = Set(count_freeze_mode);
+    }
+    if let Some(working_days) = request.working_days {
+        active_model.working_days = Set(working_days);
+    }
+    if let Some(allow_negative_stock) = request.allow_negative_stock {
+        active_model.allow_negative_stock = Set(allow_negative_stock);
+    }
+    if let Some(receiving_location_id) = request.receiving_location_id {
+        active_model.receiving_location_id = Set(Some(receiving_location_id));
+    }
 
     let updated = active_model
         .update(&db)
@@ -2,6 +2,7 @@ pub mod config;
 pub mod db;
 pub mod entities;
 pub mod handlers;
+pub mod middleware;
 pub mod routes;
 pub mod server;
 pub mod services;
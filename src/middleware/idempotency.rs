@@ -0,0 +1,307 @@
+use axum::{
+    BoxError,
+    body::{Body, Bytes},
+    extract::{OriginalUri, Request, State},
+    http::{HeaderName, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use http_body::Frame;
+use http_body_util::StreamBody;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::db::DatabaseConnection;
+use crate::services::idempotency::{self, StoredResponse};
+use crate::utils::error::AppError;
+
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Run a state-changing request at most once per `Idempotency-Key` header. A retry gets the
+/// stored response, and a key reused for a different request is refused. Requests without the
+/// header, and GET/HEAD/OPTIONS, pass straight through
+pub async fn idempotency(
+    State(db): State<DatabaseConnection>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let state_changing = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    let key = match request.headers().get(&IDEMPOTENCY_KEY) {
+        Some(value) if state_changing => value
+            .to_str()
+            .map_err(|_| AppError::bad_request("Idempotency-Key must be printable ASCII"))?
+            .trim()
+            .to_string(),
+        _ => return Ok(next.run(request).await),
+    };
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(AppError::bad_request(format!(
+            "Idempotency-Key must be 1 to {} characters",
+            MAX_KEY_LENGTH
+        )));
+    }
+
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.0.clone())
+        .unwrap_or_else(|| request.uri().clone());
+    let path = path
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/")
+        .to_string();
+
+    let multipart_boundary = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| multer::parse_boundary(content_type).ok());
+
+    if !idempotency::claim_key(&db, &key, &method, &path).await? {
+        let fingerprint = fingerprint_body(
+            &method,
+            &path,
+            multipart_boundary,
+            request.into_body(),
+            None,
+        )
+        .await;
+        return replay(&db, &key, fingerprint).await;
+    }
+
+    // Keeps the claim from being taken over until the request ends, or is dropped with it
+    let _heartbeat = ClaimHeartbeat::start(db.clone(), key.clone());
+
+    // The handler reads the body as it arrives while it is hashed, so uploads stay streamed
+    let (parts, body) = request.into_parts();
+    let (chunks_tx, mut chunks_rx) = mpsc::channel::<Result<Bytes, BoxError>>(2);
+    // Built from frames, as Body::from_stream would wrap the body's errors once more
+    let forwarded = Body::new(StreamBody::new(futures_util::stream::poll_fn(move |cx| {
+        chunks_rx
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data)))
+    })));
+    let (response, fingerprint) = tokio::join!(
+        next.run(Request::from_parts(parts, forwarded)),
+        fingerprint_body(&method, &path, multipart_boundary, body, Some(chunks_tx)),
+    );
+
+    // Only successes are kept. An error changed nothing, so the request may run again under the
+    // same key, e.g. once a validation error is fixed or a conflict such as missing stock clears
+    let fingerprint = match fingerprint {
+        Some(fingerprint) if response.status().is_success() => fingerprint,
+        _ => {
+            idempotency::release_key(&db, &key).await?;
+            return Ok(response);
+        }
+    };
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            idempotency::release_key(&db, &key).await?;
+            return Err(AppError::internal(format!(
+                "Failed to read response: {}",
+                e
+            )));
+        }
+    };
+
+    let stored = StoredResponse {
+        status: parts.status.as_u16() as i32,
+        content_type: parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()),
+        body: body.to_vec(),
+    };
+    // The request has taken effect, so its response goes back even if it cannot be stored.
+    // The claim then stays in progress until it times out, rather than letting a retry run
+    if let Err(e) = idempotency::complete_key(&db, &key, &fingerprint, stored).await {
+        tracing::error!("Idempotency-Key '{}': {}", key, e);
+    }
+
+    Ok(Response::from_parts(parts, Body::from(body)))
+}
+
+/// Hash the method, path and body, passing the body on to `forward` if given. The body is read
+/// to the end even if the receiver stops early, so the hash always covers all of it. None if the
+/// body could not be read
+///
+/// A multipart body is hashed by its fields rather than its bytes, as a client retrying an
+/// upload may send the same fields under a new boundary
+async fn fingerprint_body(
+    method: &str,
+    path: &str,
+    multipart_boundary: Option<String>,
+    body: Body,
+    forward: Option<mpsc::Sender<Result<Bytes, BoxError>>>,
+) -> Option<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+
+    let (copy_tx, copy_rx) = mpsc::channel(2);
+    let (read, fingerprint) = tokio::join!(read_body(body, forward, copy_tx), async {
+        match multipart_boundary {
+            Some(boundary) => hash_multipart(hasher, boundary, copy_rx).await,
+            None => hash_chunks(hasher, copy_rx).await,
+        }
+    });
+
+    fingerprint.filter(|_| read)
+}
+
+/// Read the body to the end, sending each chunk to `forward` if given and to `copy`. False if
+/// the body could not be read
+async fn read_body(
+    body: Body,
+    mut forward: Option<mpsc::Sender<Result<Bytes, BoxError>>>,
+    copy: mpsc::Sender<Bytes>,
+) -> bool {
+    let mut copy = Some(copy);
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => {
+                if let Some(sender) = &copy
+                    && sender.send(chunk.clone()).await.is_err()
+                {
+                    copy = None;
+                }
+                if let Some(sender) = &forward
+                    && sender.send(Ok(chunk)).await.is_err()
+                {
+                    forward = None;
+                }
+            }
+            Err(e) => {
+                if let Some(sender) = forward {
+                    let _ = sender.send(Err(e.into_inner())).await;
+                }
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+async fn hash_chunks(mut hasher: Sha256, mut chunks: mpsc::Receiver<Bytes>) -> Option<String> {
+    while let Some(chunk) = chunks.recv().await {
+        hasher.update(&chunk);
+    }
+
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Hash each field's name, file name, content type and a hash of its content, in order. None
+/// if the body is not a well-formed form
+async fn hash_multipart(
+    mut hasher: Sha256,
+    boundary: String,
+    mut chunks: mpsc::Receiver<Bytes>,
+) -> Option<String> {
+    let stream = futures_util::stream::poll_fn(move |cx| {
+        chunks
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(Ok::<_, std::convert::Infallible>))
+    });
+    let mut multipart = multer::Multipart::new(stream, boundary);
+
+    while let Some(mut field) = multipart.next_field().await.ok()? {
+        for part in [field.name(), field.file_name()] {
+            hasher.update(part.unwrap_or_default().as_bytes());
+            hasher.update(b"\n");
+        }
+        let content_type = field.content_type().map(|mime| mime.to_string());
+        hasher.update(content_type.unwrap_or_default().as_bytes());
+        hasher.update(b"\n");
+
+        let mut content = Sha256::new();
+        while let Some(chunk) = field.chunk().await.ok()? {
+            content.update(&chunk);
+        }
+        hasher.update(format!("{:x}\n", content.finalize()).as_bytes());
+    }
+
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+/// Refreshes a claim in the background until dropped
+struct ClaimHeartbeat(JoinHandle<()>);
+
+impl ClaimHeartbeat {
+    fn start(db: DatabaseConnection, key: String) -> Self {
+        Self(tokio::spawn(async move {
+            let period = Duration::from_secs(idempotency::CLAIM_HEARTBEAT_SECONDS);
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                ticks.tick().await;
+                if let Err(e) = idempotency::heartbeat_key(&db, &key).await {
+                    tracing::error!("Idempotency-Key '{}': {}", key, e);
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for ClaimHeartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Answer a request whose key is already held with the stored response
+async fn replay(
+    db: &DatabaseConnection,
+    key: &str,
+    fingerprint: Option<String>,
+) -> Result<Response, AppError> {
+    let stored = idempotency::find_key(db, key).await?;
+    let Some((stored, status)) =
+        stored.and_then(|stored| stored.response_status.map(|status| (stored, status)))
+    else {
+        return Err(AppError::idempotency_in_progress(format!(
+            "A request with Idempotency-Key '{}' is still being processed",
+            key
+        )));
+    };
+
+    if fingerprint.is_none() || fingerprint != stored.request_fingerprint {
+        return Err(AppError::idempotency_key_reused(format!(
+            "Idempotency-Key '{}' was already used for a different request to {} {}",
+            key, stored.request_method, stored.request_path
+        )));
+    }
+
+    let status = StatusCode::from_u16(status as u16)
+        .map_err(|e| AppError::internal(format!("Stored response has a bad status: {}", e)))?;
+    let mut response = (status, stored.response_body.unwrap_or_default()).into_response();
+    let headers = response.headers_mut();
+    match stored
+        .response_content_type
+        .and_then(|content_type| HeaderValue::from_str(&content_type).ok())
+    {
+        Some(content_type) => headers.insert(header::CONTENT_TYPE, content_type),
+        None => headers.remove(header::CONTENT_TYPE),
+    };
+    headers.insert(IDEMPOTENT_REPLAYED, HeaderValue::from_static("true"));
+
+    Ok(response)
+}
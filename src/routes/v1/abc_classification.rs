@@ -0,0 +1,12 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn abc_classification_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/abc-classifications",
+            axum::routing::get(handlers::get_abc_classes).post(handlers::classify_items),
+        )
+        .with_state(db)
+}
@@ -0,0 +1,12 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn adjustment_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/adjustments",
+            axum::routing::post(handlers::create_adjustment),
+        )
+        .with_state(db)
+}
@@ -0,0 +1,52 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn cycle_count_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/cycle-counts",
+            axum::routing::get(handlers::list_cycle_counts).post(handlers::create_cycle_count),
+        )
+        .route(
+            "/cycle-counts/schedule",
+            axum::routing::post(handlers::schedule_cycle_counts),
+        )
+        .route(
+            "/cycle-counts/:id",
+            axum::routing::get(handlers::get_cycle_count),
+        )
+        .route(
+            "/cycle-counts/:id/start",
+            axum::routing::post(handlers::start_cycle_count),
+        )
+        .route(
+            "/cycle-counts/:id/counts",
+            axum::routing::post(handlers::record_counts),
+        )
+        .route(
+            "/cycle-counts/:id/review",
+            axum::routing::get(handlers::review_cycle_count),
+        )
+        .route(
+            "/cycle-counts/:id/lines/:line_id/approve",
+            axum::routing::post(handlers::approve_line),
+        )
+        .route(
+            "/cycle-counts/:id/lines/:line_id/reject",
+            axum::routing::post(handlers::reject_line),
+        )
+        .route(
+            "/cycle-counts/:id/held-movements",
+            axum::routing::get(handlers::get_held_movements),
+        )
+        .route(
+            "/cycle-counts/:id/held-movements/:movement_id/discard",
+            axum::routing::post(handlers::discard_held_movement),
+        )
+        .route(
+            "/cycle-counts/:id/close",
+            axum::routing::post(handlers::close_cycle_count),
+        )
+        .with_state(db)
+}
@@ -0,0 +1,13 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn inventory_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/inventory", axum::routing::get(handlers::get_inventory))
+        .route(
+            "/warehouses/:id/inventory-snapshot",
+            axum::routing::get(handlers::export_snapshot),
+        )
+        .with_state(db)
+}
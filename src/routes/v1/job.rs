@@ -0,0 +1,13 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn job_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/jobs/:id", axum::routing::get(handlers::get_job))
+        .route(
+            "/jobs/:id/rejected-rows",
+            axum::routing::get(handlers::get_rejected_rows),
+        )
+        .with_state(db)
+}
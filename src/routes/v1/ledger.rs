@@ -0,0 +1,13 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn ledger_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/ledger", axum::routing::get(handlers::get_ledger))
+        .route(
+            "/ledger/verify",
+            axum::routing::get(handlers::verify_ledger),
+        )
+        .with_state(db)
+}
@@ -0,0 +1,19 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn mapping_profile_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/mapping-profiles",
+            axum::routing::get(handlers::get_mapping_profiles)
+                .post(handlers::create_mapping_profile),
+        )
+        .route(
+            "/mapping-profiles/:id",
+            axum::routing::get(handlers::get_mapping_profile)
+                .put(handlers::update_mapping_profile)
+                .delete(handlers::delete_mapping_profile),
+        )
+        .with_state(db)
+}
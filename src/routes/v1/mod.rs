@@ -1,21 +1,58 @@
+mod abc_classification;
+mod adjustment;
+mod cycle_count;
 mod health;
+mod inventory;
 mod item;
+mod job;
+mod ledger;
 mod location;
+mod mapping_profile;
 mod pick;
+mod purchase_order;
+mod putaway;
 mod receipt;
+mod supplier;
 mod transfer;
 mod warehouse;
 
 use crate::db::DatabaseConnection;
+use crate::middleware::idempotency::idempotency;
 use axum::Router;
+use tower_http::limit::RequestBodyLimitLayer;
+
+/// The extractors' default body limit, also applied to the body itself. It wraps the
+/// idempotency layer, which reads the whole body to fingerprint it
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
 
 pub fn create_v1_router(db: DatabaseConnection) -> Router {
+    let idempotency = axum::middleware::from_fn_with_state(db.clone(), idempotency);
+
     Router::new()
         .merge(health::health_routes())
         .merge(warehouse::warehouse_routes(db.clone()))
         .merge(location::location_routes(db.clone()))
         .merge(item::item_routes(db.clone()))
+        .merge(supplier::supplier_routes(db.clone()))
+        .merge(purchase_order::purchase_order_routes(db.clone()))
+        .merge(putaway::putaway_routes(db.clone()))
         .merge(receipt::receipt_routes(db.clone()))
         .merge(transfer::transfer_routes(db.clone()))
-        .merge(pick::pick_routes(db))
+        .merge(pick::pick_routes(db.clone()))
+        .merge(cycle_count::cycle_count_routes(db.clone()))
+        .merge(abc_classification::abc_classification_routes(db.clone()))
+        .merge(adjustment::adjustment_routes(db.clone()))
+        .merge(inventory::inventory_routes(db.clone()))
+        .merge(ledger::ledger_routes(db.clone()))
+        .merge(job::job_routes(db.clone()))
+        .merge(mapping_profile::mapping_profile_routes(db.clone()))
+        .route_layer(idempotency.clone())
+        .route_layer(RequestBodyLimitLayer::new(MAX_BODY_BYTES))
+        .merge(
+            receipt::bulk_receipt_routes(db)
+                .route_layer(idempotency)
+                .route_layer(RequestBodyLimitLayer::new(
+                    receipt::BULK_RECEIPT_MAX_UPLOAD_BYTES,
+                )),
+        )
 }
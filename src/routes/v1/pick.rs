@@ -13,5 +13,9 @@ pub fn pick_routes(db: DatabaseConnection) -> Router {
             "/pick-waves/:id/confirm-pick",
             axum::routing::post(handlers::confirm_pick),
         )
+        .route(
+            "/pick-waves/:id/reverse",
+            axum::routing::post(handlers::reverse_pick_wave),
+        )
         .with_state(db)
 }
@@ -0,0 +1,32 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn purchase_order_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/purchase-orders",
+            axum::routing::get(handlers::get_purchase_orders).post(handlers::create_purchase_order),
+        )
+        .route(
+            "/purchase-orders/:id",
+            axum::routing::get(handlers::get_purchase_order),
+        )
+        .route(
+            "/purchase-orders/:id/open",
+            axum::routing::post(handlers::open_purchase_order),
+        )
+        .route(
+            "/purchase-orders/:id/receive",
+            axum::routing::post(handlers::receive_purchase_order),
+        )
+        .route(
+            "/purchase-orders/:id/close",
+            axum::routing::post(handlers::close_purchase_order),
+        )
+        .route(
+            "/purchase-orders/:id/cancel",
+            axum::routing::post(handlers::cancel_purchase_order),
+        )
+        .with_state(db)
+}
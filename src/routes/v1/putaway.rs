@@ -0,0 +1,36 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn putaway_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/putaway-tasks",
+            axum::routing::get(handlers::get_putaway_tasks),
+        )
+        .route(
+            "/putaway-tasks/:id",
+            axum::routing::get(handlers::get_putaway_task),
+        )
+        .route(
+            "/putaway-tasks/:id/confirm",
+            axum::routing::post(handlers::confirm_putaway_task),
+        )
+        .route(
+            "/putaway-suggestions",
+            axum::routing::get(handlers::get_putaway_suggestions),
+        )
+        .route(
+            "/warehouses/:id/putaway-rules",
+            axum::routing::get(handlers::get_putaway_rules).put(handlers::update_putaway_rules),
+        )
+        .route(
+            "/pick-faces",
+            axum::routing::get(handlers::get_pick_faces).post(handlers::create_pick_face),
+        )
+        .route(
+            "/pick-faces/:id",
+            axum::routing::put(handlers::update_pick_face).delete(handlers::delete_pick_face),
+        )
+        .with_state(db)
+}
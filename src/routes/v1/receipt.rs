@@ -1,17 +1,36 @@
 use crate::db::DatabaseConnection;
 use crate::handlers;
 use axum::Router;
+use axum::extract::DefaultBodyLimit;
+
+/// Bulk receipt files are streamed, so they may exceed the default 2MB body limit
+pub const BULK_RECEIPT_MAX_UPLOAD_BYTES: usize = 256 * 1024 * 1024;
 
 pub fn receipt_routes(db: DatabaseConnection) -> Router {
     Router::new()
-        .route("/receipts", axum::routing::post(handlers::create_receipt))
         .route(
-            "/receipts/bulk",
-            axum::routing::post(handlers::bulk_receipt),
+            "/receipts",
+            axum::routing::get(handlers::get_receipts).post(handlers::create_receipt),
         )
         .route(
             "/receipts/generate-sample",
             axum::routing::get(handlers::generate_sample),
         )
+        .route("/receipts/:id", axum::routing::get(handlers::get_receipt))
+        .route(
+            "/receipts/:id/reverse",
+            axum::routing::post(handlers::reverse_receipt),
+        )
+        .with_state(db)
+}
+
+/// The bulk upload route, kept apart so the v1 router can wrap it in its own body limit. The
+/// extractor limit is lifted, as that limit wraps the body itself
+pub fn bulk_receipt_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route(
+            "/receipts/bulk",
+            axum::routing::post(handlers::bulk_receipt).layer(DefaultBodyLimit::disable()),
+        )
         .with_state(db)
 }
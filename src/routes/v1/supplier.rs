@@ -0,0 +1,15 @@
+use crate::db::DatabaseConnection;
+use crate::handlers;
+use axum::Router;
+
+pub fn supplier_routes(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/suppliers", axum::routing::get(handlers::get_suppliers))
+        .route("/suppliers", axum::routing::post(handlers::create_supplier))
+        .route("/suppliers/:id", axum::routing::get(handlers::get_supplier))
+        .route(
+            "/suppliers/:id",
+            axum::routing::put(handlers::update_supplier),
+        )
+        .with_state(db)
+}
@@ -9,5 +9,9 @@ pub fn transfer_routes(db: DatabaseConnection) -> Router {
             "/transfers/:id/complete",
             axum::routing::post(handlers::complete_transfer),
         )
+        .route(
+            "/transfers/:id/reverse",
+            axum::routing::post(handlers::reverse_transfer),
+        )
         .with_state(db)
 }
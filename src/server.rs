@@ -10,7 +10,9 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::config::Config;
 use crate::db;
+use crate::middleware::idempotency::{IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
 use crate::routes;
+use crate::services::import_job;
 
 pub fn init_tracing(log_level: &str) {
     let filter = tracing_subscriber::EnvFilter::try_from_default_env()
@@ -27,6 +29,8 @@ pub async fn create_app(database_url: &str) -> Result<Router, Box<dyn std::error
         .await
         .map_err(|e| format!("Failed to connect to database: {}", e))?;
 
+    import_job::spawn_worker(db.clone());
+
     let router = routes::create_router(db);
 
     let cors = CorsLayer::new()
@@ -49,7 +53,9 @@ pub async fn create_app(database_url: &str) -> Result<Router, Box<dyn std::error
             HeaderName::from_static("authorization"),
             HeaderName::from_static("accept"),
             HeaderName::from_static("x-requested-with"),
+            IDEMPOTENCY_KEY,
         ])
+        .expose_headers([IDEMPOTENT_REPLAYED])
         .allow_credentials(true);
 
     Ok(router.layer(
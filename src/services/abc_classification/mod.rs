@@ -0,0 +1,192 @@
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::item_abc_class;
+use crate::entities::ledger;
+use crate::entities::location;
+use crate::entities::warehouse;
+use crate::utils::error::AppError;
+
+/// Settings for an ABC classification run
+#[derive(Debug, Clone)]
+pub struct AbcOptions {
+    pub lookback_days: i64,
+    pub a_cutoff_percent: i32, // cumulative share of picks covered by A items
+    pub b_cutoff_percent: i32, // cumulative share of picks covered by A and B items
+}
+
+impl Default for AbcOptions {
+    fn default() -> Self {
+        Self {
+            lookback_days: 90,
+            a_cutoff_percent: 80,
+            b_cutoff_percent: 95,
+        }
+    }
+}
+
+/// Class for an item given the share of all picks made by the items ranked above it
+fn class_for_share(picks_before: i64, total_picks: i64, options: &AbcOptions) -> &'static str {
+    if total_picks == 0 {
+        return "C";
+    }
+
+    let share_before = picks_before * 100;
+    if share_before < i64::from(options.a_cutoff_percent) * total_picks {
+        "A"
+    } else if share_before < i64::from(options.b_cutoff_percent) * total_picks {
+        "B"
+    } else {
+        "C"
+    }
+}
+
+/// Rank a warehouse's items by pick velocity and store their ABC class
+///
+/// Velocity is the number of PICK ledger rows in the lookback window. Items are sorted by
+/// velocity and classified by the cumulative share of picks: the items making up the first
+/// a_cutoff_percent of picks are A, up to b_cutoff_percent are B, the rest (including items
+/// that moved through the warehouse but were never picked) are C. Previous classes for the
+/// warehouse are replaced.
+pub async fn classify_items(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+    options: AbcOptions,
+) -> Result<Vec<item_abc_class::Model>, AppError> {
+    if options.lookback_days <= 0 {
+        return Err(AppError::bad_request("lookback_days must be positive"));
+    }
+    if options.a_cutoff_percent <= 0
+        || options.a_cutoff_percent > options.b_cutoff_percent
+        || options.b_cutoff_percent > 100
+    {
+        return Err(AppError::bad_request(
+            "Cutoffs must satisfy 0 < a_cutoff_percent <= b_cutoff_percent <= 100",
+        ));
+    }
+
+    warehouse::Entity::find_by_id(warehouse_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Warehouse with id {} not found", warehouse_id))
+        })?;
+
+    let warehouse_locations = Query::select()
+        .column(location::Column::Id)
+        .from(location::Entity)
+        .and_where(location::Column::WarehouseId.eq(warehouse_id))
+        .to_owned();
+
+    let since = chrono::Utc::now() - chrono::Duration::days(options.lookback_days);
+
+    // (item_id, pick_count, pick_quantity), highest velocity first; reversed picks don't count
+    let velocities: Vec<(i32, i64, i64)> = ledger::Entity::find()
+        .select_only()
+        .column(ledger::Column::ItemId)
+        .column_as(ledger::Column::Id.count(), "pick_count")
+        .column_as(
+            Expr::expr(Expr::col(ledger::Column::QuantityChange).mul(-1)).sum(),
+            "pick_quantity",
+        )
+        .filter(ledger::Column::ReasonType.eq("PICK"))
+        .filter(
+            ledger::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(ledger::Column::ReversesEntryId)
+                    .from(ledger::Entity)
+                    .and_where(ledger::Column::ReversesEntryId.is_not_null())
+                    .to_owned(),
+            ),
+        )
+        .filter(ledger::Column::CreatedAt.gte(since))
+        .filter(ledger::Column::LocationId.in_subquery(warehouse_locations.clone()))
+        .group_by(ledger::Column::ItemId)
+        .order_by_desc(ledger::Column::Id.count())
+        .order_by_asc(ledger::Column::ItemId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to calculate pick velocity: {}", e)))?;
+
+    // Items that moved through the warehouse without being picked are C
+    let all_item_ids: Vec<i32> = ledger::Entity::find()
+        .select_only()
+        .column(ledger::Column::ItemId)
+        .distinct()
+        .filter(ledger::Column::LocationId.in_subquery(warehouse_locations))
+        .order_by_asc(ledger::Column::ItemId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse items: {}", e)))?;
+
+    let total_picks: i64 = velocities.iter().map(|(_, count, _)| count).sum();
+
+    let mut ranked = Vec::new();
+    let mut picks_before = 0i64;
+    for (item_id, pick_count, pick_quantity) in &velocities {
+        let class = class_for_share(picks_before, total_picks, &options);
+        ranked.push((*item_id, class, *pick_count, *pick_quantity));
+        picks_before += pick_count;
+    }
+    for item_id in all_item_ids {
+        if !velocities.iter().any(|(picked, _, _)| *picked == item_id) {
+            ranked.push((item_id, "C", 0, 0));
+        }
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    item_abc_class::Entity::delete_many()
+        .filter(item_abc_class::Column::WarehouseId.eq(warehouse_id))
+        .exec(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to clear ABC classes: {}", e)))?;
+
+    let mut classes = Vec::new();
+    for (item_id, class, pick_count, pick_quantity) in ranked {
+        let mut class_model = <item_abc_class::ActiveModel as sea_orm::ActiveModelTrait>::default();
+        class_model.item_id = Set(item_id);
+        class_model.warehouse_id = Set(warehouse_id);
+        class_model.class = Set(class.to_string());
+        class_model.pick_count = Set(i32::try_from(pick_count).unwrap_or(i32::MAX));
+        class_model.pick_quantity = Set(i32::try_from(pick_quantity).unwrap_or(i32::MAX));
+
+        let saved = class_model
+            .insert(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to store ABC class: {}", e)))?;
+
+        classes.push(saved);
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(classes)
+}
+
+/// Fetch the stored ABC classes for a warehouse, A items first
+pub async fn get_classes(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+) -> Result<Vec<item_abc_class::Model>, AppError> {
+    item_abc_class::Entity::find()
+        .filter(item_abc_class::Column::WarehouseId.eq(warehouse_id))
+        .order_by_asc(item_abc_class::Column::Class)
+        .order_by_desc(item_abc_class::Column::PickCount)
+        .order_by_asc(item_abc_class::Column::ItemId)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch ABC classes: {}", e)))
+}
@@ -0,0 +1,106 @@
+use sea_orm::{ActiveModelTrait, EntityTrait, Set, TransactionTrait};
+
+use crate::db::DatabaseConnection;
+use crate::entities::adjustment;
+use crate::entities::item;
+use crate::entities::location;
+use crate::services::inventory;
+use crate::services::ledger::{self, NewLedgerEntry, PostedEntry};
+use crate::utils::error::AppError;
+
+/// A stock correction for one item at one location
+#[derive(Debug, Clone)]
+pub struct NewAdjustment {
+    pub item_id: i32,
+    pub location_id: i32,
+    pub quantity_change: i32,
+    pub reason_code: String,
+    pub note: Option<String>,
+}
+
+/// Adjustment with the ledger entry (or held movement) it produced
+#[derive(Debug)]
+pub struct PostedAdjustment {
+    pub adjustment: adjustment::Model,
+    pub posted: PostedEntry,
+}
+
+/// Record an adjustment and post it to the ledger as an ADJUSTMENT entry
+/// Negative adjustments cannot take more than the available (unreserved) stock at the location
+pub async fn create_adjustment(
+    db: &DatabaseConnection,
+    new_adjustment: NewAdjustment,
+) -> Result<PostedAdjustment, AppError> {
+    if new_adjustment.quantity_change == 0 {
+        return Err(AppError::bad_request("Adjustment quantity cannot be zero"));
+    }
+
+    let item = item::Entity::find_by_id(new_adjustment.item_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to find item: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Item with id {} not found", new_adjustment.item_id))
+        })?;
+
+    location::Entity::find_by_id(new_adjustment.location_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to find location: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Location with id {} not found",
+                new_adjustment.location_id
+            ))
+        })?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    inventory::lock_stock(&txn, &[(item.id, new_adjustment.location_id)]).await?;
+
+    if new_adjustment.quantity_change < 0 {
+        let available =
+            inventory::calculate_available(&txn, item.id, new_adjustment.location_id).await?;
+        let requested = -new_adjustment.quantity_change;
+        if available < requested {
+            return Err(AppError::bad_request(format!(
+                "Insufficient stock for item '{}' (SKU: {}). Available: {}, Requested: {}",
+                item.name, item.sku, available, requested
+            )));
+        }
+    }
+
+    let mut adjustment_model = <adjustment::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    adjustment_model.item_id = Set(item.id);
+    adjustment_model.location_id = Set(new_adjustment.location_id);
+    adjustment_model.quantity_change = Set(new_adjustment.quantity_change);
+    adjustment_model.reason_code = Set(new_adjustment.reason_code);
+    adjustment_model.note = Set(new_adjustment.note);
+
+    let adjustment = adjustment_model
+        .insert(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create adjustment: {}", e)))?;
+
+    let posted = ledger::post_entry(
+        &txn,
+        NewLedgerEntry {
+            item_id: adjustment.item_id,
+            location_id: adjustment.location_id,
+            quantity_change: adjustment.quantity_change,
+            reason_type: "ADJUSTMENT".to_string(),
+            reference_type: Some("adjustment".to_string()),
+            reference_id: Some(adjustment.id),
+        },
+    )
+    .await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(PostedAdjustment { adjustment, posted })
+}
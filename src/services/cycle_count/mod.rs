@@ -0,0 +1,886 @@
+use sea_orm::prelude::Decimal;
+use sea_orm::sea_query::LockType;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::cycle;
+use crate::entities::cycle_count_line;
+use crate::entities::held_movement;
+use crate::entities::item;
+use crate::entities::ledger;
+use crate::entities::location;
+use crate::services::inventory;
+use crate::services::ledger as ledger_service;
+use crate::services::receipt;
+use crate::services::reservation as reservation_service;
+use crate::utils::error::AppError;
+
+/// Counted quantity for a single item in a cycle count
+#[derive(Debug, Clone)]
+pub struct CountItem {
+    pub sku: String,
+    pub counted_quantity: i32,
+}
+
+/// Counting controls chosen when a cycle count is opened
+#[derive(Debug, Clone, Default)]
+pub struct CycleCountOptions {
+    pub is_blind: bool,
+    pub recount_threshold_percent: Option<i32>,
+    pub recount_threshold_units: Option<i32>,
+    pub approval_value_limit: Option<Decimal>,
+}
+
+/// Cycle count with its lines
+#[derive(Debug)]
+pub struct CycleCountWithLines {
+    pub cycle_count: cycle::Model,
+    pub lines: Vec<cycle_count_line::Model>,
+}
+
+/// Cycle count line with the item's unit cost, for supervisor review
+#[derive(Debug)]
+pub struct ReviewLine {
+    pub line: cycle_count_line::Model,
+    pub unit_cost: Option<Decimal>,
+}
+
+/// Cycle count with unmasked lines for supervisor review
+#[derive(Debug)]
+pub struct CycleCountReview {
+    pub cycle_count: cycle::Model,
+    pub lines: Vec<ReviewLine>,
+}
+
+/// Closed cycle count with the COUNT ledger entries posted for its variances
+#[derive(Debug)]
+pub struct ClosedCycleCount {
+    pub cycle_count: cycle::Model,
+    pub lines: Vec<cycle_count_line::Model>,
+    pub ledger_entries: Vec<i32>,    // ledger entry IDs
+    pub applied_movements: Vec<i32>, // held movement IDs applied after the count
+}
+
+async fn find_cycle_count<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+) -> Result<cycle::Model, AppError> {
+    cycle::Entity::find_by_id(cycle_count_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch cycle count: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Cycle count with id {} not found", cycle_count_id))
+        })
+}
+
+/// Fetch a cycle count and lock it until the transaction ends, before changing its state or lines
+///
+/// FOR NO KEY UPDATE rather than FOR UPDATE: held movements referencing the count take a key share
+/// on it and must not wait here, as a close holding the count waits on their location. Where the
+/// location is locked as well (start and close) it is locked first.
+async fn lock_cycle_count<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+) -> Result<cycle::Model, AppError> {
+    cycle::Entity::find_by_id(cycle_count_id)
+        .lock(LockType::NoKeyUpdate)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch cycle count: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Cycle count with id {} not found", cycle_count_id))
+        })
+}
+
+/// Find the OPEN cycle count for a location, if any
+/// A location with an open count is frozen for stock movements
+///
+/// The location row is taken FOR SHARE first, so no count opens or closes on the location until
+/// the caller's transaction ends: a movement that finds the location unfrozen commits before a
+/// count snapshots it, and one that is held is there when the count applies its held movements.
+pub async fn find_open_cycle_count<C: ConnectionTrait>(
+    db: &C,
+    location_id: i32,
+) -> Result<Option<cycle::Model>, AppError> {
+    share_locations(db, &[location_id]).await?;
+
+    cycle::Entity::find()
+        .filter(cycle::Column::LocationId.eq(location_id))
+        .filter(cycle::Column::Status.eq("OPEN"))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check open cycle counts: {}", e)))
+}
+
+/// Open cycle counts of any of the locations, for checking many locations at once
+/// Locks the locations as find_open_cycle_count does
+pub async fn find_open_cycle_counts<C: ConnectionTrait>(
+    db: &C,
+    location_ids: &[i32],
+) -> Result<Vec<cycle::Model>, AppError> {
+    if location_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    share_locations(db, location_ids).await?;
+
+    cycle::Entity::find()
+        .filter(cycle::Column::LocationId.is_in(location_ids.iter().copied()))
+        .filter(cycle::Column::Status.eq("OPEN"))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check open cycle counts: {}", e)))
+}
+
+/// Take location rows FOR SHARE until the transaction ends, as every movement does before it
+/// checks or writes stock there. Shared locks do not wait on each other, only on a count opening
+/// or closing on the location (see lock_location)
+pub(crate) async fn share_locations<C: ConnectionTrait>(
+    db: &C,
+    location_ids: &[i32],
+) -> Result<(), AppError> {
+    location::Entity::find()
+        .select_only()
+        .column(location::Column::Id)
+        .filter(location::Column::Id.is_in(location_ids.iter().copied()))
+        .order_by_asc(location::Column::Id)
+        .lock_shared()
+        .into_tuple::<i32>()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to lock locations: {}", e)))?;
+
+    Ok(())
+}
+
+/// Lock a location against stock movements until the transaction ends
+///
+/// Waits for the movements in flight there to commit and keeps new ones waiting, so a count can
+/// snapshot the location or apply its held movements without one slipping past the freeze. The
+/// lock is FOR NO KEY UPDATE, which conflicts with the movements' FOR SHARE but not with the key
+/// share of rows that merely reference the location. Stock locks come before it and ledger chain
+/// locks after it.
+async fn lock_location<C: ConnectionTrait>(
+    db: &C,
+    location_id: i32,
+) -> Result<location::Model, AppError> {
+    location::Entity::find_by_id(location_id)
+        .lock(LockType::NoKeyUpdate)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to find location: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Location with id {} not found", location_id)))
+}
+
+async fn find_lines<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+) -> Result<Vec<cycle_count_line::Model>, AppError> {
+    cycle_count_line::Entity::find()
+        .filter(cycle_count_line::Column::CycleCountId.eq(cycle_count_id))
+        .order_by_asc(cycle_count_line::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch cycle count lines: {}", e)))
+}
+
+async fn find_line<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+    line_id: i32,
+) -> Result<cycle_count_line::Model, AppError> {
+    cycle_count_line::Entity::find_by_id(line_id)
+        .filter(cycle_count_line::Column::CycleCountId.eq(cycle_count_id))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch cycle count line: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Line with id {} not found in cycle count {}",
+                line_id, cycle_count_id
+            ))
+        })
+}
+
+/// Whether the absolute variance of a first count is large enough to require a recount
+fn exceeds_recount_threshold(cycle_count: &cycle::Model, expected: i32, variance: i32) -> bool {
+    let over_units = cycle_count
+        .recount_threshold_units
+        .is_some_and(|units| variance > units);
+
+    // With nothing expected, any variance is over a percent threshold
+    let over_percent = cycle_count
+        .recount_threshold_percent
+        .is_some_and(|percent| {
+            i64::from(variance) * 100 > i64::from(percent) * i64::from(expected.abs())
+        });
+
+    over_units || over_percent
+}
+
+/// Decide the status of a line after a count has been recorded
+/// The first count over a recount threshold goes to RECOUNT; a variance worth more than the
+/// approval limit waits for a supervisor in PENDING_APPROVAL. An item without a unit_cost cannot
+/// be valued, so any variance on it waits for approval when the count has a limit
+fn evaluate_line_status(
+    cycle_count: &cycle::Model,
+    expected: i32,
+    counted: i32,
+    attempts: i32,
+    unit_cost: Option<Decimal>,
+) -> &'static str {
+    let variance = (counted - expected).abs();
+    if variance == 0 {
+        return "COUNTED";
+    }
+
+    if attempts == 1 && exceeds_recount_threshold(cycle_count, expected, variance) {
+        return "RECOUNT";
+    }
+
+    if let Some(limit) = cycle_count.approval_value_limit
+        && unit_cost.is_none_or(|unit_cost| Decimal::from(variance) * unit_cost > limit)
+    {
+        return "PENDING_APPROVAL";
+    }
+
+    "COUNTED"
+}
+
+fn ensure_open(cycle_count: &cycle::Model) -> Result<(), AppError> {
+    if cycle_count.status != "OPEN" {
+        return Err(AppError::bad_request(format!(
+            "Cycle count with id {} is not in OPEN status (current status: {})",
+            cycle_count.id, cycle_count.status
+        )));
+    }
+    Ok(())
+}
+
+/// Snapshot expected_quantity from the ledger for every item with stock at the location
+async fn snapshot_lines<C: ConnectionTrait>(
+    db: &C,
+    cycle_count: &cycle::Model,
+) -> Result<Vec<cycle_count_line::Model>, AppError> {
+    // Every item that has ever moved through the location is a candidate for the snapshot
+    let item_ids: Vec<i32> = ledger::Entity::find()
+        .select_only()
+        .column(ledger::Column::ItemId)
+        .distinct()
+        .filter(ledger::Column::LocationId.eq(cycle_count.location_id))
+        .order_by_asc(ledger::Column::ItemId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch items at location: {}", e)))?;
+
+    let mut lines = Vec::new();
+    for item_id in item_ids {
+        let on_hand =
+            inventory::calculate_on_hand(db, item_id, cycle_count.location_id, None).await?;
+        if on_hand == 0 {
+            continue;
+        }
+
+        let mut line_model =
+            <cycle_count_line::ActiveModel as sea_orm::ActiveModelTrait>::default();
+        line_model.cycle_count_id = Set(cycle_count.id);
+        line_model.item_id = Set(item_id);
+        line_model.expected_quantity = Set(on_hand);
+        line_model.counted_quantity = Set(None);
+        line_model.status = Set("PENDING".to_string());
+        line_model.count_attempts = Set(0);
+
+        let line = line_model
+            .insert(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create cycle count line: {}", e)))?;
+
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+fn validate_options(options: &CycleCountOptions) -> Result<(), AppError> {
+    if options.recount_threshold_percent.is_some_and(|p| p < 0)
+        || options.recount_threshold_units.is_some_and(|u| u < 0)
+    {
+        return Err(AppError::bad_request(
+            "Recount thresholds cannot be negative",
+        ));
+    }
+    Ok(())
+}
+
+/// Move a cycle count to OPEN with the chosen counting controls
+fn set_open(count_model: &mut cycle::ActiveModel, options: &CycleCountOptions) {
+    count_model.status = Set("OPEN".to_string());
+    count_model.is_blind = Set(options.is_blind);
+    count_model.recount_threshold_percent = Set(options.recount_threshold_percent);
+    count_model.recount_threshold_units = Set(options.recount_threshold_units);
+    count_model.approval_value_limit = Set(options.approval_value_limit);
+    count_model.updated_at = Set(chrono::Utc::now().into());
+}
+
+/// Open a cycle count for a location
+/// Snapshots expected_quantity from the ledger for every item with stock at the location, once
+/// the movements in flight there have committed.
+/// If the location has a SCHEDULED count, that count is opened instead of creating a new one.
+pub async fn open_cycle_count(
+    db: &DatabaseConnection,
+    location_id: i32,
+    options: CycleCountOptions,
+) -> Result<CycleCountWithLines, AppError> {
+    validate_options(&options)?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    lock_location(&txn, location_id).await?;
+
+    if let Some(existing) = find_open_cycle_count(&txn, location_id).await? {
+        return Err(AppError::bad_request(format!(
+            "Location with id {} already has an open cycle count (id: {})",
+            location_id, existing.id
+        )));
+    }
+
+    let scheduled = cycle::Entity::find()
+        .filter(cycle::Column::LocationId.eq(location_id))
+        .filter(cycle::Column::Status.eq("SCHEDULED"))
+        .order_by_asc(cycle::Column::ScheduledFor)
+        .one(&txn)
+        .await
+        .map_err(|e| {
+            AppError::internal(format!("Failed to check scheduled cycle counts: {}", e))
+        })?;
+
+    let cycle_count = match scheduled {
+        Some(scheduled) => {
+            let mut count_update: cycle::ActiveModel = scheduled.into();
+            set_open(&mut count_update, &options);
+
+            count_update
+                .update(&txn)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to open cycle count: {}", e)))?
+        }
+        None => {
+            let mut count_model = <cycle::ActiveModel as sea_orm::ActiveModelTrait>::default();
+            count_model.location_id = Set(location_id);
+            set_open(&mut count_model, &options);
+
+            count_model
+                .insert(&txn)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to create cycle count: {}", e)))?
+        }
+    };
+
+    let lines = snapshot_lines(&txn, &cycle_count).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(CycleCountWithLines { cycle_count, lines })
+}
+
+/// Start a SCHEDULED cycle count, snapshotting expected quantities as it opens
+pub async fn start_cycle_count(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+    options: CycleCountOptions,
+) -> Result<CycleCountWithLines, AppError> {
+    validate_options(&options)?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let location_id = find_cycle_count(&txn, cycle_count_id).await?.location_id;
+    lock_location(&txn, location_id).await?;
+
+    let cycle_count = lock_cycle_count(&txn, cycle_count_id).await?;
+    if cycle_count.status != "SCHEDULED" {
+        return Err(AppError::bad_request(format!(
+            "Cycle count with id {} is not in SCHEDULED status (current status: {})",
+            cycle_count.id, cycle_count.status
+        )));
+    }
+
+    if let Some(existing) = find_open_cycle_count(&txn, cycle_count.location_id).await? {
+        return Err(AppError::bad_request(format!(
+            "Location with id {} already has an open cycle count (id: {})",
+            cycle_count.location_id, existing.id
+        )));
+    }
+
+    let mut count_update: cycle::ActiveModel = cycle_count.into();
+    set_open(&mut count_update, &options);
+
+    let cycle_count = count_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start cycle count: {}", e)))?;
+
+    let lines = snapshot_lines(&txn, &cycle_count).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(CycleCountWithLines { cycle_count, lines })
+}
+
+/// Fetch a cycle count with its lines
+pub async fn get_cycle_count(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+) -> Result<CycleCountWithLines, AppError> {
+    let cycle_count = find_cycle_count(db, cycle_count_id).await?;
+    let lines = find_lines(db, cycle_count_id).await?;
+
+    Ok(CycleCountWithLines { cycle_count, lines })
+}
+
+/// Fetch a cycle count with unmasked lines and item costs for supervisor review
+pub async fn review_cycle_count(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+) -> Result<CycleCountReview, AppError> {
+    let cycle_count = find_cycle_count(db, cycle_count_id).await?;
+    let lines = find_lines(db, cycle_count_id).await?;
+
+    let item_ids: Vec<i32> = lines.iter().map(|line| line.item_id).collect();
+    let items = item::Entity::find()
+        .filter(item::Column::Id.is_in(item_ids))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch items: {}", e)))?;
+
+    let lines = lines
+        .into_iter()
+        .map(|line| {
+            let unit_cost = items
+                .iter()
+                .find(|item| item.id == line.item_id)
+                .and_then(|item| item.unit_cost);
+            ReviewLine { line, unit_cost }
+        })
+        .collect();
+
+    Ok(CycleCountReview { cycle_count, lines })
+}
+
+/// Record counted quantities for items in an OPEN cycle count
+/// Items that were not part of the snapshot get a new line with the current on-hand as expected.
+/// Each count re-evaluates the line against the recount thresholds and approval limit.
+pub async fn record_counts(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+    items: Vec<CountItem>,
+) -> Result<CycleCountWithLines, AppError> {
+    if items.is_empty() {
+        return Err(AppError::bad_request("At least one item is required"));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let cycle_count = lock_cycle_count(&txn, cycle_count_id).await?;
+    ensure_open(&cycle_count)?;
+
+    for count_item in &items {
+        if count_item.counted_quantity < 0 {
+            return Err(AppError::bad_request(format!(
+                "Counted quantity cannot be negative for item with SKU '{}'",
+                count_item.sku
+            )));
+        }
+
+        let item = receipt::find_item_by_sku(&txn, &count_item.sku)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Item with SKU '{}' not found", count_item.sku))
+            })?;
+
+        let existing_line = cycle_count_line::Entity::find()
+            .filter(cycle_count_line::Column::CycleCountId.eq(cycle_count_id))
+            .filter(cycle_count_line::Column::ItemId.eq(item.id))
+            .one(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch cycle count line: {}", e)))?;
+
+        match existing_line {
+            Some(line) => {
+                let attempts = line.count_attempts + 1;
+                let status = evaluate_line_status(
+                    &cycle_count,
+                    line.expected_quantity,
+                    count_item.counted_quantity,
+                    attempts,
+                    item.unit_cost,
+                );
+
+                let mut line_update: cycle_count_line::ActiveModel = line.into();
+                line_update.counted_quantity = Set(Some(count_item.counted_quantity));
+                line_update.count_attempts = Set(attempts);
+                line_update.status = Set(status.to_string());
+                line_update.approved_by = Set(None);
+                line_update.approved_at = Set(None);
+
+                line_update.update(&txn).await.map_err(|e| {
+                    AppError::internal(format!("Failed to update cycle count line: {}", e))
+                })?;
+            }
+            None => {
+                let expected =
+                    inventory::calculate_on_hand(&txn, item.id, cycle_count.location_id, None)
+                        .await?;
+                let status = evaluate_line_status(
+                    &cycle_count,
+                    expected,
+                    count_item.counted_quantity,
+                    1,
+                    item.unit_cost,
+                );
+
+                let mut line_model =
+                    <cycle_count_line::ActiveModel as sea_orm::ActiveModelTrait>::default();
+                line_model.cycle_count_id = Set(cycle_count_id);
+                line_model.item_id = Set(item.id);
+                line_model.expected_quantity = Set(expected);
+                line_model.counted_quantity = Set(Some(count_item.counted_quantity));
+                line_model.count_attempts = Set(1);
+                line_model.status = Set(status.to_string());
+
+                line_model.insert(&txn).await.map_err(|e| {
+                    AppError::internal(format!("Failed to create cycle count line: {}", e))
+                })?;
+            }
+        }
+    }
+
+    let lines = find_lines(&txn, cycle_count_id).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(CycleCountWithLines { cycle_count, lines })
+}
+
+/// Approve the variance of a line that is waiting for supervisor approval
+pub async fn approve_line(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+    line_id: i32,
+    approved_by: &str,
+) -> Result<cycle_count_line::Model, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    // A close in progress finishes first, so a line is never decided after the count closed
+    let cycle_count = lock_cycle_count(&txn, cycle_count_id).await?;
+    ensure_open(&cycle_count)?;
+
+    let line = find_line(&txn, cycle_count_id, line_id).await?;
+    if line.status != "PENDING_APPROVAL" {
+        return Err(AppError::bad_request(format!(
+            "Line with id {} is not in PENDING_APPROVAL status (current status: {})",
+            line_id, line.status
+        )));
+    }
+
+    let mut line_update: cycle_count_line::ActiveModel = line.into();
+    line_update.status = Set("APPROVED".to_string());
+    line_update.approved_by = Set(Some(approved_by.to_string()));
+    line_update.approved_at = Set(Some(chrono::Utc::now().into()));
+
+    let line = line_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to approve cycle count line: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(line)
+}
+
+/// Reject the variance of a line that is waiting for supervisor approval
+/// The line goes back to RECOUNT so the location is counted again
+pub async fn reject_line(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+    line_id: i32,
+) -> Result<cycle_count_line::Model, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    // A close in progress finishes first, so a line is never decided after the count closed
+    let cycle_count = lock_cycle_count(&txn, cycle_count_id).await?;
+    ensure_open(&cycle_count)?;
+
+    let line = find_line(&txn, cycle_count_id, line_id).await?;
+    if line.status != "PENDING_APPROVAL" {
+        return Err(AppError::bad_request(format!(
+            "Line with id {} is not in PENDING_APPROVAL status (current status: {})",
+            line_id, line.status
+        )));
+    }
+
+    let mut line_update: cycle_count_line::ActiveModel = line.into();
+    line_update.status = Set("RECOUNT".to_string());
+
+    let line = line_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to reject cycle count line: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(line)
+}
+
+/// Close an OPEN cycle count
+/// Posts a COUNT ledger entry for every line whose counted quantity differs from the expected one.
+/// Every line must be COUNTED or APPROVED first. Movements held while the location was frozen
+/// are applied after the count entries, in the order they arrived. If the counted stock cannot
+/// cover one of them the close fails with INSUFFICIENT_STOCK and the count stays OPEN, so the
+/// movement can be discarded or the line recounted; a pick or transfer is never left COMPLETED
+/// without its stock having moved.
+pub async fn close_cycle_count(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+) -> Result<ClosedCycleCount, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    // Movements still posting or being held at the location finish first
+    let location_id = find_cycle_count(&txn, cycle_count_id).await?.location_id;
+    lock_location(&txn, location_id).await?;
+
+    let cycle_count = lock_cycle_count(&txn, cycle_count_id).await?;
+    ensure_open(&cycle_count)?;
+
+    let lines = find_lines(&txn, cycle_count_id).await?;
+
+    let unresolved: Vec<String> = lines
+        .iter()
+        .filter(|line| line.status != "COUNTED" && line.status != "APPROVED")
+        .map(|line| format!("{} ({})", line.id, line.status))
+        .collect();
+
+    if !unresolved.is_empty() {
+        return Err(AppError::bad_request(format!(
+            "Cycle count with id {} has unresolved lines: {}",
+            cycle_count_id,
+            unresolved.join(", ")
+        )));
+    }
+
+    let mut ledger_entries = Vec::new();
+    for line in &lines {
+        let counted = line.counted_quantity.unwrap_or(line.expected_quantity);
+        let variance = counted - line.expected_quantity;
+        if variance == 0 {
+            continue;
+        }
+
+        // The count itself lifts the freeze, so its entries skip the frozen-location check
+        let entry = ledger_service::insert_entry(
+            &txn,
+            ledger_service::NewLedgerEntry {
+                item_id: line.item_id,
+                location_id: cycle_count.location_id,
+                quantity_change: variance,
+                reason_type: "COUNT".to_string(),
+                reference_type: Some("cycle_count".to_string()),
+                reference_id: Some(cycle_count.id),
+            },
+        )
+        .await?;
+
+        ledger_entries.push(entry.id);
+    }
+
+    let applied_movements = apply_held_movements(&txn, cycle_count.id).await?;
+
+    let mut count_update: cycle::ActiveModel = cycle_count.into();
+    count_update.status = Set("CLOSED".to_string());
+    count_update.updated_at = Set(chrono::Utc::now().into());
+
+    let updated_count = count_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update cycle count status: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(ClosedCycleCount {
+        cycle_count: updated_count,
+        lines,
+        ledger_entries,
+        applied_movements,
+    })
+}
+
+/// Write the movements held during a cycle count to the ledger and mark them APPLIED
+/// Returns the applied movement IDs; a movement the stock no longer covers fails with
+/// INSUFFICIENT_STOCK
+async fn apply_held_movements<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+) -> Result<Vec<i32>, AppError> {
+    let held = held_movement::Entity::find()
+        .filter(held_movement::Column::CycleCountId.eq(cycle_count_id))
+        .filter(held_movement::Column::Status.eq("HELD"))
+        .order_by_asc(held_movement::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch held movements: {}", e)))?;
+
+    let mut applied = Vec::new();
+    for movement in held {
+        let entry = ledger_service::insert_entry(
+            db,
+            ledger_service::NewLedgerEntry {
+                item_id: movement.item_id,
+                location_id: movement.location_id,
+                quantity_change: movement.quantity_change,
+                reason_type: movement.reason_type.clone(),
+                reference_type: movement.reference_type.clone(),
+                reference_id: movement.reference_id,
+            },
+        )
+        .await?;
+
+        let mut movement_update: held_movement::ActiveModel = movement.into();
+        movement_update.status = Set("APPLIED".to_string());
+        movement_update.ledger_entry_id = Set(Some(entry.id));
+        movement_update.applied_at = Set(Some(chrono::Utc::now().into()));
+
+        let movement = movement_update
+            .update(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to apply held movement: {}", e)))?;
+
+        release_held_pick_reservation(db, &movement).await?;
+        applied.push(movement.id);
+    }
+
+    Ok(applied)
+}
+
+/// Release the reservation a held pick kept, once the movement is applied or discarded
+async fn release_held_pick_reservation<C: ConnectionTrait>(
+    db: &C,
+    movement: &held_movement::Model,
+) -> Result<(), AppError> {
+    if movement.reason_type == "PICK"
+        && movement.reference_type.as_deref() == Some("pick_wave")
+        && let Some(pick_wave_id) = movement.reference_id
+    {
+        reservation_service::release_reservations_for_pick_line(
+            db,
+            pick_wave_id,
+            movement.item_id,
+            movement.location_id,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Movements held by a cycle count, in the order they arrived
+pub async fn list_held_movements(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+) -> Result<Vec<held_movement::Model>, AppError> {
+    find_cycle_count(db, cycle_count_id).await?;
+
+    held_movement::Entity::find()
+        .filter(held_movement::Column::CycleCountId.eq(cycle_count_id))
+        .order_by_asc(held_movement::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch held movements: {}", e)))
+}
+
+/// Drop a HELD movement so it is never applied
+///
+/// The document that posted it is left as it is; this is for movements that should not happen
+/// after all, such as a pick of stock the count found missing. A discarded pick releases the
+/// reservation it kept.
+pub async fn discard_held_movement(
+    db: &DatabaseConnection,
+    cycle_count_id: i32,
+    movement_id: i32,
+    note: Option<String>,
+) -> Result<held_movement::Model, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let movement = held_movement::Entity::find_by_id(movement_id)
+        .filter(held_movement::Column::CycleCountId.eq(cycle_count_id))
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch held movement: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Held movement with id {} not found for cycle count {}",
+                movement_id, cycle_count_id
+            ))
+        })?;
+
+    if movement.status != "HELD" {
+        return Err(AppError::bad_request(format!(
+            "Held movement with id {} is not in HELD status (current status: {})",
+            movement.id, movement.status
+        )));
+    }
+
+    let mut movement_update: held_movement::ActiveModel = movement.into();
+    movement_update.status = Set("DISCARDED".to_string());
+    movement_update.applied_at = Set(Some(chrono::Utc::now().into()));
+    movement_update.resolution_note = Set(note);
+
+    let movement = movement_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to discard held movement: {}", e)))?;
+
+    release_held_pick_reservation(&txn, &movement).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(movement)
+}
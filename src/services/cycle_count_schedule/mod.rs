@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use chrono_tz::Tz;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::Query;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::cycle;
+use crate::entities::item_abc_class;
+use crate::entities::ledger;
+use crate::entities::location;
+use crate::entities::warehouse;
+use crate::utils::error::AppError;
+
+/// How often each ABC class is counted, and how far ahead to schedule
+#[derive(Debug, Clone)]
+pub struct ScheduleOptions {
+    pub interval_days_a: i64,
+    pub interval_days_b: i64,
+    pub interval_days_c: i64,
+    pub horizon_days: i64,
+}
+
+impl Default for ScheduleOptions {
+    fn default() -> Self {
+        Self {
+            interval_days_a: 30,
+            interval_days_b: 91,
+            interval_days_c: 365,
+            horizon_days: 30,
+        }
+    }
+}
+
+impl ScheduleOptions {
+    fn interval_for(&self, class: &str) -> i64 {
+        match class {
+            "A" => self.interval_days_a,
+            "B" => self.interval_days_b,
+            _ => self.interval_days_c,
+        }
+    }
+}
+
+/// Parse a warehouse's working_days column (e.g. "MON,TUE,WED,THU,FRI")
+fn parse_working_days(working_days: &str) -> Vec<Weekday> {
+    working_days
+        .split(',')
+        .filter_map(|day| Weekday::from_str(day.trim()).ok())
+        .collect()
+}
+
+/// Create SCHEDULED cycle counts for a warehouse's locations that fall due within the horizon
+///
+/// A location takes the best ABC class of the items stocked in it (items without a class are C)
+/// and is due its class interval after its last CLOSED count; never-counted locations are due
+/// today. Locations that already have a SCHEDULED or OPEN count are skipped. Each due location is
+/// placed on the least busy working day up to its due date (anywhere in the horizon when it is
+/// already due), which spreads counts evenly over the warehouse's working days.
+pub async fn schedule_cycle_counts(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+    options: ScheduleOptions,
+) -> Result<Vec<cycle::Model>, AppError> {
+    if options.interval_days_a <= 0
+        || options.interval_days_b <= 0
+        || options.interval_days_c <= 0
+        || options.horizon_days <= 0
+    {
+        return Err(AppError::bad_request(
+            "Count intervals and horizon_days must be positive",
+        ));
+    }
+
+    let warehouse = warehouse::Entity::find_by_id(warehouse_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Warehouse with id {} not found", warehouse_id))
+        })?;
+
+    let tz = Tz::from_str(&warehouse.timezone).unwrap_or(Tz::UTC);
+    let today = chrono::Utc::now().with_timezone(&tz).date_naive();
+
+    let working_days = parse_working_days(&warehouse.working_days);
+    let days: Vec<NaiveDate> = (0..options.horizon_days)
+        .map(|offset| today + chrono::Duration::days(offset))
+        .filter(|date| working_days.contains(&date.weekday()))
+        .collect();
+
+    if days.is_empty() {
+        return Err(AppError::bad_request(format!(
+            "Warehouse with id {} has no working days in the next {} days",
+            warehouse_id, options.horizon_days
+        )));
+    }
+
+    let warehouse_locations = Query::select()
+        .column(location::Column::Id)
+        .from(location::Entity)
+        .and_where(location::Column::WarehouseId.eq(warehouse_id))
+        .to_owned();
+
+    // (location_id, item_id, on_hand)
+    let balances: Vec<(i32, i32, i64)> = ledger::Entity::find()
+        .select_only()
+        .column(ledger::Column::LocationId)
+        .column(ledger::Column::ItemId)
+        .column_as(ledger::Column::QuantityChange.sum(), "on_hand")
+        .filter(ledger::Column::LocationId.in_subquery(warehouse_locations.clone()))
+        .group_by(ledger::Column::LocationId)
+        .group_by(ledger::Column::ItemId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to calculate stock: {}", e)))?;
+
+    let item_classes: HashMap<i32, String> = item_abc_class::Entity::find()
+        .filter(item_abc_class::Column::WarehouseId.eq(warehouse_id))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch ABC classes: {}", e)))?
+        .into_iter()
+        .map(|class| (class.item_id, class.class))
+        .collect();
+
+    // "A" < "B" < "C", so the smallest class is the most demanding
+    let mut location_classes: HashMap<i32, String> = HashMap::new();
+    for (location_id, item_id, on_hand) in balances {
+        if on_hand == 0 {
+            continue;
+        }
+        let class = item_classes
+            .get(&item_id)
+            .cloned()
+            .unwrap_or_else(|| "C".to_string());
+        location_classes
+            .entry(location_id)
+            .and_modify(|best| {
+                if class < *best {
+                    *best = class.clone();
+                }
+            })
+            .or_insert(class);
+    }
+
+    let pending_locations: Vec<i32> = cycle::Entity::find()
+        .select_only()
+        .column(cycle::Column::LocationId)
+        .distinct()
+        .filter(cycle::Column::Status.is_in(["SCHEDULED", "OPEN"]))
+        .filter(cycle::Column::LocationId.in_subquery(warehouse_locations.clone()))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pending cycle counts: {}", e)))?;
+
+    let last_counted: HashMap<i32, DateTimeWithTimeZone> = cycle::Entity::find()
+        .select_only()
+        .column(cycle::Column::LocationId)
+        .column_as(cycle::Column::UpdatedAt.max(), "last_counted_at")
+        .filter(cycle::Column::Status.eq("CLOSED"))
+        .filter(cycle::Column::LocationId.in_subquery(warehouse_locations))
+        .group_by(cycle::Column::LocationId)
+        .into_tuple::<(i32, DateTimeWithTimeZone)>()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch last cycle counts: {}", e)))?
+        .into_iter()
+        .collect();
+
+    let horizon_end = today + chrono::Duration::days(options.horizon_days);
+
+    // (due_date, location_id), most overdue first
+    let mut due: Vec<(NaiveDate, i32)> = location_classes
+        .iter()
+        .filter(|(location_id, _)| !pending_locations.contains(location_id))
+        .filter_map(|(location_id, class)| {
+            let due_date = match last_counted.get(location_id) {
+                Some(counted_at) => {
+                    counted_at.with_timezone(&tz).date_naive()
+                        + chrono::Duration::days(options.interval_for(class))
+                }
+                None => today,
+            };
+            (due_date < horizon_end).then_some((due_date, *location_id))
+        })
+        .collect();
+    due.sort();
+
+    let mut load = vec![0usize; days.len()];
+    let mut assignments = Vec::new();
+    for (due_date, location_id) in due {
+        // Counts that are already due are spread over the whole horizon
+        let candidates = match days.iter().rposition(|day| *day <= due_date) {
+            Some(last_index) if due_date > today => 0..=last_index,
+            _ => 0..=days.len() - 1,
+        };
+
+        let day_index = candidates
+            .min_by_key(|index| (load[*index], *index))
+            .unwrap_or(0);
+        load[day_index] += 1;
+        assignments.push((location_id, days[day_index]));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let mut scheduled = Vec::new();
+    for (location_id, scheduled_for) in assignments {
+        let mut count_model = <cycle::ActiveModel as sea_orm::ActiveModelTrait>::default();
+        count_model.location_id = Set(location_id);
+        count_model.status = Set("SCHEDULED".to_string());
+        count_model.is_blind = Set(false);
+        count_model.scheduled_for = Set(Some(scheduled_for));
+
+        let cycle_count = count_model
+            .insert(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to schedule cycle count: {}", e)))?;
+
+        scheduled.push(cycle_count);
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    scheduled.sort_by_key(|count| (count.scheduled_for, count.location_id));
+    Ok(scheduled)
+}
+
+/// List cycle counts for a warehouse, optionally filtered by status, in schedule order
+pub async fn list_cycle_counts(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+    status: Option<String>,
+) -> Result<Vec<cycle::Model>, AppError> {
+    let warehouse_locations = Query::select()
+        .column(location::Column::Id)
+        .from(location::Entity)
+        .and_where(location::Column::WarehouseId.eq(warehouse_id))
+        .to_owned();
+
+    let mut query =
+        cycle::Entity::find().filter(cycle::Column::LocationId.in_subquery(warehouse_locations));
+
+    if let Some(status) = status {
+        query = query.filter(cycle::Column::Status.eq(status));
+    }
+
+    query
+        .order_by_asc(cycle::Column::ScheduledFor)
+        .order_by_asc(cycle::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch cycle counts: {}", e)))
+}
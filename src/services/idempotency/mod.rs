@@ -0,0 +1,162 @@
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, PaginatorTrait,
+    QueryFilter, Statement,
+};
+
+use crate::entities::idempotency_key;
+use crate::utils::error::AppError;
+
+/// Keys are forgotten this long after their first request, after which they may be reused
+pub const KEY_RETENTION_HOURS: i64 = 24;
+
+/// A claim is refreshed this often while its request runs
+pub const CLAIM_HEARTBEAT_SECONDS: u64 = 30;
+
+/// An unfinished claim not refreshed for this long died with its server, and is taken over
+pub const CLAIM_TIMEOUT_SECONDS: i64 = 120;
+
+/// A response to store against a key
+pub struct StoredResponse {
+    pub status: i32,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Claim a key for a new request. Returns false if another request holds it, in progress or
+/// finished; expired keys and abandoned claims are taken over
+pub async fn claim_key(
+    db: &DatabaseConnection,
+    key: &str,
+    method: &str,
+    path: &str,
+) -> Result<bool, AppError> {
+    let now = chrono::Utc::now();
+    let expired_before: DateTimeWithTimeZone =
+        (now - chrono::Duration::hours(KEY_RETENTION_HOURS)).into();
+    let abandoned_before: DateTimeWithTimeZone =
+        (now - chrono::Duration::seconds(CLAIM_TIMEOUT_SECONDS)).into();
+
+    let claimed = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO idempotency_key (key, request_method, request_path, created_at, heartbeat_at)
+            VALUES ($1, $2, $3, NOW(), NOW())
+            ON CONFLICT (key) DO UPDATE
+            SET request_method = EXCLUDED.request_method,
+                request_path = EXCLUDED.request_path,
+                request_fingerprint = NULL,
+                response_status = NULL,
+                response_content_type = NULL,
+                response_body = NULL,
+                created_at = EXCLUDED.created_at,
+                completed_at = NULL,
+                heartbeat_at = EXCLUDED.heartbeat_at
+            WHERE idempotency_key.created_at < $4
+               OR (idempotency_key.completed_at IS NULL AND idempotency_key.heartbeat_at < $5)
+            RETURNING id
+            "#,
+            [
+                key.into(),
+                method.into(),
+                path.into(),
+                expired_before.into(),
+                abandoned_before.into(),
+            ],
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to claim idempotency key: {}", e)))?;
+
+    Ok(claimed.is_some())
+}
+
+pub async fn find_key(
+    db: &DatabaseConnection,
+    key: &str,
+) -> Result<Option<idempotency_key::Model>, AppError> {
+    idempotency_key::Entity::find()
+        .filter(idempotency_key::Column::Key.eq(key))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch idempotency key: {}", e)))
+}
+
+/// Store the fingerprint and response of the request that claimed the key
+pub async fn complete_key(
+    db: &DatabaseConnection,
+    key: &str,
+    fingerprint: &str,
+    response: StoredResponse,
+) -> Result<(), AppError> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        r#"
+        UPDATE idempotency_key
+        SET request_fingerprint = $2,
+            response_status = $3,
+            response_content_type = $4,
+            response_body = $5,
+            completed_at = NOW()
+        WHERE key = $1 AND completed_at IS NULL
+        "#,
+        [
+            key.into(),
+            fingerprint.into(),
+            response.status.into(),
+            response.content_type.into(),
+            response.body.into(),
+        ],
+    ))
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to store idempotent response: {}", e)))?;
+
+    Ok(())
+}
+
+/// Show that the request holding an unfinished claim is still running
+pub async fn heartbeat_key(db: &DatabaseConnection, key: &str) -> Result<(), AppError> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "UPDATE idempotency_key SET heartbeat_at = NOW() WHERE key = $1 AND completed_at IS NULL",
+        [key.into()],
+    ))
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to refresh idempotency key: {}", e)))?;
+
+    Ok(())
+}
+
+/// Give up an unfinished claim so the request can run again under the same key
+pub async fn release_key(db: &DatabaseConnection, key: &str) -> Result<(), AppError> {
+    idempotency_key::Entity::delete_many()
+        .filter(idempotency_key::Column::Key.eq(key))
+        .filter(idempotency_key::Column::CompletedAt.is_null())
+        .exec(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to release idempotency key: {}", e)))?;
+
+    Ok(())
+}
+
+/// Delete keys past their retention. Returns how many there were
+pub async fn prune_keys(db: &DatabaseConnection, dry_run: bool) -> Result<u64, AppError> {
+    let expired_before: DateTimeWithTimeZone =
+        (chrono::Utc::now() - chrono::Duration::hours(KEY_RETENTION_HOURS)).into();
+
+    if dry_run {
+        return idempotency_key::Entity::find()
+            .filter(idempotency_key::Column::CreatedAt.lt(expired_before))
+            .count(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to count idempotency keys: {}", e)));
+    }
+
+    let result = idempotency_key::Entity::delete_many()
+        .filter(idempotency_key::Column::CreatedAt.lt(expired_before))
+        .exec(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to prune idempotency keys: {}", e)))?;
+
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,663 @@
+use std::time::Duration;
+
+use csv::{Writer, WriterBuilder};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DatabaseTransaction,
+    DbBackend, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
+};
+use serde_json::json;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::entities::{import_job, import_job_chunk, import_job_row};
+use crate::services::receipt::{
+    BULK_RECEIPT_BATCH_SIZE, BulkReceiptError, BulkReceiptImport, BulkReceiptResult,
+    BulkReceiptRow, ReceiptHeader,
+};
+use crate::services::receipt_file::{self, ColumnMapping, FileFormat, ParsedRow};
+use crate::utils::error::AppError;
+
+pub const KIND_BULK_RECEIPT: &str = "BULK_RECEIPT";
+
+/// How long an idle worker waits before looking for queued jobs again
+pub const WORKER_POLL_SECONDS: u64 = 2;
+
+/// A running job that is no longer locked by its worker and has made no progress for this long
+/// died with its server, and is run again
+pub const STALE_JOB_MINUTES: i64 = 5;
+
+/// A job is failed rather than started again once it has been started this many times
+pub const MAX_ATTEMPTS: i32 = 3;
+
+/// Uploaded files are stored in chunks of this size
+const CHUNK_BYTES: usize = 1024 * 1024;
+
+/// An uploaded file being stored for a new job
+///
+/// The upload is one transaction, committed by finish, so workers never see a job whose file is
+/// incomplete.
+pub struct JobUpload {
+    txn: DatabaseTransaction,
+    job: import_job::Model,
+    buffer: Vec<u8>,
+    sequence: i32,
+    file_size: i64,
+}
+
+impl JobUpload {
+    /// Start storing a bulk receipt file, to be imported with the given header
+    pub async fn begin_bulk_receipt(
+        db: &DatabaseConnection,
+        header: &ReceiptHeader,
+        mapping: &ColumnMapping,
+        format: FileFormat,
+        partial: bool,
+    ) -> Result<Self, AppError> {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+        let job = import_job::ActiveModel {
+            kind: Set(KIND_BULK_RECEIPT.to_string()),
+            status: Set("QUEUED".to_string()),
+            file_format: Set(format.name().to_string()),
+            file_size: Set(0),
+            partial: Set(partial),
+            receipt_number: Set(header.receipt_number.clone()),
+            supplier: Set(header.supplier.clone()),
+            reference_number: Set(header.reference_number.clone()),
+            received_by: Set(header.received_by.clone()),
+            mapping_profile: Set(mapping.profile.clone()),
+            column_mapping: Set(match mapping.profile {
+                Some(_) => Some(serde_json::to_value(mapping).map_err(|e| {
+                    AppError::internal(format!("Failed to serialize column mapping: {}", e))
+                })?),
+                None => None,
+            }),
+            attempts: Set(0),
+            rows_processed: Set(0),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create import job: {}", e)))?;
+
+        Ok(Self {
+            txn,
+            job,
+            buffer: Vec::with_capacity(CHUNK_BYTES),
+            sequence: 0,
+            file_size: 0,
+        })
+    }
+
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), AppError> {
+        self.file_size += data.len() as i64;
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= CHUNK_BYTES {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Store the rest of the file and queue the job
+    pub async fn finish(mut self) -> Result<import_job::Model, AppError> {
+        self.flush().await?;
+
+        let mut update: import_job::ActiveModel = self.job.into();
+        update.file_size = Set(self.file_size);
+        let job = update
+            .update(&self.txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to update import job: {}", e)))?;
+
+        self.txn
+            .commit()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+        Ok(job)
+    }
+
+    async fn flush(&mut self) -> Result<(), AppError> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let data = std::mem::replace(&mut self.buffer, Vec::with_capacity(CHUNK_BYTES));
+        import_job_chunk::ActiveModel {
+            import_job_id: Set(self.job.id),
+            sequence: Set(self.sequence),
+            data: Set(data),
+            ..Default::default()
+        }
+        .insert(&self.txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to store import file: {}", e)))?;
+        self.sequence += 1;
+
+        Ok(())
+    }
+}
+
+pub async fn get_job(db: &DatabaseConnection, job_id: i32) -> Result<import_job::Model, AppError> {
+    import_job::Entity::find_by_id(job_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch import job: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Job with id {} not found", job_id)))
+}
+
+/// Run queued jobs one at a time in the background, for as long as the server runs
+pub fn spawn_worker(db: DatabaseConnection) {
+    tokio::spawn(async move {
+        loop {
+            match claim_next_job(&db).await {
+                Ok(Some(job)) => {
+                    let job_id = job.id;
+                    if let Err(e) = run_job(&db, job).await {
+                        tracing::error!("Import job {}: {}", job_id, e);
+                    }
+                }
+                Ok(None) => tokio::time::sleep(Duration::from_secs(WORKER_POLL_SECONDS)).await,
+                Err(e) => {
+                    tracing::error!("{}", e);
+                    tokio::time::sleep(Duration::from_secs(WORKER_POLL_SECONDS)).await;
+                }
+            }
+        }
+    });
+}
+
+/// Take the oldest queued job, or a running one abandoned by a worker that died, and mark it
+/// running. Jobs locked by a live worker are skipped
+async fn claim_next_job(db: &DatabaseConnection) -> Result<Option<import_job::Model>, AppError> {
+    let stale_before: DateTimeWithTimeZone =
+        (chrono::Utc::now() - chrono::Duration::minutes(STALE_JOB_MINUTES)).into();
+
+    import_job::Entity::find()
+        .from_raw_sql(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            UPDATE import_job
+            SET status = 'RUNNING',
+                attempts = attempts + 1,
+                rows_processed = 0,
+                started_at = NOW(),
+                heartbeat_at = NOW()
+            WHERE id = (
+                SELECT id FROM import_job
+                WHERE status = 'QUEUED'
+                   OR (status = 'RUNNING' AND heartbeat_at < $1)
+                ORDER BY id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+            [stale_before.into()],
+        ))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to claim import job: {}", e)))
+}
+
+/// How a job ended
+struct JobOutcome {
+    status: &'static str,
+    total_rows: Option<usize>, // None if the file was never read through
+    successful_rows: Option<usize>,
+    errors: Vec<serde_json::Value>,
+    rejected_csv: Option<String>,
+    receipt_id: Option<i32>,
+    receipt_number: Option<String>,
+    error: Option<String>,
+}
+
+impl JobOutcome {
+    fn failed(error: String) -> Self {
+        Self {
+            status: "FAILED",
+            total_rows: None,
+            successful_rows: None,
+            errors: Vec::new(),
+            rejected_csv: None,
+            receipt_id: None,
+            receipt_number: None,
+            error: Some(error),
+        }
+    }
+}
+
+async fn run_job(db: &DatabaseConnection, job: import_job::Model) -> Result<(), AppError> {
+    if job.attempts > MAX_ATTEMPTS {
+        let outcome = JobOutcome::failed(format!(
+            "Stopped after {} attempts that did not finish",
+            MAX_ATTEMPTS
+        ));
+        return finish_job(db, job, outcome).await;
+    }
+
+    // Held until the job is finished, so no other worker takes it over. A worker that dies
+    // drops its connection and with it the lock
+    let lock = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+    lock.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT id FROM import_job WHERE id = $1 FOR KEY SHARE",
+        [job.id.into()],
+    ))
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to lock import job: {}", e)))?;
+
+    let outcome = match job.kind.as_str() {
+        KIND_BULK_RECEIPT => run_bulk_receipt(db, &job).await,
+        kind => Err(AppError::internal(format!("Unknown job kind '{}'", kind))),
+    };
+    let result = match outcome {
+        // A finished import has already recorded its outcome with the receipt
+        Ok(None) => Ok(()),
+        Ok(Some(outcome)) => finish_job(db, job, outcome).await,
+        Err(e) => finish_job(db, job, JobOutcome::failed(e.to_string())).await,
+    };
+
+    lock.rollback()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to rollback transaction: {}", e)))?;
+
+    result
+}
+
+/// Import a bulk receipt file the way a synchronous upload would be. Returns None if the
+/// receipt was committed, the job's outcome having been recorded with it
+async fn run_bulk_receipt(
+    db: &DatabaseConnection,
+    job: &import_job::Model,
+) -> Result<Option<JobOutcome>, AppError> {
+    let format = FileFormat::from_name(&job.file_format)
+        .ok_or_else(|| AppError::internal(format!("Unknown file format '{}'", job.file_format)))?;
+    let header = ReceiptHeader {
+        receipt_number: job.receipt_number.clone(),
+        supplier: job.supplier.clone(),
+        reference_number: job.reference_number.clone(),
+        received_by: job.received_by.clone(),
+        ..Default::default()
+    };
+    // Read as at upload, whatever has happened to the profile since
+    let mapping = match &job.column_mapping {
+        Some(mapping) => serde_json::from_value(mapping.clone())
+            .map_err(|e| AppError::internal(format!("Failed to read job column mapping: {}", e)))?,
+        None => ColumnMapping::standard(),
+    };
+
+    // Rows stored by an attempt that did not finish are read again
+    delete_staged_rows(db, job.id).await?;
+
+    // The import only commits once the whole file was read, not just up to a failed read
+    let (chunks_tx, chunks_rx) = mpsc::channel(2);
+    let (read_tx, read_rx) = oneshot::channel();
+    let read = async move {
+        let _ = read_tx.send(read_chunks(db, job.id, chunks_tx).await);
+    };
+    let ((), imported) = tokio::join!(
+        read,
+        import_file(db, job, &header, mapping, format, chunks_rx, read_rx)
+    );
+    imported
+}
+
+/// Send a job's stored file to `chunks` in order
+async fn read_chunks(
+    db: &DatabaseConnection,
+    job_id: i32,
+    chunks: mpsc::Sender<Vec<u8>>,
+) -> Result<(), AppError> {
+    for sequence in 0.. {
+        let chunk = import_job_chunk::Entity::find()
+            .filter(import_job_chunk::Column::ImportJobId.eq(job_id))
+            .filter(import_job_chunk::Column::Sequence.eq(sequence))
+            .one(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to read import file: {}", e)))?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        if chunks.send(chunk.data).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Read a job's file into import_job_row, then import the stored rows
+///
+/// Each batch of parsed rows is stored with a statement of its own, so no transaction stays open
+/// while the file is read, however slowly it arrives; the import transaction only starts once
+/// the whole file is in.
+async fn import_file(
+    db: &DatabaseConnection,
+    job: &import_job::Model,
+    header: &ReceiptHeader,
+    mapping: ColumnMapping,
+    format: FileFormat,
+    chunks: mpsc::Receiver<Vec<u8>>,
+    read: oneshot::Receiver<Result<(), AppError>>,
+) -> Result<Option<JobOutcome>, AppError> {
+    let mut batches = receipt_file::parse_file(format, mapping, chunks).await?;
+    let mut total_rows = 0;
+    let mut parse_errors = Vec::new();
+
+    while let Some(batch) = batches.next_batch().await? {
+        total_rows += batch.len();
+
+        let mut staged = Vec::with_capacity(batch.len());
+        for ParsedRow { line, fields, row } in batch {
+            match row {
+                Err(error) if !job.partial => {
+                    parse_errors.push(BulkReceiptError { row: line, error })
+                }
+                row => staged.push(staged_row_model(job, line, fields, row)?),
+            }
+        }
+
+        // A file with unreadable rows is refused whole, so only keep reading for their errors
+        if parse_errors.is_empty() && !staged.is_empty() {
+            import_job_row::Entity::insert_many(staged)
+                .exec_without_returning(db)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to store import rows: {}", e)))?;
+        }
+
+        record_progress(db, job, total_rows).await?;
+    }
+
+    read.await
+        .map_err(|_| AppError::internal("Import file reader stopped"))??;
+
+    if !parse_errors.is_empty() {
+        return Ok(Some(JobOutcome {
+            total_rows: Some(total_rows),
+            successful_rows: Some(0),
+            errors: errors_json(&parse_errors),
+            ..JobOutcome::failed(format!("{} parsing errors", format.name()))
+        }));
+    }
+
+    if total_rows == 0 {
+        return Ok(Some(JobOutcome::failed(format!(
+            "{} file contains no valid rows",
+            format.name()
+        ))));
+    }
+
+    import_staged_rows(db, job, header, &batches.headers, total_rows).await
+}
+
+/// A parsed row stored for import; its fields are only kept in partial mode, for the rejected
+/// rows CSV
+fn staged_row_model(
+    job: &import_job::Model,
+    line: usize,
+    fields: Vec<String>,
+    row: Result<BulkReceiptRow, String>,
+) -> Result<import_job_row::ActiveModel, AppError> {
+    let to_json = |value: serde_json::Result<serde_json::Value>| {
+        value.map_err(|e| AppError::internal(format!("Failed to store import row: {}", e)))
+    };
+    let (row, error) = match row {
+        Ok(row) => (Some(to_json(serde_json::to_value(&row))?), None),
+        Err(error) => (None, Some(error)),
+    };
+
+    Ok(import_job_row::ActiveModel {
+        import_job_id: Set(job.id),
+        line: Set(line as i32),
+        fields: Set(match job.partial {
+            true => Some(to_json(serde_json::to_value(&fields))?),
+            false => None,
+        }),
+        row: Set(row),
+        error: Set(error),
+        ..Default::default()
+    })
+}
+
+/// Import the rows import_file stored, a batch at a time, in the receipt's transaction
+async fn import_staged_rows(
+    db: &DatabaseConnection,
+    job: &import_job::Model,
+    header: &ReceiptHeader,
+    headers: &[String],
+    total_rows: usize,
+) -> Result<Option<JobOutcome>, AppError> {
+    let mut import = BulkReceiptImport::begin(db, header, job.partial).await?;
+    let mut rejected = if job.partial {
+        let mut writer = WriterBuilder::new().flexible(true).from_writer(Vec::new());
+        writer
+            .write_record(headers.iter().chain(["error".to_string()].iter()))
+            .map_err(rejected_write_error)?;
+        Some(writer)
+    } else {
+        None
+    };
+
+    let read_error =
+        |e: serde_json::Error| AppError::internal(format!("Failed to read import row: {}", e));
+    let mut after = 0;
+    loop {
+        let staged = import_job_row::Entity::find()
+            .filter(import_job_row::Column::ImportJobId.eq(job.id))
+            .filter(import_job_row::Column::Line.gt(after))
+            .order_by_asc(import_job_row::Column::Line)
+            .limit(BULK_RECEIPT_BATCH_SIZE as u64)
+            .all(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to read import rows: {}", e)))?;
+        let Some(last) = staged.last() else {
+            break;
+        };
+        after = last.line;
+
+        let mut rows = Vec::with_capacity(staged.len());
+        let mut fields = Vec::with_capacity(staged.len());
+        let mut batch_errors = Vec::new();
+        for staged_row in staged {
+            let line = staged_row.line as usize;
+            let row_fields: Vec<String> = match staged_row.fields {
+                Some(row_fields) => serde_json::from_value(row_fields).map_err(read_error)?,
+                None => Vec::new(),
+            };
+            match staged_row.row {
+                Some(row) => {
+                    rows.push(serde_json::from_value(row).map_err(read_error)?);
+                    fields.push((line, row_fields));
+                }
+                None => {
+                    let error = staged_row.error.unwrap_or_default();
+                    import.reject(BulkReceiptError {
+                        row: line,
+                        error: error.clone(),
+                    });
+                    batch_errors.push((line, row_fields, error));
+                }
+            }
+        }
+
+        let errors = import.add_batch(rows).await?;
+
+        if let Some(writer) = rejected.as_mut() {
+            for error in errors {
+                if let Ok(index) = fields.binary_search_by_key(&error.row, |(line, _)| *line) {
+                    let row_fields = std::mem::take(&mut fields[index].1);
+                    batch_errors.push((error.row, row_fields, error.error.clone()));
+                }
+            }
+            batch_errors.sort_by_key(|(line, _, _)| *line);
+            for (_, row_fields, error) in batch_errors {
+                write_rejected_row(writer, headers.len(), row_fields, error)?;
+            }
+        }
+
+        // Keeps the job's heartbeat going while the import runs
+        record_progress(db, job, total_rows).await?;
+    }
+
+    let rejected_csv =
+        match rejected {
+            Some(writer) => {
+                let data = writer.into_inner().map_err(|e| {
+                    AppError::internal(format!("Failed to write rejected rows: {}", e))
+                })?;
+                Some(String::from_utf8(data).map_err(|e| {
+                    AppError::internal(format!("Failed to write rejected rows: {}", e))
+                })?)
+            }
+            None => None,
+        };
+
+    let job_id = job.id;
+    let result = import
+        .finish_recording(async |txn, result: &BulkReceiptResult| {
+            let outcome = JobOutcome {
+                status: "SUCCEEDED",
+                rejected_csv: rejected_csv.clone().filter(|_| !result.errors.is_empty()),
+                ..import_outcome(result)
+            };
+            let job = import_job::Entity::find_by_id(job_id)
+                .one(txn)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to fetch import job: {}", e)))?
+                .ok_or_else(|| AppError::not_found(format!("Job with id {} not found", job_id)))?;
+            finish_job(txn, job, outcome).await
+        })
+        .await?;
+    if result.receipt.is_some() {
+        return Ok(None);
+    }
+
+    let error = if job.partial {
+        "No rows could be imported"
+    } else {
+        "Import rolled back because some rows failed"
+    };
+    Ok(Some(JobOutcome {
+        error: Some(error.to_string()),
+        ..import_outcome(&result)
+    }))
+}
+
+/// The outcome of an import that ran to the end; nothing counts as imported if it was rolled back
+fn import_outcome(result: &BulkReceiptResult) -> JobOutcome {
+    let receipt = result.receipt.as_ref();
+    JobOutcome {
+        status: "FAILED",
+        total_rows: Some(result.total_rows),
+        successful_rows: Some(receipt.map_or(0, |_| result.successful_rows)),
+        errors: errors_json(&result.errors),
+        rejected_csv: None,
+        receipt_id: receipt.map(|receipt| receipt.id),
+        receipt_number: receipt.map(|receipt| receipt.receipt_number.clone()),
+        error: None,
+    }
+}
+
+async fn record_progress(
+    db: &DatabaseConnection,
+    job: &import_job::Model,
+    rows_processed: usize,
+) -> Result<(), AppError> {
+    let update = import_job::ActiveModel {
+        id: Set(job.id),
+        rows_processed: Set(rows_processed as i32),
+        heartbeat_at: Set(Some(chrono::Utc::now().into())),
+        ..Default::default()
+    };
+    import_job::Entity::update(update)
+        .exec(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update import job: {}", e)))?;
+
+    Ok(())
+}
+
+/// Record how a job ended and drop its stored file and rows
+async fn finish_job<C: ConnectionTrait>(
+    db: &C,
+    job: import_job::Model,
+    outcome: JobOutcome,
+) -> Result<(), AppError> {
+    let job_id = job.id;
+    let mut update: import_job::ActiveModel = job.into();
+    update.status = Set(outcome.status.to_string());
+    if let Some(total_rows) = outcome.total_rows {
+        update.rows_processed = Set(total_rows as i32);
+    }
+    update.total_rows = Set(outcome.total_rows.map(|rows| rows as i32));
+    update.successful_rows = Set(outcome.successful_rows.map(|rows| rows as i32));
+    update.errors = Set(Some(json!(outcome.errors)));
+    update.rejected_csv = Set(outcome.rejected_csv);
+    update.receipt_id = Set(outcome.receipt_id);
+    if outcome.receipt_number.is_some() {
+        update.receipt_number = Set(outcome.receipt_number);
+    }
+    update.error = Set(outcome.error);
+    update.finished_at = Set(Some(chrono::Utc::now().into()));
+    update
+        .update(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update import job: {}", e)))?;
+
+    import_job_chunk::Entity::delete_many()
+        .filter(import_job_chunk::Column::ImportJobId.eq(job_id))
+        .exec(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to delete import file: {}", e)))?;
+
+    delete_staged_rows(db, job_id).await
+}
+
+async fn delete_staged_rows<C: ConnectionTrait>(db: &C, job_id: i32) -> Result<(), AppError> {
+    import_job_row::Entity::delete_many()
+        .filter(import_job_row::Column::ImportJobId.eq(job_id))
+        .exec(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to delete import rows: {}", e)))?;
+
+    Ok(())
+}
+
+/// Row errors in row order, as reported by the job
+fn errors_json(errors: &[BulkReceiptError]) -> Vec<serde_json::Value> {
+    let mut errors: Vec<&BulkReceiptError> = errors.iter().collect();
+    errors.sort_by_key(|e| e.row);
+    errors
+        .iter()
+        .map(|e| {
+            json!({
+                "row": e.row,
+                "error": e.error
+            })
+        })
+        .collect()
+}
+
+/// A rejected row with its original columns and the error; rows that could not be read at all
+/// only carry the error
+fn write_rejected_row(
+    writer: &mut Writer<Vec<u8>>,
+    columns: usize,
+    mut fields: Vec<String>,
+    error: String,
+) -> Result<(), AppError> {
+    fields.resize(columns, String::new());
+    fields.push(error);
+    writer.write_record(&fields).map_err(rejected_write_error)
+}
+
+fn rejected_write_error(e: csv::Error) -> AppError {
+    AppError::internal(format!("Failed to write rejected rows: {}", e))
+}
@@ -1,37 +1,93 @@
-use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
+    QuerySelect, Statement, TransactionTrait, Value,
+};
 
 use crate::db::DatabaseConnection;
+use crate::entities::inventory_balance;
 use crate::entities::ledger;
 use crate::entities::reservation;
 use crate::utils::error::AppError;
 
-/// Calculate on-hand quantity by summing quantity_change from inventory_ledger
-/// for a specific item and location.
+/// Take the stock lock for each (item_id, location_id) pair until the transaction ends
 ///
-/// Never trust a stored stock number - always compute from the ledger.
+/// Operations that check stock and then write (allocations, transfers, adjustments, picks) take
+/// these locks first so that concurrent operations on the same item and location run one after
+/// another instead of both passing the check. Keys are locked in sorted order to avoid deadlocks
+/// between operations touching several pairs. Transaction-scoped advisory locks are re-entrant and
+/// are released on commit or rollback, so this must be called on a transaction.
+pub async fn lock_stock<C: ConnectionTrait>(db: &C, keys: &[(i32, i32)]) -> Result<(), AppError> {
+    let mut keys = keys.to_vec();
+    keys.sort_unstable();
+    keys.dedup();
+
+    for (item_id, location_id) in keys {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_advisory_xact_lock($1, $2)",
+            [item_id.into(), location_id.into()],
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to lock stock: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Read on-hand quantity for a specific item and location from inventory_balance.
+///
+/// The balance table is a projection maintained alongside every ledger insert (see
+/// `ledger::insert_entry`); the ledger remains the source of truth and `reconcile_balances`
+/// rebuilds the projection from it. With `as_of` set, the quantity is instead summed from the
+/// ledger rows created up to that instant.
 pub async fn calculate_on_hand<C: ConnectionTrait>(
     db: &C,
     item_id: i32,
     location_id: i32,
+    as_of: Option<DateTimeWithTimeZone>,
 ) -> Result<i32, AppError> {
-    let records = ledger::Entity::find()
+    if let Some(as_of) = as_of {
+        let sum: Option<i64> = ledger::Entity::find()
+            .select_only()
+            .column_as(ledger::Column::QuantityChange.sum(), "on_hand")
+            .filter(
+                sea_orm::Condition::all()
+                    .add(ledger::Column::ItemId.eq(item_id))
+                    .add(ledger::Column::LocationId.eq(location_id))
+                    .add(ledger::Column::CreatedAt.lte(as_of)),
+            )
+            .into_tuple()
+            .one(db)
+            .await
+            .map_err(|e| {
+                AppError::internal(format!("Failed to calculate on-hand quantity: {}", e))
+            })?
+            .flatten();
+
+        return Ok(sum.unwrap_or(0) as i32);
+    }
+
+    let balance = inventory_balance::Entity::find()
         .filter(
             sea_orm::Condition::all()
-                .add(ledger::Column::ItemId.eq(item_id))
-                .add(ledger::Column::LocationId.eq(location_id)),
+                .add(inventory_balance::Column::ItemId.eq(item_id))
+                .add(inventory_balance::Column::LocationId.eq(location_id)),
         )
-        .all(db)
+        .one(db)
         .await
         .map_err(|e| AppError::internal(format!("Failed to calculate on-hand quantity: {}", e)))?;
 
-    let sum: i32 = records.iter().map(|r| r.quantity_change).sum();
-    Ok(sum)
+    Ok(balance.map(|b| b.on_hand).unwrap_or(0))
 }
 
 /// Calculate reserved quantity by summing quantity from reservations
 /// for a specific item and location where the reservation hasn't expired.
-pub async fn calculate_reserved(
-    db: &DatabaseConnection,
+///
+/// Current reservations only: they are deleted once consumed or released, so past levels cannot
+/// be reconstructed.
+pub async fn calculate_reserved<C: ConnectionTrait>(
+    db: &C,
     item_id: i32,
     location_id: i32,
 ) -> Result<i32, AppError> {
@@ -58,13 +114,241 @@ pub async fn calculate_reserved(
 }
 
 /// Calculate available quantity as on_hand - reserved.
-pub async fn calculate_available(
-    db: &DatabaseConnection,
+/// Current stock only, as reserved quantities cannot be reconstructed for the past.
+pub async fn calculate_available<C: ConnectionTrait>(
+    db: &C,
     item_id: i32,
     location_id: i32,
 ) -> Result<i32, AppError> {
-    let on_hand = calculate_on_hand(db, item_id, location_id).await?;
+    let on_hand = calculate_on_hand(db, item_id, location_id, None).await?;
     let reserved = calculate_reserved(db, item_id, location_id).await?;
 
     Ok(on_hand - reserved)
 }
+
+/// Filters for a stock query; all are optional
+#[derive(Debug, Clone, Default)]
+pub struct StockFilter {
+    pub sku: Option<String>,
+    pub location_code: Option<String>,
+    pub warehouse_id: Option<i32>,
+    pub only_nonzero: bool,
+    pub as_of: Option<DateTimeWithTimeZone>, // count only ledger rows created at or before this instant
+}
+
+/// On-hand, reserved and available quantity of one item at one location
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct StockLine {
+    pub item_id: i32,
+    pub sku: String,
+    pub item_name: String,
+    pub location_id: i32,
+    pub location_code: String,
+    pub warehouse_id: i32,
+    pub on_hand: i64,
+    pub reserved: Option<i64>, // None for as-of queries; past reservations are not kept
+    pub available: Option<i64>, // None for as-of queries
+}
+
+/// Query stock per item and location
+///
+/// On-hand comes from inventory_balance and reservations are aggregated in the database, both
+/// filtered by item and location before they are aggregated.
+/// Reservations count while they have not expired. With `as_of` set, on-hand is summed from the
+/// ledger rows created up to that instant instead, and reserved/available are not reported because
+/// reservations are deleted once consumed.
+pub async fn query_stock<C: ConnectionTrait>(
+    db: &C,
+    filter: &StockFilter,
+) -> Result<Vec<StockLine>, AppError> {
+    let mut values: Vec<Value> = Vec::new();
+
+    // Item and location filters, applied inside the CTEs so only matching rows are aggregated
+    let mut key_conditions = Vec::new();
+    if let Some(sku) = &filter.sku {
+        values.push(sku.clone().into());
+        key_conditions.push(format!(
+            "item_id IN (SELECT id FROM item WHERE sku = ${})",
+            values.len()
+        ));
+    }
+    let mut location_conditions = Vec::new();
+    if let Some(location_code) = &filter.location_code {
+        values.push(location_code.clone().into());
+        location_conditions.push(format!("code = ${}", values.len()));
+    }
+    let mut warehouse_param = None;
+    if let Some(warehouse_id) = filter.warehouse_id {
+        values.push(warehouse_id.into());
+        warehouse_param = Some(values.len());
+        location_conditions.push(format!("warehouse_id = ${}", values.len()));
+    }
+    if !location_conditions.is_empty() {
+        key_conditions.push(format!(
+            "location_id IN (SELECT id FROM location WHERE {})",
+            location_conditions.join(" AND ")
+        ));
+    }
+
+    let (on_hand_source, reserved_filter, reserved_expr, available_expr) = match filter.as_of {
+        Some(as_of) => {
+            let mut conditions = key_conditions;
+            values.push(as_of.into());
+            conditions.push(format!("created_at <= ${}", values.len()));
+            // Ledger rows carry their warehouse too, which its chain index covers
+            if let Some(param) = warehouse_param {
+                conditions.push(format!("warehouse_id = ${}", param));
+            }
+            (
+                format!(
+                    "SELECT item_id, location_id, SUM(quantity_change)::bigint AS on_hand \
+                     FROM inventory_ledger WHERE {} \
+                     GROUP BY item_id, location_id",
+                    conditions.join(" AND ")
+                ),
+                "WHERE false".to_string(),
+                "NULL::bigint",
+                "NULL::bigint",
+            )
+        }
+        None => {
+            let key_filter = key_conditions
+                .iter()
+                .map(|condition| format!(" AND {}", condition))
+                .collect::<String>();
+            (
+                format!(
+                    "SELECT item_id, location_id, on_hand::bigint AS on_hand \
+                     FROM inventory_balance WHERE true{}",
+                    key_filter
+                ),
+                format!(
+                    "WHERE (expires_at IS NULL OR expires_at > NOW()){}",
+                    key_filter
+                ),
+                "COALESCE(r.reserved, 0)",
+                "COALESCE(o.on_hand, 0) - COALESCE(r.reserved, 0)",
+            )
+        }
+    };
+
+    let where_clause = if filter.only_nonzero {
+        "WHERE COALESCE(o.on_hand, 0) <> 0"
+    } else {
+        ""
+    };
+
+    let sql = format!(
+        r#"
+        WITH on_hand AS (
+            {on_hand_source}
+        ),
+        reserved AS (
+            SELECT item_id, location_id, SUM(quantity)::bigint AS reserved
+            FROM reservation
+            {reserved_filter}
+            GROUP BY item_id, location_id
+        )
+        SELECT
+            i.id AS item_id,
+            i.sku,
+            i.name AS item_name,
+            l.id AS location_id,
+            l.code AS location_code,
+            l.warehouse_id,
+            COALESCE(o.on_hand, 0) AS on_hand,
+            {reserved_expr} AS reserved,
+            {available_expr} AS available
+        FROM on_hand o
+        FULL OUTER JOIN reserved r USING (item_id, location_id)
+        JOIN item i ON i.id = item_id
+        JOIN location l ON l.id = location_id
+        {where_clause}
+        ORDER BY l.warehouse_id, i.sku, l.code
+        "#
+    );
+
+    StockLine::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        sql,
+        values,
+    ))
+    .all(db)
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to query stock: {}", e)))
+}
+
+/// An item/location whose stored balance differs from the ledger
+#[derive(Debug, Clone, FromQueryResult)]
+pub struct BalanceDrift {
+    pub item_id: i32,
+    pub location_id: i32,
+    pub ledger_on_hand: i64,
+    pub balance_on_hand: Option<i64>, // None when the balance row is missing
+}
+
+/// Compare inventory_balance against inventory_ledger and, unless dry_run, rebuild it
+///
+/// The rebuild locks inventory_balance against concurrent ledger writes, so the table matches the
+/// ledger exactly when the transaction commits. Returns every item/location that had drifted.
+pub async fn reconcile_balances(
+    db: &DatabaseConnection,
+    dry_run: bool,
+) -> Result<Vec<BalanceDrift>, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    if !dry_run {
+        txn.execute_unprepared("LOCK TABLE inventory_balance IN EXCLUSIVE MODE")
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to lock inventory balances: {}", e)))?;
+    }
+
+    let drift = BalanceDrift::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"
+        WITH ledger_totals AS (
+            SELECT item_id, location_id, SUM(quantity_change)::bigint AS on_hand
+            FROM inventory_ledger
+            GROUP BY item_id, location_id
+        )
+        SELECT
+            COALESCE(l.item_id, b.item_id) AS item_id,
+            COALESCE(l.location_id, b.location_id) AS location_id,
+            COALESCE(l.on_hand, 0) AS ledger_on_hand,
+            b.on_hand::bigint AS balance_on_hand
+        FROM ledger_totals l
+        FULL OUTER JOIN inventory_balance b
+            ON b.item_id = l.item_id AND b.location_id = l.location_id
+        WHERE b.id IS NULL OR COALESCE(l.on_hand, 0) <> b.on_hand
+        ORDER BY 1, 2
+        "#,
+    ))
+    .all(&txn)
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to compare inventory balances: {}", e)))?;
+
+    if !dry_run {
+        txn.execute_unprepared("DELETE FROM inventory_balance")
+            .await
+            .map_err(|e| {
+                AppError::internal(format!("Failed to clear inventory balances: {}", e))
+            })?;
+
+        txn.execute_unprepared(
+            "INSERT INTO inventory_balance (item_id, location_id, on_hand, updated_at) \
+             SELECT item_id, location_id, SUM(quantity_change), NOW() \
+             FROM inventory_ledger GROUP BY item_id, location_id",
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to rebuild inventory balances: {}", e)))?;
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(drift)
+}
@@ -0,0 +1,834 @@
+use std::collections::BTreeMap;
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::Query;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::held_movement;
+use crate::entities::inventory_balance;
+use crate::entities::item;
+use crate::entities::ledger;
+use crate::entities::location;
+use crate::entities::warehouse;
+use crate::services::cycle_count;
+use crate::services::inventory;
+use crate::utils::error::{AppError, StockShortfall};
+
+/// A stock movement to be written to inventory_ledger
+#[derive(Debug, Clone)]
+pub struct NewLedgerEntry {
+    pub item_id: i32,
+    pub location_id: i32,
+    pub quantity_change: i32,
+    pub reason_type: String,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+}
+
+/// Outcome of posting a movement: written to the ledger, or held until a cycle count closes
+#[derive(Debug)]
+pub enum PostedEntry {
+    Posted(ledger::Model),
+    Held(held_movement::Model),
+}
+
+impl PostedEntry {
+    pub fn ledger_entry_id(&self) -> Option<i32> {
+        match self {
+            PostedEntry::Posted(entry) => Some(entry.id),
+            PostedEntry::Held(_) => None,
+        }
+    }
+
+    pub fn held_movement_id(&self) -> Option<i32> {
+        match self {
+            PostedEntry::Posted(_) => None,
+            PostedEntry::Held(held) => Some(held.id),
+        }
+    }
+}
+
+/// Filters for reading ledger history; all are optional
+#[derive(Debug, Clone, Default)]
+pub struct LedgerFilter {
+    pub item_id: Option<i32>,
+    pub location_id: Option<i32>,
+    pub warehouse_id: Option<i32>,
+    pub reason_type: Option<String>,
+    pub reference_type: Option<String>,
+    pub reference_id: Option<i32>,
+    pub from: Option<DateTimeWithTimeZone>, // inclusive
+    pub to: Option<DateTimeWithTimeZone>,   // exclusive
+}
+
+/// A page of ledger entries in id order
+#[derive(Debug)]
+pub struct LedgerPage {
+    pub entries: Vec<ledger::Model>,
+    pub next_cursor: Option<i32>, // pass as `after` to fetch the next page
+}
+
+/// Read ledger entries with id greater than `after`, oldest first
+///
+/// Keyset pagination on the primary key: each page is a range scan from the cursor, so walking the
+/// whole history costs the same per page however deep it goes, and rows inserted while paging
+/// simply show up on a later page.
+///
+/// Ids are taken at insert but become visible at commit, so an entry whose transaction commits
+/// after a reader has paged past its id never shows up behind the cursor. Holding back entries
+/// until every older transaction has finished would close the gap but stalls the whole feed behind
+/// one long transaction (a bulk import, say). Readers that must see every entry re-read a window
+/// behind their cursor, or follow each warehouse's chain_seq, which is handed out in commit order
+/// under the chain lock.
+pub async fn find_entries<C: ConnectionTrait>(
+    db: &C,
+    filter: &LedgerFilter,
+    after: Option<i32>,
+    limit: u64,
+) -> Result<LedgerPage, AppError> {
+    let mut query = ledger::Entity::find();
+
+    if let Some(after) = after {
+        query = query.filter(ledger::Column::Id.gt(after));
+    }
+    if let Some(item_id) = filter.item_id {
+        query = query.filter(ledger::Column::ItemId.eq(item_id));
+    }
+    if let Some(location_id) = filter.location_id {
+        query = query.filter(ledger::Column::LocationId.eq(location_id));
+    }
+    if let Some(warehouse_id) = filter.warehouse_id {
+        query = query.filter(
+            ledger::Column::LocationId.in_subquery(
+                Query::select()
+                    .column(location::Column::Id)
+                    .from(location::Entity)
+                    .and_where(location::Column::WarehouseId.eq(warehouse_id))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(reason_type) = &filter.reason_type {
+        query = query.filter(ledger::Column::ReasonType.eq(reason_type.clone()));
+    }
+    if let Some(reference_type) = &filter.reference_type {
+        query = query.filter(ledger::Column::ReferenceType.eq(reference_type.clone()));
+    }
+    if let Some(reference_id) = filter.reference_id {
+        query = query.filter(ledger::Column::ReferenceId.eq(reference_id));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(ledger::Column::CreatedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(ledger::Column::CreatedAt.lt(to));
+    }
+
+    // Fetch one extra row to know whether there is another page
+    let mut entries = query
+        .order_by_asc(ledger::Column::Id)
+        .limit(limit + 1)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch ledger entries: {}", e)))?;
+
+    let next_cursor = if entries.len() as u64 > limit {
+        entries.truncate(limit as usize);
+        entries.last().map(|entry| entry.id)
+    } else {
+        None
+    };
+
+    Ok(LedgerPage {
+        entries,
+        next_cursor,
+    })
+}
+
+/// Result of walking one warehouse's ledger hash chain
+#[derive(Debug)]
+pub struct ChainVerification {
+    pub warehouse_id: i32,
+    pub entries_checked: i64,
+    pub head_seq: Option<i32>,
+    pub head_hash: Option<String>, // record this to detect entries removed from the end later
+    pub first_broken: Option<BrokenLink>,
+}
+
+/// The first entry whose recorded sequence or hashes do not match the chain
+#[derive(Debug, FromQueryResult)]
+pub struct BrokenLink {
+    pub entry_id: i32,
+    pub chain_seq: i32,
+    pub field: String, // chain_seq | prev_hash | entry_hash
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ChainHead {
+    entries_checked: i64,
+    head_seq: Option<i32>,
+    head_hash: Option<String>,
+}
+
+/// Walk the hash chain of one warehouse, or of every warehouse with ledger entries
+///
+/// Every entry's hash is recomputed from its columns and its prev_hash compared with the entry
+/// before it, in chain_seq order. A changed entry fails on entry_hash, a removed one on the
+/// chain_seq after it, and a changed entry whose hash was recomputed on the prev_hash after it.
+pub async fn verify_chains<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: Option<i32>,
+) -> Result<Vec<ChainVerification>, AppError> {
+    let warehouse_ids: Vec<i32> = match warehouse_id {
+        Some(warehouse_id) => vec![warehouse_id],
+        None => ledger::Entity::find()
+            .select_only()
+            .column(ledger::Column::WarehouseId)
+            .distinct()
+            .order_by_asc(ledger::Column::WarehouseId)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch ledger warehouses: {}", e)))?,
+    };
+
+    let mut results = Vec::new();
+    for warehouse_id in warehouse_ids {
+        let head = ChainHead::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            SELECT COUNT(*) AS entries_checked,
+                   MAX(chain_seq) AS head_seq,
+                   (SELECT entry_hash FROM inventory_ledger
+                    WHERE warehouse_id = $1 ORDER BY chain_seq DESC LIMIT 1) AS head_hash
+            FROM inventory_ledger
+            WHERE warehouse_id = $1
+            "#,
+            [warehouse_id.into()],
+        ))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to read ledger chain: {}", e)))?
+        .ok_or_else(|| AppError::internal("Ledger chain summary returned no row"))?;
+
+        let first_broken = BrokenLink::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            WITH chain AS (
+                SELECT id, chain_seq, prev_hash, entry_hash,
+                       ROW_NUMBER() OVER (ORDER BY chain_seq)::INTEGER AS expected_seq,
+                       LAG(entry_hash) OVER (ORDER BY chain_seq) AS expected_prev_hash,
+                       inventory_ledger_entry_hash(
+                           id, warehouse_id, chain_seq, item_id, location_id, quantity_change,
+                           reason_type, reference_type, reference_id, reverses_entry_id,
+                           created_at, prev_hash
+                       ) AS expected_entry_hash
+                FROM inventory_ledger
+                WHERE warehouse_id = $1
+            )
+            SELECT id AS entry_id,
+                   chain_seq,
+                   CASE
+                       WHEN chain_seq <> expected_seq THEN 'chain_seq'
+                       WHEN prev_hash IS DISTINCT FROM expected_prev_hash THEN 'prev_hash'
+                       ELSE 'entry_hash'
+                   END AS field,
+                   CASE
+                       WHEN chain_seq <> expected_seq THEN expected_seq::TEXT
+                       WHEN prev_hash IS DISTINCT FROM expected_prev_hash THEN expected_prev_hash
+                       ELSE expected_entry_hash
+                   END AS expected,
+                   CASE
+                       WHEN chain_seq <> expected_seq THEN chain_seq::TEXT
+                       WHEN prev_hash IS DISTINCT FROM expected_prev_hash THEN prev_hash
+                       ELSE entry_hash
+                   END AS actual
+            FROM chain
+            WHERE chain_seq <> expected_seq
+               OR prev_hash IS DISTINCT FROM expected_prev_hash
+               OR entry_hash IS DISTINCT FROM expected_entry_hash
+            ORDER BY chain_seq
+            LIMIT 1
+            "#,
+            [warehouse_id.into()],
+        ))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to verify ledger chain: {}", e)))?;
+
+        results.push(ChainVerification {
+            warehouse_id,
+            entries_checked: head.entries_checked,
+            head_seq: head.head_seq,
+            head_hash: head.head_hash,
+            first_broken,
+        });
+    }
+
+    Ok(results)
+}
+
+/// A ledger entry whose cached balance_after differs from the running on-hand
+#[derive(Debug, FromQueryResult)]
+pub struct BalanceAfterMismatch {
+    pub entry_id: i32,
+    pub item_id: i32,
+    pub location_id: i32,
+    pub recorded: Option<i32>,
+    pub expected: i64,
+}
+
+/// Recompute balance_after per item/location and, unless dry_run, correct the entries that differ
+///
+/// The expected value is the running sum of quantity_change in (created_at, id) order. The fix
+/// blocks new ledger entries until it commits and calls inventory_ledger_repair_balance_after(),
+/// the only way past the append-only trigger: the function runs as a role of its own, which the
+/// trigger lets change balance_after and nothing else. Returns every mismatched entry.
+pub async fn repair_balance_after(
+    db: &DatabaseConnection,
+    dry_run: bool,
+) -> Result<Vec<BalanceAfterMismatch>, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    if !dry_run {
+        txn.execute_unprepared("LOCK TABLE inventory_ledger IN SHARE ROW EXCLUSIVE MODE")
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to lock inventory ledger: {}", e)))?;
+    }
+
+    let mismatches = BalanceAfterMismatch::find_by_statement(Statement::from_string(
+        DbBackend::Postgres,
+        r#"
+        WITH running AS (
+            SELECT id, item_id, location_id, balance_after,
+                   SUM(quantity_change) OVER (
+                       PARTITION BY item_id, location_id ORDER BY created_at, id
+                   )::BIGINT AS expected
+            FROM inventory_ledger
+        )
+        SELECT id AS entry_id, item_id, location_id, balance_after AS recorded, expected
+        FROM running
+        WHERE balance_after IS DISTINCT FROM expected
+        ORDER BY item_id, location_id, id
+        "#,
+    ))
+    .all(&txn)
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to compare balance_after: {}", e)))?;
+
+    if !dry_run && !mismatches.is_empty() {
+        txn.execute_unprepared("SELECT inventory_ledger_repair_balance_after()")
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to repair balance_after: {}", e)))?;
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(mismatches)
+}
+
+/// Post a stock movement to the ledger
+///
+/// Every ledger write goes through here. While a cycle count is OPEN for the location the
+/// location is frozen: depending on the warehouse's count_freeze_mode the movement is either
+/// rejected with LOCATION_FROZEN or held and applied when the count closes.
+pub async fn post_entry<C: ConnectionTrait>(
+    db: &C,
+    entry: NewLedgerEntry,
+) -> Result<PostedEntry, AppError> {
+    if let Some(open_count) = cycle_count::find_open_cycle_count(db, entry.location_id).await? {
+        let location = location::Entity::find_by_id(entry.location_id)
+            .one(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with id {} not found", entry.location_id))
+            })?;
+
+        let freeze_mode = warehouse::Entity::find_by_id(location.warehouse_id)
+            .one(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+            .map(|w| w.count_freeze_mode)
+            .unwrap_or_else(|| "REJECT".to_string());
+
+        if freeze_mode != "QUEUE" {
+            return Err(AppError::location_frozen(format!(
+                "Location '{}' is frozen by open cycle count {}",
+                location.code, open_count.id
+            )));
+        }
+
+        let held = hold_entry(db, open_count.id, entry).await?;
+        return Ok(PostedEntry::Held(held));
+    }
+
+    let ledger_entry = insert_entry(db, entry).await?;
+    Ok(PostedEntry::Posted(ledger_entry))
+}
+
+/// Insert a ledger row and move inventory_balance by the same amount
+/// balance_after is the on-hand returned by the balance upsert, which also locks the balance row
+/// until the transaction ends. Skips the freeze check; callers must go through post_entry unless
+/// they are lifting the freeze or have rejected frozen locations themselves. Movements that would
+/// take on-hand below zero are rejected unless the warehouse allows negative stock
+pub(crate) async fn insert_entry<C: ConnectionTrait>(
+    db: &C,
+    entry: NewLedgerEntry,
+) -> Result<ledger::Model, AppError> {
+    insert_row(db, entry, None).await
+}
+
+/// Insert many stock-adding ledger rows at once, returning their ids in the order given
+///
+/// The batch counterpart of insert_entry for bulk imports: one chain lock per warehouse, one
+/// balance upsert and one multi-row insert. Ids are allocated up front so each entry's id and
+/// balance_after are known before the insert, which appends the rows to the chain in order. Only
+/// takes positive quantity changes, so there is no stock to check; like insert_entry it skips the
+/// freeze check.
+pub(crate) async fn insert_entries<C: ConnectionTrait>(
+    db: &C,
+    entries: &[NewLedgerEntry],
+) -> Result<Vec<i32>, AppError> {
+    if entries.is_empty() {
+        return Ok(Vec::new());
+    }
+    if entries.iter().any(|entry| entry.quantity_change <= 0) {
+        return Err(AppError::internal(
+            "Batched ledger inserts only take positive quantity changes",
+        ));
+    }
+
+    let location_ids: Vec<i32> = entries.iter().map(|entry| entry.location_id).collect();
+    lock_chains(db, &location_ids).await?;
+
+    // Total change per item/location, upserted in key order
+    let mut totals: BTreeMap<(i32, i32), i32> = BTreeMap::new();
+    for entry in entries {
+        *totals
+            .entry((entry.item_id, entry.location_id))
+            .or_default() += entry.quantity_change;
+    }
+    let mut placeholders = Vec::with_capacity(totals.len());
+    let mut values: Vec<sea_orm::Value> = Vec::with_capacity(totals.len() * 3);
+    for (index, (&(item_id, location_id), &quantity_change)) in totals.iter().enumerate() {
+        placeholders.push(format!(
+            "(${}, ${}, ${}, NOW())",
+            index * 3 + 1,
+            index * 3 + 2,
+            index * 3 + 3
+        ));
+        values.extend([item_id.into(), location_id.into(), quantity_change.into()]);
+    }
+    let balances = db
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                INSERT INTO inventory_balance (item_id, location_id, on_hand, updated_at)
+                VALUES {}
+                ON CONFLICT (item_id, location_id) DO UPDATE
+                SET on_hand = inventory_balance.on_hand + EXCLUDED.on_hand,
+                    updated_at = EXCLUDED.updated_at
+                RETURNING item_id, location_id, on_hand
+                "#,
+                placeholders.join(", ")
+            ),
+            values,
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update inventory balance: {}", e)))?;
+
+    // On-hand before the batch, which each entry's balance_after then runs on from
+    let mut running: BTreeMap<(i32, i32), i32> = BTreeMap::new();
+    for row in balances {
+        let read = |column: &str| -> Result<i32, AppError> {
+            row.try_get("", column)
+                .map_err(|e| AppError::internal(format!("Failed to read inventory balance: {}", e)))
+        };
+        let key = (read("item_id")?, read("location_id")?);
+        running.insert(
+            key,
+            read("on_hand")? - totals.get(&key).copied().unwrap_or(0),
+        );
+    }
+
+    let mut ids: Vec<i32> = db
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT nextval(pg_get_serial_sequence('inventory_ledger', 'id'))::INTEGER AS id \
+             FROM generate_series(1, $1)",
+            [(entries.len() as i32).into()],
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to allocate ledger entry ids: {}", e)))?
+        .into_iter()
+        .map(|row| row.try_get::<i32>("", "id"))
+        .collect::<Result<_, _>>()
+        .map_err(|e| AppError::internal(format!("Failed to allocate ledger entry ids: {}", e)))?;
+    ids.sort_unstable();
+
+    let mut active_models = Vec::with_capacity(entries.len());
+    for (entry, &id) in entries.iter().zip(&ids) {
+        let balance_after = running
+            .entry((entry.item_id, entry.location_id))
+            .or_default();
+        *balance_after += entry.quantity_change;
+
+        let mut active_model = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
+        active_model.id = Set(id);
+        active_model.item_id = Set(entry.item_id);
+        active_model.location_id = Set(entry.location_id);
+        active_model.quantity_change = Set(entry.quantity_change);
+        active_model.balance_after = Set(Some(*balance_after));
+        active_model.reason_type = Set(entry.reason_type.clone());
+        active_model.reference_type = Set(entry.reference_type.clone());
+        active_model.reference_id = Set(entry.reference_id);
+        active_model.reverses_entry_id = Set(None);
+        active_models.push(active_model);
+    }
+
+    ledger::Entity::insert_many(active_models)
+        .exec_without_returning(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create ledger entries: {}", e)))?;
+
+    Ok(ids)
+}
+
+async fn insert_row<C: ConnectionTrait>(
+    db: &C,
+    entry: NewLedgerEntry,
+    reverses_entry_id: Option<i32>,
+) -> Result<ledger::Model, AppError> {
+    lock_chains(db, &[entry.location_id]).await?;
+    ensure_stock_covers(db, &entry).await?;
+
+    let balance_after =
+        apply_to_balance(db, entry.item_id, entry.location_id, entry.quantity_change).await?;
+
+    let mut active_model = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.item_id = Set(entry.item_id);
+    active_model.location_id = Set(entry.location_id);
+    active_model.quantity_change = Set(entry.quantity_change);
+    active_model.balance_after = Set(Some(balance_after));
+    active_model.reason_type = Set(entry.reason_type);
+    active_model.reference_type = Set(entry.reference_type);
+    active_model.reference_id = Set(entry.reference_id);
+    active_model.reverses_entry_id = Set(reverses_entry_id);
+
+    active_model
+        .insert(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create ledger entry: {}", e)))
+}
+
+/// Take the append lock of the ledger chain of each warehouse the locations belong to
+///
+/// Each warehouse's entries form a hash chain, so entries for one warehouse are written by one
+/// transaction at a time. Every ledger insert takes its warehouse's lock (the insert trigger does
+/// too); operations that write to several warehouses call this first with all their locations so
+/// the locks are always taken in warehouse id order. Held until the transaction ends.
+///
+/// The locations' rows are taken FOR SHARE before the chains, so a cycle count cannot open or
+/// close on a location while a movement there is in flight (see cycle_count::share_locations).
+/// Locks are always taken in the order stock (inventory::lock_stock), location rows, chains,
+/// balance rows: insert_row takes the chain lock before ensure_stock_covers or the balance upsert
+/// touches the balance row.
+pub async fn lock_chains<C: ConnectionTrait>(db: &C, location_ids: &[i32]) -> Result<(), AppError> {
+    cycle_count::share_locations(db, location_ids).await?;
+
+    let mut warehouse_ids: Vec<i32> = location::Entity::find()
+        .select_only()
+        .column(location::Column::WarehouseId)
+        .filter(location::Column::Id.is_in(location_ids.iter().copied()))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location warehouses: {}", e)))?;
+    warehouse_ids.sort_unstable();
+    warehouse_ids.dedup();
+
+    for warehouse_id in warehouse_ids {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT inventory_ledger_chain_lock($1)",
+            [warehouse_id.into()],
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to lock ledger chain: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// Ledger entries a document posted, oldest first
+/// Refuses documents with movements still held by a cycle count, since those are not in the
+/// ledger yet and could not be reversed with the rest
+pub async fn find_posted_entries<C: ConnectionTrait>(
+    db: &C,
+    reason_type: &str,
+    reference_type: &str,
+    reference_id: i32,
+) -> Result<Vec<ledger::Model>, AppError> {
+    let held = held_movement::Entity::find()
+        .filter(held_movement::Column::ReferenceType.eq(reference_type))
+        .filter(held_movement::Column::ReferenceId.eq(reference_id))
+        .filter(held_movement::Column::Status.eq("HELD"))
+        .count(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch held movements: {}", e)))?;
+    if held > 0 {
+        return Err(AppError::bad_request(format!(
+            "{} {} has {} movement(s) held by an open cycle count",
+            reference_type, reference_id, held
+        )));
+    }
+
+    ledger::Entity::find()
+        .filter(ledger::Column::ReasonType.eq(reason_type))
+        .filter(ledger::Column::ReferenceType.eq(reference_type))
+        .filter(ledger::Column::ReferenceId.eq(reference_id))
+        .order_by_asc(ledger::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch ledger entries: {}", e)))
+}
+
+/// Post equal-and-opposite REVERSAL entries for posted ledger entries
+///
+/// Each reversal keeps the original's reference and links back through reverses_entry_id; the
+/// originals are left untouched. Must be called on a transaction. Stock is locked for every
+/// item/location touched, and a reversal that would take on-hand or unreserved stock below zero
+/// is refused whatever the warehouse's negative stock policy, since that stock has already been
+/// used. Reversals are never held: a location frozen by a cycle count rejects them.
+pub async fn reverse_entries<C: ConnectionTrait>(
+    db: &C,
+    originals: &[ledger::Model],
+) -> Result<Vec<ledger::Model>, AppError> {
+    let stock_keys: Vec<(i32, i32)> = originals
+        .iter()
+        .map(|entry| (entry.item_id, entry.location_id))
+        .collect();
+    inventory::lock_stock(db, &stock_keys).await?;
+    let location_ids: Vec<i32> = stock_keys
+        .iter()
+        .map(|(_, location_id)| *location_id)
+        .collect();
+    lock_chains(db, &location_ids).await?;
+
+    let reversed: Vec<i32> = ledger::Entity::find()
+        .select_only()
+        .column(ledger::Column::ReversesEntryId)
+        .filter(ledger::Column::ReversesEntryId.is_in(originals.iter().map(|entry| entry.id)))
+        .into_tuple::<Option<i32>>()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch reversals: {}", e)))?
+        .into_iter()
+        .flatten()
+        .collect();
+    if !reversed.is_empty() {
+        return Err(AppError::bad_request(format!(
+            "Ledger entries already reversed: {}",
+            reversed
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+
+    // Stock each item/location gives up, summed so several lines on one key are checked together
+    let mut removals: BTreeMap<(i32, i32), i32> = BTreeMap::new();
+    for entry in originals.iter().filter(|entry| entry.quantity_change > 0) {
+        *removals
+            .entry((entry.item_id, entry.location_id))
+            .or_default() += entry.quantity_change;
+    }
+    for (&(item_id, location_id), &requested) in &removals {
+        let on_hand = inventory::calculate_on_hand(db, item_id, location_id, None).await?;
+        if on_hand < requested {
+            let shortfall = stock_shortfall(db, item_id, location_id, on_hand, requested).await?;
+            return Err(AppError::insufficient_stock(shortfall));
+        }
+
+        let available = inventory::calculate_available(db, item_id, location_id).await?;
+        if available < requested {
+            return Err(AppError::bad_request(format!(
+                "Cannot reverse: stock of item_id {} at location_id {} is reserved. Available: {}, Requested: {}",
+                item_id, location_id, available, requested
+            )));
+        }
+    }
+
+    let mut reversals = Vec::new();
+    for original in originals {
+        if let Some(open_count) =
+            cycle_count::find_open_cycle_count(db, original.location_id).await?
+        {
+            return Err(AppError::location_frozen(format!(
+                "Location with id {} is frozen by open cycle count {}",
+                original.location_id, open_count.id
+            )));
+        }
+
+        let reversal = insert_row(
+            db,
+            NewLedgerEntry {
+                item_id: original.item_id,
+                location_id: original.location_id,
+                quantity_change: -original.quantity_change,
+                reason_type: "REVERSAL".to_string(),
+                reference_type: original.reference_type.clone(),
+                reference_id: original.reference_id,
+            },
+            Some(original.id),
+        )
+        .await?;
+
+        reversals.push(reversal);
+    }
+
+    Ok(reversals)
+}
+
+/// Fail with INSUFFICIENT_STOCK if a negative movement exceeds the on-hand at its location
+/// The balance row is locked first, so the on-hand checked is the one the movement is applied to
+async fn ensure_stock_covers<C: ConnectionTrait>(
+    db: &C,
+    entry: &NewLedgerEntry,
+) -> Result<(), AppError> {
+    if entry.quantity_change >= 0 {
+        return Ok(());
+    }
+
+    let on_hand = inventory_balance::Entity::find()
+        .filter(inventory_balance::Column::ItemId.eq(entry.item_id))
+        .filter(inventory_balance::Column::LocationId.eq(entry.location_id))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch inventory balance: {}", e)))?
+        .map(|balance| balance.on_hand)
+        .unwrap_or(0);
+
+    let requested = -entry.quantity_change;
+    if on_hand >= requested {
+        return Ok(());
+    }
+
+    let location = location::Entity::find_by_id(entry.location_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Location with id {} not found", entry.location_id))
+        })?;
+
+    let allow_negative_stock = warehouse::Entity::find_by_id(location.warehouse_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .is_some_and(|w| w.allow_negative_stock);
+    if allow_negative_stock {
+        return Ok(());
+    }
+
+    let shortfall =
+        stock_shortfall(db, entry.item_id, entry.location_id, on_hand, requested).await?;
+    Err(AppError::insufficient_stock(shortfall))
+}
+
+/// Describe a shortfall at an item/location, naming both by code
+pub(crate) async fn stock_shortfall<C: ConnectionTrait>(
+    db: &C,
+    item_id: i32,
+    location_id: i32,
+    on_hand: i32,
+    requested: i32,
+) -> Result<StockShortfall, AppError> {
+    let item = item::Entity::find_by_id(item_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch item: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Item with id {} not found", item_id)))?;
+
+    let location = location::Entity::find_by_id(location_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Location with id {} not found", location_id))
+        })?;
+
+    Ok(StockShortfall {
+        item_id: item.id,
+        sku: item.sku,
+        location_id: location.id,
+        location_code: location.code,
+        on_hand,
+        requested,
+        shortfall: requested - on_hand,
+    })
+}
+
+/// Add a quantity change to the item/location balance and return the new on-hand
+async fn apply_to_balance<C: ConnectionTrait>(
+    db: &C,
+    item_id: i32,
+    location_id: i32,
+    quantity_change: i32,
+) -> Result<i32, AppError> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+            INSERT INTO inventory_balance (item_id, location_id, on_hand, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (item_id, location_id) DO UPDATE
+            SET on_hand = inventory_balance.on_hand + EXCLUDED.on_hand,
+                updated_at = EXCLUDED.updated_at
+            RETURNING on_hand
+            "#,
+            [item_id.into(), location_id.into(), quantity_change.into()],
+        ))
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update inventory balance: {}", e)))?
+        .ok_or_else(|| AppError::internal("Inventory balance upsert returned no row"))?;
+
+    row.try_get("", "on_hand")
+        .map_err(|e| AppError::internal(format!("Failed to read inventory balance: {}", e)))
+}
+
+async fn hold_entry<C: ConnectionTrait>(
+    db: &C,
+    cycle_count_id: i32,
+    entry: NewLedgerEntry,
+) -> Result<held_movement::Model, AppError> {
+    let mut active_model = <held_movement::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.cycle_count_id = Set(cycle_count_id);
+    active_model.item_id = Set(entry.item_id);
+    active_model.location_id = Set(entry.location_id);
+    active_model.quantity_change = Set(entry.quantity_change);
+    active_model.reason_type = Set(entry.reason_type);
+    active_model.reference_type = Set(entry.reference_type);
+    active_model.reference_id = Set(entry.reference_id);
+    active_model.status = Set("HELD".to_string());
+
+    active_model
+        .insert(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to hold movement: {}", e)))
+}
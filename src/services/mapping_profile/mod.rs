@@ -0,0 +1,312 @@
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, ModelTrait,
+    PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
+
+use crate::entities::mapping_profile;
+use crate::entities::mapping_profile_field;
+use crate::services::receipt_file::{
+    ColumnMapping, FieldMapping, RECEIPT_COLUMNS, REQUIRED_COLUMNS,
+};
+use crate::utils::error::AppError;
+
+/// A mapping profile with how it reads each field
+#[derive(Debug, Clone)]
+pub struct MappingProfileWithFields {
+    pub profile: mapping_profile::Model,
+    pub fields: Vec<mapping_profile_field::Model>,
+}
+
+pub async fn create_profile(
+    db: &DatabaseConnection,
+    name: String,
+    description: Option<String>,
+    fields: Vec<FieldMapping>,
+) -> Result<MappingProfileWithFields, AppError> {
+    validate_fields(&fields)?;
+    check_name_free(db, &name, None).await?;
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let now = chrono::Utc::now();
+    let profile = mapping_profile::ActiveModel {
+        name: Set(name),
+        description: Set(description),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to create mapping profile: {}", e)))?;
+
+    let fields = insert_fields(&txn, profile.id, fields).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(MappingProfileWithFields { profile, fields })
+}
+
+pub async fn list_profiles(
+    db: &DatabaseConnection,
+) -> Result<Vec<mapping_profile::Model>, AppError> {
+    mapping_profile::Entity::find()
+        .order_by_asc(mapping_profile::Column::Name)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch mapping profiles: {}", e)))
+}
+
+pub async fn get_profile(
+    db: &DatabaseConnection,
+    profile_id: i32,
+) -> Result<MappingProfileWithFields, AppError> {
+    let profile = find_profile(db, profile_id).await?;
+    let fields = find_fields(db, profile.id).await?;
+
+    Ok(MappingProfileWithFields { profile, fields })
+}
+
+/// Rename or describe a profile, or replace all of its fields
+pub async fn update_profile(
+    db: &DatabaseConnection,
+    profile_id: i32,
+    name: Option<String>,
+    description: Option<String>,
+    fields: Option<Vec<FieldMapping>>,
+) -> Result<MappingProfileWithFields, AppError> {
+    let profile = find_profile(db, profile_id).await?;
+    if let Some(fields) = &fields {
+        validate_fields(fields)?;
+    }
+    if let Some(name) = &name {
+        check_name_free(db, name, Some(profile_id)).await?;
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let mut update: mapping_profile::ActiveModel = profile.into();
+    if let Some(name) = name {
+        update.name = Set(name);
+    }
+    if let Some(description) = description {
+        update.description = Set(Some(description));
+    }
+    update.updated_at = Set(chrono::Utc::now().into());
+    let profile = update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update mapping profile: {}", e)))?;
+
+    let fields = match fields {
+        Some(fields) => {
+            mapping_profile_field::Entity::delete_many()
+                .filter(mapping_profile_field::Column::ProfileId.eq(profile_id))
+                .exec(&txn)
+                .await
+                .map_err(|e| {
+                    AppError::internal(format!("Failed to replace mapping profile fields: {}", e))
+                })?;
+            insert_fields(&txn, profile_id, fields).await?
+        }
+        None => find_fields(&txn, profile_id).await?,
+    };
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(MappingProfileWithFields { profile, fields })
+}
+
+/// Delete a profile; imports already queued with it will fail
+pub async fn delete_profile(db: &DatabaseConnection, profile_id: i32) -> Result<(), AppError> {
+    let profile = find_profile(db, profile_id).await?;
+
+    profile
+        .delete(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to delete mapping profile: {}", e)))?;
+
+    Ok(())
+}
+
+/// The column mapping of the profile with the given name
+pub async fn find_column_mapping(
+    db: &DatabaseConnection,
+    name: &str,
+) -> Result<ColumnMapping, AppError> {
+    let profile = mapping_profile::Entity::find()
+        .filter(mapping_profile::Column::Name.eq(name))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch mapping profile: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Mapping profile '{}' not found", name)))?;
+    let fields = find_fields(db, profile.id).await?;
+
+    Ok(ColumnMapping {
+        profile: Some(profile.name),
+        fields: fields
+            .into_iter()
+            .map(|field| FieldMapping {
+                field: field.field,
+                source_column: field.source_column,
+                default_value: field.default_value,
+                trim: field.trim,
+                uppercase: field.uppercase,
+                multiplier: field.multiplier,
+            })
+            .collect(),
+    })
+}
+
+async fn find_profile(
+    db: &DatabaseConnection,
+    profile_id: i32,
+) -> Result<mapping_profile::Model, AppError> {
+    mapping_profile::Entity::find_by_id(profile_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch mapping profile: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Mapping profile with id {} not found", profile_id))
+        })
+}
+
+async fn find_fields<C: ConnectionTrait>(
+    db: &C,
+    profile_id: i32,
+) -> Result<Vec<mapping_profile_field::Model>, AppError> {
+    mapping_profile_field::Entity::find()
+        .filter(mapping_profile_field::Column::ProfileId.eq(profile_id))
+        .order_by_asc(mapping_profile_field::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch mapping profile fields: {}", e)))
+}
+
+async fn insert_fields<C: ConnectionTrait>(
+    db: &C,
+    profile_id: i32,
+    fields: Vec<FieldMapping>,
+) -> Result<Vec<mapping_profile_field::Model>, AppError> {
+    let mut inserted = Vec::with_capacity(fields.len());
+    for field in fields {
+        let model = mapping_profile_field::ActiveModel {
+            profile_id: Set(profile_id),
+            field: Set(field.field),
+            source_column: Set(field.source_column),
+            default_value: Set(field.default_value),
+            trim: Set(field.trim),
+            uppercase: Set(field.uppercase),
+            multiplier: Set(field.multiplier),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .map_err(|e| {
+            AppError::internal(format!("Failed to create mapping profile field: {}", e))
+        })?;
+        inserted.push(model);
+    }
+
+    Ok(inserted)
+}
+
+async fn check_name_free(
+    db: &DatabaseConnection,
+    name: &str,
+    profile_id: Option<i32>,
+) -> Result<(), AppError> {
+    let mut query = mapping_profile::Entity::find().filter(mapping_profile::Column::Name.eq(name));
+    if let Some(profile_id) = profile_id {
+        query = query.filter(mapping_profile::Column::Id.ne(profile_id));
+    }
+    let existing = query
+        .count(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check mapping profile name: {}", e)))?;
+    if existing > 0 {
+        return Err(AppError::bad_request(format!(
+            "Mapping profile '{}' already exists",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Every field a receipt field, mapped once, read from a column or given a default; the
+/// required fields mapped; defaults of the right type; and multipliers only on quantity
+fn validate_fields(fields: &[FieldMapping]) -> Result<(), AppError> {
+    for (i, mapping) in fields.iter().enumerate() {
+        if !RECEIPT_COLUMNS.contains(&mapping.field.as_str()) {
+            return Err(AppError::bad_request(format!(
+                "Unknown field '{}'. Expected one of: {}",
+                mapping.field,
+                RECEIPT_COLUMNS.join(", ")
+            )));
+        }
+        if fields[..i].iter().any(|other| other.field == mapping.field) {
+            return Err(AppError::bad_request(format!(
+                "Field '{}' is mapped more than once",
+                mapping.field
+            )));
+        }
+        if mapping.source_column.is_none() && mapping.default_value.is_none() {
+            return Err(AppError::bad_request(format!(
+                "Field '{}' needs a source_column or a default_value",
+                mapping.field
+            )));
+        }
+
+        if let Some(multiplier) = mapping.multiplier {
+            if mapping.field != "quantity" {
+                return Err(AppError::bad_request(format!(
+                    "Field '{}' cannot have a multiplier; only quantity can",
+                    mapping.field
+                )));
+            }
+            if multiplier < 1 {
+                return Err(AppError::bad_request(
+                    "Multiplier must be a positive integer",
+                ));
+            }
+        }
+
+        if let Some(default_value) = &mapping.default_value {
+            let valid = match mapping.field.as_str() {
+                "quantity" | "warehouse_id" => default_value.parse::<i32>().is_ok(),
+                "is_serialized" | "is_pickable" | "is_bulk" => {
+                    default_value.parse::<bool>().is_ok()
+                }
+                _ => true,
+            };
+            if !valid {
+                return Err(AppError::bad_request(format!(
+                    "Invalid default_value '{}' for field '{}'",
+                    default_value, mapping.field
+                )));
+            }
+        }
+    }
+
+    for col in REQUIRED_COLUMNS {
+        if !fields.iter().any(|mapping| mapping.field == col) {
+            return Err(AppError::bad_request(format!(
+                "Required field '{}' must be mapped",
+                col
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,17 @@
+pub mod abc_classification;
+pub mod adjustment;
+pub mod cycle_count;
+pub mod cycle_count_schedule;
+pub mod idempotency;
+pub mod import_job;
 pub mod inventory;
+pub mod ledger;
+pub mod mapping_profile;
 pub mod pick;
+pub mod purchase_order;
+pub mod putaway;
+pub mod putaway_rule;
 pub mod receipt;
+pub mod receipt_file;
 pub mod reservation;
 pub mod transfer;
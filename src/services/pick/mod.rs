@@ -1,11 +1,14 @@
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set, TransactionTrait};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, Set, TransactionTrait,
+};
 
 use crate::db::DatabaseConnection;
-use crate::entities::ledger;
+use crate::entities::ledger as ledger_entity;
 use crate::entities::pick;
 use crate::entities::pick_line;
 use crate::entities::reservation;
 use crate::services::inventory;
+use crate::services::ledger::{self, NewLedgerEntry, PostedEntry};
 use crate::services::receipt;
 use crate::services::reservation as reservation_service;
 use crate::utils::error::AppError;
@@ -39,6 +42,14 @@ pub struct ConfirmedPick {
     pub wave: pick::Model,
     pub lines: Vec<pick_line::Model>,
     pub ledger_entries: Vec<i32>, // ledger entry IDs
+    pub held_movements: Vec<i32>, // held movement IDs (location frozen by a cycle count)
+}
+
+/// Reversed pick wave with the REVERSAL entries that returned its stock
+#[derive(Debug)]
+pub struct ReversedPickWave {
+    pub wave: pick::Model,
+    pub reversals: Vec<ledger_entity::Model>,
 }
 
 /// Create a pick wave with DRAFT status and pick lines
@@ -121,13 +132,20 @@ pub async fn create_pick_wave(
 }
 
 /// Allocate inventory for a pick wave by creating reservations
-/// Validates pick wave is in DRAFT status and stock is available
+/// Validates pick wave is in DRAFT status and stock is available. The wave row and the stock of
+/// every line are locked for the transaction, so concurrent allocations cannot reserve the same units
 pub async fn allocate_pick_wave(
     db: &DatabaseConnection,
     pick_wave_id: i32,
 ) -> Result<AllocatedPickWave, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
     let wave = pick::Entity::find_by_id(pick_wave_id)
-        .one(db)
+        .lock_exclusive()
+        .one(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to find pick wave: {}", e)))?
         .ok_or_else(|| {
@@ -143,7 +161,7 @@ pub async fn allocate_pick_wave(
 
     let lines = pick_line::Entity::find()
         .filter(pick_line::Column::WaveId.eq(pick_wave_id))
-        .all(db)
+        .all(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to fetch pick lines: {}", e)))?;
 
@@ -154,10 +172,17 @@ pub async fn allocate_pick_wave(
         )));
     }
 
+    let stock_keys: Vec<(i32, i32)> = lines
+        .iter()
+        .map(|line| (line.item_id, line.location_id))
+        .collect();
+    inventory::lock_stock(&txn, &stock_keys).await?;
+
     // Check available stock and create reservations
     let mut reservations = Vec::new();
     for line in &lines {
-        let available = inventory::calculate_available(db, line.item_id, line.location_id).await?;
+        let available =
+            inventory::calculate_available(&txn, line.item_id, line.location_id).await?;
         if available < line.quantity {
             return Err(AppError::bad_request(format!(
                 "Insufficient stock for pick line {} (item_id: {}, location_id: {}). Available: {}, Requested: {}",
@@ -166,7 +191,7 @@ pub async fn allocate_pick_wave(
         }
 
         let reservation = reservation_service::create_reservation(
-            db,
+            &txn,
             line.item_id,
             line.location_id,
             line.quantity,
@@ -182,10 +207,14 @@ pub async fn allocate_pick_wave(
     wave_update.status = Set("ALLOCATED".to_string());
 
     let updated_wave = wave_update
-        .update(db)
+        .update(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to update pick wave status: {}", e)))?;
 
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
     Ok(AllocatedPickWave {
         wave: updated_wave,
         lines,
@@ -205,6 +234,7 @@ pub async fn confirm_pick(
         .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
 
     let wave = pick::Entity::find_by_id(pick_wave_id)
+        .lock_exclusive()
         .one(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to fetch pick wave: {}", e)))?
@@ -232,30 +262,48 @@ pub async fn confirm_pick(
         )));
     }
 
+    let stock_keys: Vec<(i32, i32)> = lines
+        .iter()
+        .map(|line| (line.item_id, line.location_id))
+        .collect();
+    inventory::lock_stock(&txn, &stock_keys).await?;
+    let location_ids: Vec<i32> = lines.iter().map(|line| line.location_id).collect();
+    ledger::lock_chains(&txn, &location_ids).await?;
+
     // Create ledger entries and update line statuses
     let mut ledger_entries = Vec::new();
+    let mut held_movements = Vec::new();
     let mut updated_lines = Vec::new();
 
     for line in &lines {
-        // Calculate current on_hand before the pick
-        let on_hand = inventory::calculate_on_hand(&txn, line.item_id, line.location_id).await?;
-        let balance_after = on_hand - line.quantity;
-
         // Create ledger entry with -qty PICK
-        let mut ledger_entry = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
-        ledger_entry.item_id = Set(line.item_id);
-        ledger_entry.location_id = Set(line.location_id);
-        ledger_entry.quantity_change = Set(-line.quantity);
-        ledger_entry.balance_after = Set(Some(balance_after));
-        ledger_entry.reason_type = Set("PICK".to_string());
-        ledger_entry.reference_type = Set(Some("pick_wave".to_string()));
-        ledger_entry.reference_id = Set(Some(wave.id));
-
-        let entry = ledger_entry.insert(&txn).await.map_err(|e| {
-            AppError::internal(format!("Failed to create ledger entry: {}", e))
-        })?;
+        let posted = ledger::post_entry(
+            &txn,
+            NewLedgerEntry {
+                item_id: line.item_id,
+                location_id: line.location_id,
+                quantity_change: -line.quantity,
+                reason_type: "PICK".to_string(),
+                reference_type: Some("pick_wave".to_string()),
+                reference_id: Some(wave.id),
+            },
+        )
+        .await?;
 
-        ledger_entries.push(entry.id);
+        // A held pick keeps its reservation until the count closes and the stock leaves
+        match posted {
+            PostedEntry::Posted(entry) => {
+                reservation_service::release_reservations_for_pick_line(
+                    &txn,
+                    pick_wave_id,
+                    line.item_id,
+                    line.location_id,
+                )
+                .await?;
+                ledger_entries.push(entry.id);
+            }
+            PostedEntry::Held(held) => held_movements.push(held.id),
+        }
 
         // Update pick line status to CONFIRMED
         let mut line_update: pick_line::ActiveModel = line.clone().into();
@@ -269,9 +317,6 @@ pub async fn confirm_pick(
         updated_lines.push(updated_line);
     }
 
-    // Release all reservations for the pick wave
-    reservation_service::release_reservations_for_pick_wave(&txn, pick_wave_id).await?;
-
     // Update pick wave status based on line completion
     let all_confirmed = updated_lines.iter().all(|line| line.status == "CONFIRMED");
     let new_status = if all_confirmed {
@@ -296,5 +341,54 @@ pub async fn confirm_pick(
         wave: updated_wave,
         lines: updated_lines,
         ledger_entries,
+        held_movements,
+    })
+}
+
+/// Reverse a COMPLETED pick wave by returning its picked stock and mark it REVERSED
+pub async fn reverse_pick_wave(
+    db: &DatabaseConnection,
+    pick_wave_id: i32,
+) -> Result<ReversedPickWave, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let wave = pick::Entity::find_by_id(pick_wave_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick wave: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Pick wave with id {} not found", pick_wave_id))
+        })?;
+
+    if wave.status != "COMPLETED" {
+        return Err(AppError::bad_request(format!(
+            "Pick wave with id {} is not in COMPLETED status (current status: {})",
+            pick_wave_id, wave.status
+        )));
+    }
+
+    let entries = ledger::find_posted_entries(&txn, "PICK", "pick_wave", pick_wave_id).await?;
+    let reversals = ledger::reverse_entries(&txn, &entries).await?;
+
+    let mut wave_update: pick::ActiveModel = wave.into();
+    wave_update.status = Set("REVERSED".to_string());
+    wave_update.updated_at = Set(chrono::Utc::now().into());
+
+    let updated_wave = wave_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update pick wave status: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(ReversedPickWave {
+        wave: updated_wave,
+        reversals,
     })
 }
@@ -0,0 +1,592 @@
+use std::collections::HashMap;
+
+use sea_orm::prelude::Date;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, SqlErr, TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::item;
+use crate::entities::po_line;
+use crate::entities::purchase_order;
+use crate::entities::putaway_task;
+use crate::entities::receipt as receipt_entity;
+use crate::entities::receipt_line;
+use crate::entities::supplier;
+use crate::services::putaway;
+use crate::services::receipt::{self, ReceiptHeader, ReceivedLine};
+use crate::utils::error::AppError;
+
+/// Request item for purchase order creation
+#[derive(Debug, Clone)]
+pub struct PurchaseOrderItem {
+    pub sku: String,
+    pub quantity: i32,
+}
+
+/// Header of a new purchase order; tolerances default to the supplier's
+#[derive(Debug, Clone)]
+pub struct NewPurchaseOrder {
+    pub po_number: String,
+    pub supplier_id: i32,
+    pub expected_at: Option<Date>,
+    pub over_receipt_tolerance_percent: Option<i32>,
+    pub under_receipt_tolerance_percent: Option<i32>,
+}
+
+/// Purchase order with its lines
+#[derive(Debug)]
+pub struct PurchaseOrderWithLines {
+    pub purchase_order: purchase_order::Model,
+    pub lines: Vec<po_line::Model>,
+}
+
+/// Request item for receiving against a purchase order
+#[derive(Debug, Clone)]
+pub struct PurchaseOrderReceiptItem {
+    pub sku: String,
+    pub location_code: Option<String>, // None to receive into the warehouse's receiving location
+    pub warehouse_id: Option<i32>,
+    pub quantity: i32,
+}
+
+/// Receipt posted against a purchase order, with the order as it stands afterwards
+#[derive(Debug)]
+pub struct PurchaseOrderReceipt {
+    pub receipt: receipt_entity::Model,
+    pub received: Vec<ReceivedLine>,
+    pub putaway_tasks: Vec<putaway_task::Model>, // for lines received into the receiving location
+    pub purchase_order: PurchaseOrderWithLines,
+}
+
+/// Most a line may receive in total: the ordered quantity plus the over-receipt tolerance
+pub fn max_receivable(purchase_order: &purchase_order::Model, line: &po_line::Model) -> i32 {
+    (line.quantity_ordered as i64 * (100 + purchase_order.over_receipt_tolerance_percent) as i64
+        / 100) as i32
+}
+
+/// Quantity from which a line counts as fully received: the ordered quantity less the
+/// under-receipt tolerance, rounded up
+pub fn min_complete(purchase_order: &purchase_order::Model, line: &po_line::Model) -> i32 {
+    let percent = (100 - purchase_order.under_receipt_tolerance_percent) as i64;
+    ((line.quantity_ordered as i64 * percent + 99) / 100) as i32
+}
+
+/// Status of an OPEN or receiving purchase order given what its lines have received
+fn receiving_status(
+    purchase_order: &purchase_order::Model,
+    lines: &[po_line::Model],
+) -> &'static str {
+    if lines
+        .iter()
+        .all(|line| line.quantity_received >= min_complete(purchase_order, line))
+    {
+        "CLOSED"
+    } else if lines.iter().any(|line| line.quantity_received > 0) {
+        "PARTIALLY_RECEIVED"
+    } else {
+        "OPEN"
+    }
+}
+
+fn validate_tolerance(name: &str, percent: i32) -> Result<(), AppError> {
+    if !(0..=100).contains(&percent) {
+        return Err(AppError::bad_request(format!(
+            "{} must be between 0 and 100",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Create a purchase order with DRAFT status and its lines
+pub async fn create_purchase_order(
+    db: &DatabaseConnection,
+    order: NewPurchaseOrder,
+    items: Vec<PurchaseOrderItem>,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    if items.is_empty() {
+        return Err(AppError::bad_request("At least one item is required"));
+    }
+
+    let supplier = supplier::Entity::find_by_id(order.supplier_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch supplier: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Supplier with id {} not found", order.supplier_id))
+        })?;
+    if !supplier.is_active {
+        return Err(AppError::bad_request(format!(
+            "Supplier '{}' is not active",
+            supplier.code
+        )));
+    }
+
+    let over = order
+        .over_receipt_tolerance_percent
+        .unwrap_or(supplier.over_receipt_tolerance_percent);
+    let under = order
+        .under_receipt_tolerance_percent
+        .unwrap_or(supplier.under_receipt_tolerance_percent);
+    validate_tolerance("over_receipt_tolerance_percent", over)?;
+    validate_tolerance("under_receipt_tolerance_percent", under)?;
+
+    let existing = purchase_order::Entity::find()
+        .filter(purchase_order::Column::PoNumber.eq(order.po_number.as_str()))
+        .count(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check PO number: {}", e)))?;
+    if existing > 0 {
+        return Err(AppError::bad_request(format!(
+            "Purchase order '{}' already exists",
+            order.po_number
+        )));
+    }
+
+    let mut requested_items: Vec<(item::Model, i32)> = Vec::new();
+    for item_request in &items {
+        if item_request.quantity <= 0 {
+            return Err(AppError::bad_request(format!(
+                "Quantity must be positive for item with SKU '{}'",
+                item_request.sku
+            )));
+        }
+
+        let item = receipt::find_item_by_sku(db, &item_request.sku)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Item with SKU '{}' not found", item_request.sku))
+            })?;
+
+        if requested_items
+            .iter()
+            .any(|(requested, _)| requested.id == item.id)
+        {
+            return Err(AppError::bad_request(format!(
+                "Item with SKU '{}' appears more than once",
+                item_request.sku
+            )));
+        }
+
+        requested_items.push((item, item_request.quantity));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let mut active_model = <purchase_order::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.po_number = Set(order.po_number.clone());
+    active_model.supplier_id = Set(supplier.id);
+    active_model.status = Set("DRAFT".to_string());
+    active_model.over_receipt_tolerance_percent = Set(over);
+    active_model.under_receipt_tolerance_percent = Set(under);
+    active_model.expected_at = Set(order.expected_at);
+
+    // The check above can race another request creating the same number
+    let purchase_order = active_model
+        .insert(&txn)
+        .await
+        .map_err(|e| match e.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => AppError::bad_request(format!(
+                "Purchase order '{}' already exists",
+                order.po_number
+            )),
+            _ => AppError::internal(format!("Failed to create purchase order: {}", e)),
+        })?;
+
+    let mut lines = Vec::new();
+    for (item, quantity) in requested_items {
+        let mut line_model = <po_line::ActiveModel as sea_orm::ActiveModelTrait>::default();
+        line_model.purchase_order_id = Set(purchase_order.id);
+        line_model.item_id = Set(item.id);
+        line_model.quantity_ordered = Set(quantity);
+        line_model.quantity_received = Set(0);
+
+        let line = line_model
+            .insert(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create PO line: {}", e)))?;
+        lines.push(line);
+    }
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(PurchaseOrderWithLines {
+        purchase_order,
+        lines,
+    })
+}
+
+async fn find_lines<C: ConnectionTrait>(
+    db: &C,
+    purchase_order_id: i32,
+    lock: bool,
+) -> Result<Vec<po_line::Model>, AppError> {
+    let mut query = po_line::Entity::find()
+        .filter(po_line::Column::PurchaseOrderId.eq(purchase_order_id))
+        .order_by_asc(po_line::Column::Id);
+    if lock {
+        query = query.lock_exclusive();
+    }
+
+    query
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch PO lines: {}", e)))
+}
+
+/// Fetch a purchase order, locking it when `lock` is set
+pub async fn find_purchase_order<C: ConnectionTrait>(
+    db: &C,
+    purchase_order_id: i32,
+    lock: bool,
+) -> Result<purchase_order::Model, AppError> {
+    let mut query = purchase_order::Entity::find_by_id(purchase_order_id);
+    if lock {
+        query = query.lock_exclusive();
+    }
+
+    query
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch purchase order: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!(
+                "Purchase order with id {} not found",
+                purchase_order_id
+            ))
+        })
+}
+
+pub async fn get_purchase_order(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    let purchase_order = find_purchase_order(db, purchase_order_id, false).await?;
+    let lines = find_lines(db, purchase_order_id, false).await?;
+
+    Ok(PurchaseOrderWithLines {
+        purchase_order,
+        lines,
+    })
+}
+
+/// List purchase orders, optionally filtered by status and supplier
+pub async fn list_purchase_orders(
+    db: &DatabaseConnection,
+    status: Option<String>,
+    supplier_id: Option<i32>,
+) -> Result<Vec<purchase_order::Model>, AppError> {
+    let mut query = purchase_order::Entity::find();
+    if let Some(status) = status {
+        query = query.filter(purchase_order::Column::Status.eq(status));
+    }
+    if let Some(supplier_id) = supplier_id {
+        query = query.filter(purchase_order::Column::SupplierId.eq(supplier_id));
+    }
+
+    query
+        .order_by_asc(purchase_order::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch purchase orders: {}", e)))
+}
+
+/// Move a purchase order from one of `from` to `to`, returning it with its lines
+async fn change_status(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+    from: &[&str],
+    to: &str,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let purchase_order = find_purchase_order(&txn, purchase_order_id, true).await?;
+    if !from.contains(&purchase_order.status.as_str()) {
+        return Err(AppError::bad_request(format!(
+            "Purchase order with id {} is not in {} status (current status: {})",
+            purchase_order_id,
+            from.join(" or "),
+            purchase_order.status
+        )));
+    }
+
+    let lines = find_lines(&txn, purchase_order_id, false).await?;
+    if to == "CANCELLED" && lines.iter().any(|line| line.quantity_received > 0) {
+        return Err(AppError::bad_request(format!(
+            "Purchase order with id {} has received stock; close it instead",
+            purchase_order_id
+        )));
+    }
+
+    let mut update: purchase_order::ActiveModel = purchase_order.into();
+    update.status = Set(to.to_string());
+    update.closed_manually = Set(to == "CLOSED");
+    update.updated_at = Set(chrono::Utc::now().into());
+
+    let purchase_order = update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update purchase order: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(PurchaseOrderWithLines {
+        purchase_order,
+        lines,
+    })
+}
+
+/// Release a DRAFT purchase order for receiving
+pub async fn open_purchase_order(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    change_status(db, purchase_order_id, &["DRAFT"], "OPEN").await
+}
+
+/// Close a purchase order short; nothing more can be received against it
+pub async fn close_purchase_order(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    change_status(
+        db,
+        purchase_order_id,
+        &["OPEN", "PARTIALLY_RECEIVED"],
+        "CLOSED",
+    )
+    .await
+}
+
+/// Cancel a purchase order that has not received anything
+pub async fn cancel_purchase_order(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+) -> Result<PurchaseOrderWithLines, AppError> {
+    change_status(db, purchase_order_id, &["DRAFT", "OPEN"], "CANCELLED").await
+}
+
+/// Post a receipt against an OPEN or PARTIALLY_RECEIVED purchase order
+///
+/// Every item must be on the order, and no line may receive more in total than its ordered
+/// quantity plus the over-receipt tolerance. The order closes once every line has received at
+/// least its ordered quantity less the under-receipt tolerance.
+pub async fn receive_purchase_order(
+    db: &DatabaseConnection,
+    purchase_order_id: i32,
+    mut header: ReceiptHeader,
+    items: Vec<PurchaseOrderReceiptItem>,
+) -> Result<PurchaseOrderReceipt, AppError> {
+    if items.is_empty() {
+        return Err(AppError::bad_request("At least one item is required"));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    // The order is locked before the receipt, as reverse_receipt does
+    let purchase_order = find_purchase_order(&txn, purchase_order_id, true).await?;
+    if purchase_order.status != "OPEN" && purchase_order.status != "PARTIALLY_RECEIVED" {
+        return Err(AppError::bad_request(format!(
+            "Purchase order with id {} is not in OPEN or PARTIALLY_RECEIVED status (current status: {})",
+            purchase_order_id, purchase_order.status
+        )));
+    }
+    let mut lines = find_lines(&txn, purchase_order_id, true).await?;
+
+    let mut requested = Vec::new();
+    let mut line_totals: HashMap<i32, (i32, &str)> = HashMap::new(); // po_line id -> (quantity, SKU)
+    for item_request in &items {
+        if item_request.quantity <= 0 {
+            return Err(AppError::bad_request(format!(
+                "Quantity must be positive for item with SKU '{}'",
+                item_request.sku
+            )));
+        }
+
+        let item = receipt::find_item_by_sku(&txn, &item_request.sku)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Item with SKU '{}' not found", item_request.sku))
+            })?;
+        let line_index = lines
+            .iter()
+            .position(|line| line.item_id == item.id)
+            .ok_or_else(|| {
+                AppError::bad_request(format!(
+                    "Item with SKU '{}' is not on purchase order '{}'",
+                    item_request.sku, purchase_order.po_number
+                ))
+            })?;
+        let location = match (&item_request.location_code, item_request.warehouse_id) {
+            (Some(code), _) => receipt::find_location_by_code(&txn, code)
+                .await?
+                .ok_or_else(|| {
+                    AppError::not_found(format!("Location with code '{}' not found", code))
+                })?,
+            (None, Some(warehouse_id)) => {
+                putaway::find_receiving_location(&txn, warehouse_id).await?
+            }
+            (None, None) => {
+                return Err(AppError::bad_request(format!(
+                    "A location_code or warehouse_id is required for item with SKU '{}'",
+                    item_request.sku
+                )));
+            }
+        };
+
+        line_totals
+            .entry(lines[line_index].id)
+            .or_insert((0, item_request.sku.as_str()))
+            .0 += item_request.quantity;
+        requested.push((line_index, item_request.quantity, location));
+    }
+
+    for line in &lines {
+        let Some((quantity, sku)) = line_totals.get(&line.id) else {
+            continue;
+        };
+        let max = max_receivable(&purchase_order, line);
+        if line.quantity_received + quantity > max {
+            return Err(AppError::bad_request(format!(
+                "Receiving {} of SKU '{}' would exceed the over-receipt tolerance: {} ordered, {} received, at most {} more allowed",
+                quantity,
+                sku,
+                line.quantity_ordered,
+                line.quantity_received,
+                max - line.quantity_received
+            )));
+        }
+    }
+
+    let supplier = supplier::Entity::find_by_id(purchase_order.supplier_id)
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch supplier: {}", e)))?
+        .ok_or_else(|| AppError::internal("Purchase order supplier not found"))?;
+    header.supplier = Some(supplier.name);
+    header.supplier_id = Some(supplier.id);
+    header.purchase_order_id = Some(purchase_order.id);
+
+    let receipt = receipt::find_or_create_receipt(&txn, &header).await?;
+
+    let mut received = Vec::new();
+    for (line_index, quantity, location) in requested {
+        let line = &lines[line_index];
+        received.push(
+            receipt::create_receipt_ledger_entry(
+                &txn,
+                &receipt,
+                line.item_id,
+                location.id,
+                quantity,
+                Some(line.id),
+            )
+            .await?,
+        );
+    }
+
+    let received_lines: Vec<receipt_line::Model> = received
+        .iter()
+        .map(|received| received.line.clone())
+        .collect();
+    let putaway_tasks = putaway::create_tasks(&txn, &received_lines).await?;
+
+    for line in lines.iter_mut() {
+        if let Some((quantity, _)) = line_totals.get(&line.id) {
+            *line = update_received(&txn, line.clone(), line.quantity_received + quantity).await?;
+        }
+    }
+
+    let purchase_order = update_receiving_status(&txn, purchase_order, &lines).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(PurchaseOrderReceipt {
+        receipt,
+        received,
+        putaway_tasks,
+        purchase_order: PurchaseOrderWithLines {
+            purchase_order,
+            lines,
+        },
+    })
+}
+
+async fn update_received<C: ConnectionTrait>(
+    db: &C,
+    line: po_line::Model,
+    quantity_received: i32,
+) -> Result<po_line::Model, AppError> {
+    let mut update: po_line::ActiveModel = line.into();
+    update.quantity_received = Set(quantity_received);
+
+    update
+        .update(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update PO line: {}", e)))
+}
+
+async fn update_receiving_status<C: ConnectionTrait>(
+    db: &C,
+    purchase_order: purchase_order::Model,
+    lines: &[po_line::Model],
+) -> Result<purchase_order::Model, AppError> {
+    let status = receiving_status(&purchase_order, lines);
+    if purchase_order.status == status {
+        return Ok(purchase_order);
+    }
+
+    let mut update: purchase_order::ActiveModel = purchase_order.into();
+    update.status = Set(status.to_string());
+    update.updated_at = Set(chrono::Utc::now().into());
+
+    update
+        .update(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update purchase order: {}", e)))
+}
+
+/// Take a reversed receipt's lines off the purchase order's received quantities
+///
+/// The order must already be locked by the caller. An order CLOSED by receiving reopens if a
+/// line drops below its under-receipt threshold; one closed manually stays closed.
+pub async fn unreceive<C: ConnectionTrait>(
+    db: &C,
+    purchase_order: purchase_order::Model,
+    receipt_lines: &[receipt_line::Model],
+) -> Result<purchase_order::Model, AppError> {
+    let mut lines = find_lines(db, purchase_order.id, true).await?;
+
+    for line in lines.iter_mut() {
+        let reversed: i32 = receipt_lines
+            .iter()
+            .filter(|receipt_line| receipt_line.po_line_id == Some(line.id))
+            .map(|receipt_line| receipt_line.quantity)
+            .sum();
+        if reversed > 0 {
+            *line = update_received(db, line.clone(), line.quantity_received - reversed).await?;
+        }
+    }
+
+    if purchase_order.closed_manually {
+        return Ok(purchase_order);
+    }
+    update_receiving_status(db, purchase_order, &lines).await
+}
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use sea_orm::sea_query::{LockType, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::ledger as ledger_entity;
+use crate::entities::location;
+use crate::entities::putaway_task;
+use crate::entities::receipt_line;
+use crate::entities::warehouse;
+use crate::services::cycle_count;
+use crate::services::inventory;
+use crate::services::ledger::{self, NewLedgerEntry};
+use crate::services::putaway_rule;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+
+/// The location receipts sent to a warehouse are posted into
+pub async fn find_receiving_location<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+) -> Result<location::Model, AppError> {
+    let warehouse = warehouse::Entity::find_by_id(warehouse_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Warehouse with id {} not found", warehouse_id))
+        })?;
+
+    let location_id = warehouse.receiving_location_id.ok_or_else(|| {
+        AppError::bad_request(format!(
+            "Warehouse with id {} has no receiving location",
+            warehouse_id
+        ))
+    })?;
+
+    location::Entity::find_by_id(location_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+        .ok_or_else(|| {
+            AppError::internal(format!(
+                "Receiving location with id {} not found",
+                location_id
+            ))
+        })
+}
+
+/// Create putaway tasks for the receipt lines received into their warehouse's receiving location
+///
+/// Lines posted anywhere else are already where they belong and get no task. A line's stock goes
+/// to the best candidate of the warehouse's putaway rules, split over the next best while that
+/// has no room for all of it; each part is a task. Stock no candidate has room for is left on a
+/// task with no suggested location.
+pub async fn create_tasks<C: ConnectionTrait>(
+    db: &C,
+    lines: &[receipt_line::Model],
+) -> Result<Vec<putaway_task::Model>, AppError> {
+    let mut location_ids: Vec<i32> = lines.iter().map(|line| line.location_id).collect();
+    location_ids.sort_unstable();
+    location_ids.dedup();
+
+    let receiving: HashMap<i32, i32> = warehouse::Entity::find()
+        .filter(warehouse::Column::ReceivingLocationId.is_in(location_ids))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouses: {}", e)))?
+        .into_iter()
+        .filter_map(|warehouse| {
+            warehouse
+                .receiving_location_id
+                .map(|location_id| (location_id, warehouse.id))
+        })
+        .collect();
+
+    let mut tasks = Vec::new();
+    for line in lines {
+        let Some(&warehouse_id) = receiving.get(&line.location_id) else {
+            continue;
+        };
+
+        // Each task is OPEN once inserted, so the next suggestion leaves its room to it
+        let mut remaining = line.quantity;
+        while remaining > 0 {
+            let candidate =
+                putaway_rule::suggest_locations(db, warehouse_id, line.item_id, remaining, 1)
+                    .await?
+                    .pop();
+            let (suggested_location_id, quantity) = match candidate {
+                Some(candidate) => (Some(candidate.location.id), candidate.quantity),
+                None => (None, remaining),
+            };
+
+            let task = putaway_task::ActiveModel {
+                warehouse_id: Set(warehouse_id),
+                receipt_id: Set(line.receipt_id),
+                receipt_line_id: Set(line.id),
+                item_id: Set(line.item_id),
+                from_location_id: Set(line.location_id),
+                suggested_location_id: Set(suggested_location_id),
+                to_location_id: Set(None),
+                quantity: Set(quantity),
+                status: Set("OPEN".to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create putaway task: {}", e)))?;
+            tasks.push(task);
+            remaining -= quantity;
+        }
+    }
+
+    Ok(tasks)
+}
+
+/// Create putaway tasks for the lines of a receipt with id greater than `after_line_id`
+pub async fn create_tasks_for_receipt<C: ConnectionTrait>(
+    db: &C,
+    receipt_id: i32,
+    after_line_id: i32,
+) -> Result<Vec<putaway_task::Model>, AppError> {
+    let lines = receipt_line::Entity::find()
+        .filter(receipt_line::Column::ReceiptId.eq(receipt_id))
+        .filter(receipt_line::Column::Id.gt(after_line_id))
+        .filter(
+            receipt_line::Column::LocationId.in_subquery(
+                Query::select()
+                    .column(warehouse::Column::ReceivingLocationId)
+                    .from(warehouse::Entity)
+                    .and_where(warehouse::Column::ReceivingLocationId.is_not_null())
+                    .to_owned(),
+            ),
+        )
+        .order_by_asc(receipt_line::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt lines: {}", e)))?;
+
+    create_tasks(db, &lines).await
+}
+
+/// Filters for listing putaway tasks; all are optional
+#[derive(Debug, Clone, Default)]
+pub struct PutawayTaskFilter {
+    pub warehouse_id: Option<i32>,
+    pub receipt_id: Option<i32>,
+    pub status: Option<String>,
+}
+
+/// Putaway tasks matching the filter, oldest first
+pub async fn list_tasks(
+    db: &DatabaseConnection,
+    filter: &PutawayTaskFilter,
+) -> Result<Vec<putaway_task::Model>, AppError> {
+    let mut query = putaway_task::Entity::find();
+
+    if let Some(warehouse_id) = filter.warehouse_id {
+        query = query.filter(putaway_task::Column::WarehouseId.eq(warehouse_id));
+    }
+    if let Some(receipt_id) = filter.receipt_id {
+        query = query.filter(putaway_task::Column::ReceiptId.eq(receipt_id));
+    }
+    if let Some(status) = &filter.status {
+        query = query.filter(putaway_task::Column::Status.eq(status.clone()));
+    }
+
+    query
+        .order_by_asc(putaway_task::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch putaway tasks: {}", e)))
+}
+
+pub async fn get_task(
+    db: &DatabaseConnection,
+    task_id: i32,
+) -> Result<putaway_task::Model, AppError> {
+    putaway_task::Entity::find_by_id(task_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch putaway task: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Putaway task with id {} not found", task_id)))
+}
+
+/// Move an OPEN task's stock from the receiving location and mark the task COMPLETED
+///
+/// The stock goes to `location_code` if given, otherwise to the suggested location, which must
+/// have room for it within its capacity and, if it is the item's pick face, its max_quantity.
+/// The move is posted as a pair of PUTAWAY entries referencing the task, as a transfer would
+/// post it. A putaway is posted whole or not at all: if either location is frozen by an open
+/// cycle count it is rejected with LOCATION_FROZEN, whatever the warehouse's count_freeze_mode,
+/// rather than holding one leg and posting the other.
+pub async fn confirm_task(
+    db: &DatabaseConnection,
+    task_id: i32,
+    location_code: Option<String>,
+) -> Result<ConfirmedPutaway, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let task = putaway_task::Entity::find_by_id(task_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch putaway task: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Putaway task with id {} not found", task_id))
+        })?;
+
+    if task.status != "OPEN" {
+        return Err(AppError::bad_request(format!(
+            "Putaway task with id {} is not in OPEN status (current status: {})",
+            task_id, task.status
+        )));
+    }
+
+    let destination = match (location_code, task.suggested_location_id) {
+        (Some(code), _) => receipt::find_location_by_code(&txn, &code)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with code '{}' not found", code))
+            })?,
+        (None, Some(location_id)) => location::Entity::find_by_id(location_id)
+            .one(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+            .ok_or_else(|| {
+                AppError::not_found(format!("Location with id {} not found", location_id))
+            })?,
+        (None, None) => {
+            return Err(AppError::bad_request(format!(
+                "Putaway task with id {} has no suggested location; a location_code is required",
+                task_id
+            )));
+        }
+    };
+
+    if destination.warehouse_id != task.warehouse_id {
+        return Err(AppError::bad_request(format!(
+            "Location '{}' is not in warehouse {}",
+            destination.code, task.warehouse_id
+        )));
+    }
+    if destination.is_blocked {
+        return Err(AppError::bad_request(format!(
+            "Location '{}' is blocked",
+            destination.code
+        )));
+    }
+    if destination.id == task.from_location_id {
+        return Err(AppError::bad_request(
+            "Putaway destination must be different from the receiving location",
+        ));
+    }
+
+    inventory::lock_stock(
+        &txn,
+        &[
+            (task.item_id, task.from_location_id),
+            (task.item_id, destination.id),
+        ],
+    )
+    .await?;
+    // Putaways into the location wait on each other, as its capacity is shared by all items.
+    // Taken after the stock locks and before the chains, where location locks go
+    location::Entity::find_by_id(destination.id)
+        .lock(LockType::NoKeyUpdate)
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to lock location: {}", e)))?;
+    ledger::lock_chains(&txn, &[task.from_location_id, destination.id]).await?;
+
+    let open_counts =
+        cycle_count::find_open_cycle_counts(&txn, &[task.from_location_id, destination.id]).await?;
+    if let Some(open_count) = open_counts.first() {
+        let code = if open_count.location_id == destination.id {
+            destination.code.clone()
+        } else {
+            location::Entity::find_by_id(open_count.location_id)
+                .one(&txn)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to fetch location: {}", e)))?
+                .map(|location| location.code)
+                .unwrap_or_default()
+        };
+        return Err(AppError::location_frozen(format!(
+            "Location '{}' is frozen by open cycle count {}",
+            code, open_count.id
+        )));
+    }
+
+    let available =
+        inventory::calculate_available(&txn, task.item_id, task.from_location_id).await?;
+    if available < task.quantity {
+        return Err(AppError::bad_request(format!(
+            "Insufficient stock for putaway task {} (item_id: {}, location_id: {}). Available: {}, Requested: {}",
+            task.id, task.item_id, task.from_location_id, available, task.quantity
+        )));
+    }
+    putaway_rule::check_room(&txn, &destination, task.item_id, task.quantity).await?;
+
+    // Neither location is frozen, so both legs are inserted as they are
+    let source_entry = ledger::insert_entry(
+        &txn,
+        NewLedgerEntry {
+            item_id: task.item_id,
+            location_id: task.from_location_id,
+            quantity_change: -task.quantity,
+            reason_type: "PUTAWAY".to_string(),
+            reference_type: Some("putaway_task".to_string()),
+            reference_id: Some(task.id),
+        },
+    )
+    .await?;
+
+    let dest_entry = ledger::insert_entry(
+        &txn,
+        NewLedgerEntry {
+            item_id: task.item_id,
+            location_id: destination.id,
+            quantity_change: task.quantity,
+            reason_type: "PUTAWAY".to_string(),
+            reference_type: Some("putaway_task".to_string()),
+            reference_id: Some(task.id),
+        },
+    )
+    .await?;
+
+    let mut task_update: putaway_task::ActiveModel = task.into();
+    task_update.status = Set("COMPLETED".to_string());
+    task_update.to_location_id = Set(Some(destination.id));
+    task_update.completed_at = Set(Some(chrono::Utc::now().into()));
+
+    let updated_task = task_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update putaway task: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(ConfirmedPutaway {
+        task: updated_task,
+        ledger_entries: (source_entry, dest_entry),
+    })
+}
+
+/// Undo the putaway of a receipt that is being reversed
+///
+/// OPEN tasks are CANCELLED. COMPLETED tasks have their PUTAWAY entries reversed, bringing the
+/// stock back to the receiving location so the receipt itself can be reversed, and are marked
+/// REVERSED. Must be called on the reversing transaction. `stock_keys` is the (item_id,
+/// location_id) stock the caller moves afterwards: it is locked together with the tasks' stock,
+/// before any ledger chain, so the reversal takes its locks in the order every movement does.
+pub async fn reverse_tasks_for_receipt<C: ConnectionTrait>(
+    db: &C,
+    receipt_id: i32,
+    stock_keys: &[(i32, i32)],
+) -> Result<Vec<ledger_entity::Model>, AppError> {
+    let tasks = putaway_task::Entity::find()
+        .filter(putaway_task::Column::ReceiptId.eq(receipt_id))
+        .order_by_asc(putaway_task::Column::Id)
+        .lock_exclusive()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch putaway tasks: {}", e)))?;
+
+    let mut task_entries = Vec::new();
+    for task in tasks {
+        let entries = match task.status.as_str() {
+            "OPEN" => Vec::new(),
+            "COMPLETED" => {
+                ledger::find_posted_entries(db, "PUTAWAY", "putaway_task", task.id).await?
+            }
+            _ => continue,
+        };
+        task_entries.push((task, entries));
+    }
+
+    let mut keys = stock_keys.to_vec();
+    keys.extend(
+        task_entries
+            .iter()
+            .flat_map(|(_, entries)| entries.iter())
+            .map(|entry| (entry.item_id, entry.location_id)),
+    );
+    inventory::lock_stock(db, &keys).await?;
+    let location_ids: Vec<i32> = keys.iter().map(|(_, location_id)| *location_id).collect();
+    ledger::lock_chains(db, &location_ids).await?;
+
+    let mut reversals = Vec::new();
+    for (task, entries) in task_entries {
+        let status = if task.status == "OPEN" {
+            "CANCELLED"
+        } else {
+            reversals.extend(ledger::reverse_entries(db, &entries).await?);
+            "REVERSED"
+        };
+
+        let mut task_update: putaway_task::ActiveModel = task.into();
+        task_update.status = Set(status.to_string());
+        task_update
+            .update(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to update putaway task: {}", e)))?;
+    }
+
+    Ok(reversals)
+}
+
+/// Confirmed putaway task with the pair of entries that moved its stock
+#[derive(Debug)]
+pub struct ConfirmedPutaway {
+    pub task: putaway_task::Model,
+    pub ledger_entries: (ledger_entity::Model, ledger_entity::Model), // (receiving location, destination)
+}
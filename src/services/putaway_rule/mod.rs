@@ -0,0 +1,581 @@
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::sea_query::Query;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, TransactionTrait,
+};
+
+use crate::db::DatabaseConnection;
+use crate::entities::inventory_balance;
+use crate::entities::location;
+use crate::entities::pick_face;
+use crate::entities::putaway_rule;
+use crate::entities::putaway_task;
+use crate::entities::warehouse;
+use crate::services::receipt;
+use crate::utils::error::AppError;
+
+/// Rules a warehouse's putaway rules can be made of
+///
+/// - PICK_FACE - the item's home pick face, while it has room up to its max_quantity
+/// - CONSOLIDATE - locations already holding the item, the most first, other than pick
+///   locations, which only their own item's PICK_FACE rule fills
+/// - BULK - bulk locations, in code order
+pub const RULE_TYPES: [&str; 3] = ["PICK_FACE", "CONSOLIDATE", "BULK"];
+
+/// Rules of a warehouse that has not set its own
+pub const DEFAULT_RULES: [&str; 3] = ["PICK_FACE", "CONSOLIDATE", "BULK"];
+
+/// A warehouse's putaway rules in the order they are applied
+#[derive(Debug)]
+pub struct WarehouseRules {
+    pub warehouse_id: i32,
+    pub rules: Vec<String>,
+    pub is_default: bool, // the warehouse has not set its own
+}
+
+pub async fn get_rules(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+) -> Result<WarehouseRules, AppError> {
+    find_warehouse(db, warehouse_id).await?;
+    find_rules(db, warehouse_id).await
+}
+
+/// Replace a warehouse's rules; an empty list goes back to the default rules. Concurrent
+/// replacements for the same warehouse wait on its row, so the last one wins whole.
+pub async fn set_rules(
+    db: &DatabaseConnection,
+    warehouse_id: i32,
+    rules: Vec<String>,
+) -> Result<WarehouseRules, AppError> {
+    for (i, rule) in rules.iter().enumerate() {
+        if !RULE_TYPES.contains(&rule.as_str()) {
+            return Err(AppError::bad_request(format!(
+                "Unknown putaway rule '{}'. Expected one of: {}",
+                rule,
+                RULE_TYPES.join(", ")
+            )));
+        }
+        if rules[..i].contains(rule) {
+            return Err(AppError::bad_request(format!(
+                "Putaway rule '{}' is listed more than once",
+                rule
+            )));
+        }
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    warehouse::Entity::find_by_id(warehouse_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to lock warehouse: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Warehouse with id {} not found", warehouse_id))
+        })?;
+
+    putaway_rule::Entity::delete_many()
+        .filter(putaway_rule::Column::WarehouseId.eq(warehouse_id))
+        .exec(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to replace putaway rules: {}", e)))?;
+
+    for (sequence, rule) in rules.into_iter().enumerate() {
+        putaway_rule::ActiveModel {
+            warehouse_id: Set(warehouse_id),
+            sequence: Set(sequence as i32 + 1),
+            rule_type: Set(rule),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create putaway rule: {}", e)))?;
+    }
+
+    let rules = find_rules(&txn, warehouse_id).await?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(rules)
+}
+
+async fn find_rules<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+) -> Result<WarehouseRules, AppError> {
+    let rules: Vec<String> = putaway_rule::Entity::find()
+        .select_only()
+        .column(putaway_rule::Column::RuleType)
+        .filter(putaway_rule::Column::WarehouseId.eq(warehouse_id))
+        .order_by_asc(putaway_rule::Column::Sequence)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch putaway rules: {}", e)))?;
+
+    if rules.is_empty() {
+        return Ok(WarehouseRules {
+            warehouse_id,
+            rules: DEFAULT_RULES.iter().map(|rule| rule.to_string()).collect(),
+            is_default: true,
+        });
+    }
+
+    Ok(WarehouseRules {
+        warehouse_id,
+        rules,
+        is_default: false,
+    })
+}
+
+async fn find_warehouse<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+) -> Result<warehouse::Model, AppError> {
+    warehouse::Entity::find_by_id(warehouse_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Warehouse with id {} not found", warehouse_id)))
+}
+
+/// Home pick faces, optionally of one warehouse or item
+pub async fn list_pick_faces(
+    db: &DatabaseConnection,
+    warehouse_id: Option<i32>,
+    item_id: Option<i32>,
+) -> Result<Vec<pick_face::Model>, AppError> {
+    let mut query = pick_face::Entity::find();
+
+    if let Some(warehouse_id) = warehouse_id {
+        query = query.filter(pick_face::Column::WarehouseId.eq(warehouse_id));
+    }
+    if let Some(item_id) = item_id {
+        query = query.filter(pick_face::Column::ItemId.eq(item_id));
+    }
+
+    query
+        .order_by_asc(pick_face::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick faces: {}", e)))
+}
+
+/// Make a location the item's home pick face in the location's warehouse
+pub async fn create_pick_face(
+    db: &DatabaseConnection,
+    sku: &str,
+    location_code: &str,
+    max_quantity: i32,
+) -> Result<pick_face::Model, AppError> {
+    let item = receipt::find_item_by_sku(db, sku)
+        .await?
+        .ok_or_else(|| AppError::not_found(format!("Item with SKU '{}' not found", sku)))?;
+    let location = find_pick_face_location(db, location_code).await?;
+
+    let existing = pick_face::Entity::find()
+        .filter(pick_face::Column::ItemId.eq(item.id))
+        .filter(pick_face::Column::WarehouseId.eq(location.warehouse_id))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick face: {}", e)))?;
+    if existing.is_some() {
+        return Err(AppError::bad_request(format!(
+            "Item '{}' already has a pick face in warehouse {}",
+            sku, location.warehouse_id
+        )));
+    }
+
+    pick_face::ActiveModel {
+        item_id: Set(item.id),
+        warehouse_id: Set(location.warehouse_id),
+        location_id: Set(location.id),
+        max_quantity: Set(max_quantity),
+        ..Default::default()
+    }
+    .insert(db)
+    .await
+    .map_err(|e| AppError::internal(format!("Failed to create pick face: {}", e)))
+}
+
+/// Move a pick face to another location of its warehouse, or change its max_quantity
+pub async fn update_pick_face(
+    db: &DatabaseConnection,
+    pick_face_id: i32,
+    location_code: Option<String>,
+    max_quantity: Option<i32>,
+) -> Result<pick_face::Model, AppError> {
+    let face = find_pick_face(db, pick_face_id).await?;
+    let warehouse_id = face.warehouse_id;
+
+    let mut update: pick_face::ActiveModel = face.into();
+    if let Some(location_code) = location_code {
+        let location = find_pick_face_location(db, &location_code).await?;
+        if location.warehouse_id != warehouse_id {
+            return Err(AppError::bad_request(format!(
+                "Location '{}' is not in warehouse {}",
+                location.code, warehouse_id
+            )));
+        }
+        update.location_id = Set(location.id);
+    }
+    if let Some(max_quantity) = max_quantity {
+        update.max_quantity = Set(max_quantity);
+    }
+
+    update
+        .update(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update pick face: {}", e)))
+}
+
+pub async fn delete_pick_face(db: &DatabaseConnection, pick_face_id: i32) -> Result<(), AppError> {
+    let face = find_pick_face(db, pick_face_id).await?;
+
+    face.delete(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to delete pick face: {}", e)))?;
+
+    Ok(())
+}
+
+async fn find_pick_face(
+    db: &DatabaseConnection,
+    pick_face_id: i32,
+) -> Result<pick_face::Model, AppError> {
+    pick_face::Entity::find_by_id(pick_face_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick face: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Pick face with id {} not found", pick_face_id)))
+}
+
+/// Whether stock is picked from the location rather than stored in it: pickable and not bulk
+fn is_pick_location(location: &location::Model) -> bool {
+    location.is_pickable && !location.is_bulk
+}
+
+/// The location with the code, which must be a pick location and not its warehouse's receiving
+/// location
+async fn find_pick_face_location(
+    db: &DatabaseConnection,
+    location_code: &str,
+) -> Result<location::Model, AppError> {
+    let location = receipt::find_location_by_code(db, location_code)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Location with code '{}' not found", location_code))
+        })?;
+
+    if !is_pick_location(&location) {
+        return Err(AppError::bad_request(format!(
+            "Location '{}' is not a pick location; a pick face must be pickable and not bulk",
+            location.code
+        )));
+    }
+
+    let warehouse = find_warehouse(db, location.warehouse_id).await?;
+    if warehouse.receiving_location_id == Some(location.id) {
+        return Err(AppError::bad_request(format!(
+            "Location '{}' is the receiving location of warehouse {}",
+            location.code, location.warehouse_id
+        )));
+    }
+
+    Ok(location)
+}
+
+/// Refuse putting `quantity` of an item into a location beyond its capacity, or beyond the
+/// max_quantity of the item's pick face if the location is one
+pub async fn check_room<C: ConnectionTrait>(
+    db: &C,
+    location: &location::Model,
+    item_id: i32,
+    quantity: i32,
+) -> Result<(), AppError> {
+    let stock: Vec<(i32, i32)> = inventory_balance::Entity::find()
+        .select_only()
+        .column(inventory_balance::Column::ItemId)
+        .column(inventory_balance::Column::OnHand)
+        .filter(inventory_balance::Column::LocationId.eq(location.id))
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location stock: {}", e)))?;
+
+    if let Some(capacity) = location.capacity {
+        let on_hand: i64 = stock.iter().map(|(_, on_hand)| *on_hand as i64).sum();
+        if on_hand + quantity as i64 > capacity as i64 {
+            return Err(AppError::bad_request(format!(
+                "Location '{}' has room for {} more (capacity {}), {} requested",
+                location.code,
+                (capacity as i64 - on_hand).max(0),
+                capacity,
+                quantity
+            )));
+        }
+    }
+
+    let face = pick_face::Entity::find()
+        .filter(pick_face::Column::ItemId.eq(item_id))
+        .filter(pick_face::Column::LocationId.eq(location.id))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick face: {}", e)))?;
+    if let Some(face) = face {
+        let item_on_hand: i64 = stock
+            .iter()
+            .filter(|(stock_item_id, _)| *stock_item_id == item_id)
+            .map(|(_, on_hand)| *on_hand as i64)
+            .sum();
+        if item_on_hand + quantity as i64 > face.max_quantity as i64 {
+            return Err(AppError::bad_request(format!(
+                "Location '{}' is the item's pick face and has room for {} more (max_quantity {}), {} requested",
+                location.code,
+                (face.max_quantity as i64 - item_on_hand).max(0),
+                face.max_quantity,
+                quantity
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// A location putaway could send stock to, and why
+#[derive(Debug)]
+pub struct PutawayCandidate {
+    pub location: location::Model,
+    pub rule: String,
+    pub reason: String,
+    pub quantity: i32,     // how much of the stock the location has room for
+    pub on_hand: i32,      // of all items, before the putaway
+    pub item_on_hand: i32, // of the item being put away
+    pub incoming: i32,     // of all items, on OPEN putaway tasks suggesting the location
+}
+
+/// Rank the locations of a warehouse to put `quantity` of an item away to
+///
+/// The warehouse's rules are applied in order, each proposing locations; a location proposed by
+/// more than one rule keeps the rank and reason of the first. Locations that cannot take any of
+/// the stock are skipped whichever rule proposed them: the receiving location, blocked
+/// locations, full locations, and the item's pick face once full or if its location is no longer
+/// a pick location. A location's room is what its capacity, or a pick face's max_quantity, leaves
+/// after its stock and what OPEN putaway tasks are already bringing it; each candidate offers
+/// the stock up to that room. At most `limit` candidates are returned, best first.
+pub async fn suggest_locations<C: ConnectionTrait>(
+    db: &C,
+    warehouse_id: i32,
+    item_id: i32,
+    quantity: i32,
+    limit: usize,
+) -> Result<Vec<PutawayCandidate>, AppError> {
+    let warehouse = find_warehouse(db, warehouse_id).await?;
+    let rules = find_rules(db, warehouse_id).await?.rules;
+
+    let pick_face = pick_face::Entity::find()
+        .filter(pick_face::Column::ItemId.eq(item_id))
+        .filter(pick_face::Column::WarehouseId.eq(warehouse_id))
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch pick face: {}", e)))?;
+
+    let item_stock: Vec<(i32, i32)> = inventory_balance::Entity::find()
+        .select_only()
+        .column(inventory_balance::Column::LocationId)
+        .column(inventory_balance::Column::OnHand)
+        .filter(inventory_balance::Column::ItemId.eq(item_id))
+        .filter(inventory_balance::Column::OnHand.gt(0))
+        .filter(
+            inventory_balance::Column::LocationId.in_subquery(
+                Query::select()
+                    .column(location::Column::Id)
+                    .from(location::Entity)
+                    .and_where(location::Column::WarehouseId.eq(warehouse_id))
+                    .to_owned(),
+            ),
+        )
+        .order_by_desc(inventory_balance::Column::OnHand)
+        .order_by_asc(inventory_balance::Column::LocationId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch item stock: {}", e)))?;
+
+    let mut proposals: Vec<(i32, &str)> = Vec::new(); // (location, rule)
+    for rule in &rules {
+        match rule.as_str() {
+            "PICK_FACE" => {
+                if let Some(face) = &pick_face {
+                    proposals.push((face.location_id, rule));
+                }
+            }
+            "CONSOLIDATE" => {
+                proposals.extend(
+                    item_stock
+                        .iter()
+                        .map(|(location_id, _)| (*location_id, rule.as_str())),
+                );
+            }
+            "BULK" => {
+                let bulk: Vec<i32> = location::Entity::find()
+                    .select_only()
+                    .column(location::Column::Id)
+                    .filter(location::Column::WarehouseId.eq(warehouse_id))
+                    .filter(location::Column::IsBulk.eq(true))
+                    .filter(location::Column::IsBlocked.eq(false))
+                    .order_by_asc(location::Column::Code)
+                    .into_tuple()
+                    .all(db)
+                    .await
+                    .map_err(|e| {
+                        AppError::internal(format!("Failed to fetch bulk locations: {}", e))
+                    })?;
+                proposals.extend(
+                    bulk.into_iter()
+                        .map(|location_id| (location_id, rule.as_str())),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let mut location_ids: Vec<i32> = proposals
+        .iter()
+        .map(|(location_id, _)| *location_id)
+        .collect();
+    location_ids.sort_unstable();
+    location_ids.dedup();
+    if location_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut locations: HashMap<i32, location::Model> = location::Entity::find()
+        .filter(location::Column::Id.is_in(location_ids.clone()))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch locations: {}", e)))?
+        .into_iter()
+        .map(|location| (location.id, location))
+        .collect();
+
+    let on_hand: HashMap<i32, i64> = inventory_balance::Entity::find()
+        .select_only()
+        .column(inventory_balance::Column::LocationId)
+        .column_as(inventory_balance::Column::OnHand.sum(), "on_hand")
+        .filter(inventory_balance::Column::LocationId.is_in(location_ids.clone()))
+        .group_by(inventory_balance::Column::LocationId)
+        .into_tuple::<(i32, i64)>()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch location stock: {}", e)))?
+        .into_iter()
+        .collect();
+    let item_on_hand: HashMap<i32, i32> = item_stock.into_iter().collect();
+
+    // Stock already on its way, so that tasks suggested one after another share out the room
+    let mut incoming: HashMap<i32, i64> = HashMap::new();
+    let mut item_incoming: HashMap<i32, i64> = HashMap::new();
+    let open_tasks: Vec<(Option<i32>, i32, i64)> = putaway_task::Entity::find()
+        .select_only()
+        .column(putaway_task::Column::SuggestedLocationId)
+        .column(putaway_task::Column::ItemId)
+        .column_as(putaway_task::Column::Quantity.sum(), "quantity")
+        .filter(putaway_task::Column::Status.eq("OPEN"))
+        .filter(putaway_task::Column::SuggestedLocationId.is_in(location_ids))
+        .group_by(putaway_task::Column::SuggestedLocationId)
+        .group_by(putaway_task::Column::ItemId)
+        .into_tuple()
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch open putaway tasks: {}", e)))?;
+    for (location_id, task_item_id, quantity) in open_tasks {
+        let Some(location_id) = location_id else {
+            continue;
+        };
+        *incoming.entry(location_id).or_default() += quantity;
+        if task_item_id == item_id {
+            *item_incoming.entry(location_id).or_default() += quantity;
+        }
+    }
+
+    // Pick locations are only filled by their item's PICK_FACE rule, and only while they are
+    // still pick locations
+    proposals.retain(|(location_id, rule)| {
+        locations
+            .get(location_id)
+            .is_some_and(|location| is_pick_location(location) == (*rule == "PICK_FACE"))
+    });
+
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    for (location_id, rule) in proposals {
+        if candidates.len() >= limit {
+            break;
+        }
+        if !seen.insert(location_id) || warehouse.receiving_location_id == Some(location_id) {
+            continue;
+        }
+        let Some(location) = locations.remove(&location_id) else {
+            continue;
+        };
+        if location.is_blocked {
+            continue;
+        }
+
+        let on_hand = on_hand.get(&location_id).copied().unwrap_or(0);
+        let incoming = incoming.get(&location_id).copied().unwrap_or(0);
+        let item_on_hand = item_on_hand.get(&location_id).copied().unwrap_or(0);
+        let face = pick_face
+            .as_ref()
+            .filter(|face| face.location_id == location_id);
+
+        let mut room = quantity as i64;
+        if let Some(capacity) = location.capacity {
+            room = room.min(capacity as i64 - on_hand - incoming);
+        }
+        if let Some(face) = face {
+            let item_incoming = item_incoming.get(&location_id).copied().unwrap_or(0);
+            room = room.min(face.max_quantity as i64 - item_on_hand as i64 - item_incoming);
+        }
+        if room <= 0 {
+            continue;
+        }
+
+        let reason = match rule {
+            "PICK_FACE" => format!(
+                "Home pick face of the item, {} of max {} on hand",
+                item_on_hand,
+                face.map_or(0, |face| face.max_quantity)
+            ),
+            "CONSOLIDATE" => format!("Already holds {} of the item", item_on_hand),
+            _ => match location.capacity {
+                Some(capacity) => format!(
+                    "Bulk overflow, room for {} more",
+                    capacity as i64 - on_hand - incoming
+                ),
+                None => "Bulk overflow".to_string(),
+            },
+        };
+
+        candidates.push(PutawayCandidate {
+            location,
+            rule: rule.to_string(),
+            reason,
+            quantity: room as i32,
+            on_hand: on_hand as i32,
+            item_on_hand,
+            incoming: incoming as i32,
+        });
+    }
+
+    Ok(candidates)
+}
@@ -1,12 +1,28 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::OnConflict;
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, Set, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseTransaction, DbBackend, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, Statement, TransactionTrait,
 };
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::db::DatabaseConnection;
+use crate::entities::held_movement;
 use crate::entities::item;
-use crate::entities::ledger;
+use crate::entities::ledger as ledger_entity;
 use crate::entities::location;
+use crate::entities::putaway_task;
+use crate::entities::receipt;
+use crate::entities::receipt_line;
+use crate::entities::warehouse;
+use crate::services::cycle_count;
+use crate::services::ledger::{self, NewLedgerEntry, PostedEntry};
+use crate::services::purchase_order;
+use crate::services::putaway;
 use crate::utils::error::AppError;
 
 pub async fn find_item_by_sku<C: ConnectionTrait>(
@@ -47,19 +63,7 @@ pub async fn find_or_create_item_by_sku<C: ConnectionTrait>(
         return Ok(item);
     }
 
-    let name = name.unwrap_or_else(|| format!("Item {}", sku));
-    let unit_of_measure = unit_of_measure.unwrap_or_else(|| "EA".to_string());
-    let barcode = barcode.unwrap_or_else(|| sku.to_string());
-    let is_serialized = is_serialized.unwrap_or(false);
-
-    let mut active_model = <item::ActiveModel as sea_orm::ActiveModelTrait>::default();
-    active_model.sku = Set(sku.to_string());
-    active_model.name = Set(name);
-    active_model.unit_of_measure = Set(unit_of_measure);
-    active_model.barcode = Set(Some(barcode));
-    active_model.is_serialized = Set(is_serialized);
-
-    let item = active_model
+    let item = new_item_model(sku, name, unit_of_measure, barcode, is_serialized)
         .insert(db)
         .await
         .map_err(|e| AppError::internal(format!("Failed to create item: {}", e)))?;
@@ -69,6 +73,7 @@ pub async fn find_or_create_item_by_sku<C: ConnectionTrait>(
 
 /// Find or create a location by code. Used for CSV bulk import.
 /// If location doesn't exist, creates it with provided or default values.
+#[allow(clippy::too_many_arguments)]
 pub async fn find_or_create_location_by_code<C: ConnectionTrait>(
     db: &C,
     code: &str,
@@ -90,6 +95,46 @@ pub async fn find_or_create_location_by_code<C: ConnectionTrait>(
         ))
     })?;
 
+    let location = new_location_model(code, warehouse_id, aisle, bin, shelf, is_pickable, is_bulk)
+        .insert(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create location: {}", e)))?;
+
+    Ok(location)
+}
+
+/// A new item with the defaults used when a SKU is first seen on a receipt
+fn new_item_model(
+    sku: &str,
+    name: Option<String>,
+    unit_of_measure: Option<String>,
+    barcode: Option<String>,
+    is_serialized: Option<bool>,
+) -> item::ActiveModel {
+    let name = name.unwrap_or_else(|| format!("Item {}", sku));
+    let unit_of_measure = unit_of_measure.unwrap_or_else(|| "EA".to_string());
+    let barcode = barcode.unwrap_or_else(|| sku.to_string());
+    let is_serialized = is_serialized.unwrap_or(false);
+
+    let mut active_model = <item::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.sku = Set(sku.to_string());
+    active_model.name = Set(name);
+    active_model.unit_of_measure = Set(unit_of_measure);
+    active_model.barcode = Set(Some(barcode));
+    active_model.is_serialized = Set(is_serialized);
+    active_model
+}
+
+/// A new location with the defaults used when a location code is first seen on a receipt
+fn new_location_model(
+    code: &str,
+    warehouse_id: i32,
+    aisle: Option<String>,
+    bin: Option<String>,
+    shelf: Option<String>,
+    is_pickable: Option<bool>,
+    is_bulk: Option<bool>,
+) -> location::ActiveModel {
     let aisle = aisle.unwrap_or_else(|| "A".to_string());
     let bin = bin.unwrap_or_else(|| "1".to_string());
     let shelf = shelf.unwrap_or_else(|| "1".to_string());
@@ -104,168 +149,1062 @@ pub async fn find_or_create_location_by_code<C: ConnectionTrait>(
     active_model.shelf = Set(shelf);
     active_model.is_pickable = Set(is_pickable);
     active_model.is_bulk = Set(is_bulk);
+    active_model
+}
 
-    let location = active_model
-        .insert(db)
+/// Items with any of the SKUs, by SKU; the oldest wins if a SKU is on several items
+async fn find_items_by_sku<C: ConnectionTrait>(
+    db: &C,
+    skus: &[&str],
+) -> Result<HashMap<String, item::Model>, AppError> {
+    let items = item::Entity::find()
+        .filter(item::Column::Sku.is_in(skus.iter().copied()))
+        .order_by_desc(item::Column::Id)
+        .all(db)
         .await
-        .map_err(|e| AppError::internal(format!("Failed to create location: {}", e)))?;
+        .map_err(|e| AppError::internal(format!("Failed to find items by SKU: {}", e)))?;
 
-    Ok(location)
+    Ok(items
+        .into_iter()
+        .map(|item| (item.sku.clone(), item))
+        .collect())
+}
+
+/// Locations with any of the codes, by code; the oldest wins if a code is on several locations
+async fn find_locations_by_code<C: ConnectionTrait>(
+    db: &C,
+    codes: &[&str],
+) -> Result<HashMap<String, location::Model>, AppError> {
+    let locations = location::Entity::find()
+        .filter(location::Column::Code.is_in(codes.iter().copied()))
+        .order_by_desc(location::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to find locations by code: {}", e)))?;
+
+    Ok(locations
+        .into_iter()
+        .map(|location| (location.code.clone(), location))
+        .collect())
+}
+
+/// Header fields of a receipt document; all are optional
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptHeader {
+    pub receipt_number: Option<String>, // a new UUID when not given
+    pub supplier: Option<String>,
+    pub supplier_id: Option<i32>,
+    pub purchase_order_id: Option<i32>, // receiving against a purchase order
+    pub reference_number: Option<String>,
+    pub received_by: Option<String>,
+}
+
+/// Find the receipt with the header's receipt_number, or create it, and lock it
+///
+/// Posting again under an existing receipt_number adds lines to that receipt; the other header
+/// fields are only used when the receipt is created. The lock keeps lines from being added while
+/// the receipt is being reversed.
+pub async fn find_or_create_receipt<C: ConnectionTrait>(
+    db: &C,
+    header: &ReceiptHeader,
+) -> Result<receipt::Model, AppError> {
+    let receipt_number = header
+        .receipt_number
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let mut active_model = <receipt::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.receipt_number = Set(receipt_number.clone());
+    active_model.supplier = Set(header.supplier.clone());
+    active_model.supplier_id = Set(header.supplier_id);
+    active_model.purchase_order_id = Set(header.purchase_order_id);
+    active_model.reference_number = Set(header.reference_number.clone());
+    active_model.received_by = Set(header.received_by.clone());
+    active_model.status = Set("POSTED".to_string());
+
+    // Concurrent posts under a new receipt_number both end up on the one row
+    receipt::Entity::insert(active_model)
+        .on_conflict(
+            OnConflict::column(receipt::Column::ReceiptNumber)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create receipt: {}", e)))?;
+
+    let receipt = receipt::Entity::find()
+        .filter(receipt::Column::ReceiptNumber.eq(receipt_number.as_str()))
+        .lock_exclusive()
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt: {}", e)))?
+        .ok_or_else(|| {
+            AppError::internal(format!("Receipt '{}' was not created", receipt_number))
+        })?;
+
+    check_receipt_accepts_lines(&receipt, header)?;
+
+    Ok(receipt)
+}
+
+/// Lines can only be added to a POSTED receipt, and only from the purchase order it belongs to
+fn check_receipt_accepts_lines(
+    receipt: &receipt::Model,
+    header: &ReceiptHeader,
+) -> Result<(), AppError> {
+    if receipt.status != "POSTED" {
+        return Err(AppError::bad_request(format!(
+            "Receipt '{}' is not in POSTED status (current status: {})",
+            receipt.receipt_number, receipt.status
+        )));
+    }
+    if receipt.purchase_order_id != header.purchase_order_id {
+        return Err(AppError::bad_request(format!(
+            "Receipt '{}' belongs to a different purchase order",
+            receipt.receipt_number
+        )));
+    }
+
+    Ok(())
 }
 
+/// A receipt line and the ledger movement it posted
+#[derive(Debug)]
+pub struct ReceivedLine {
+    pub line: receipt_line::Model,
+    pub posted: PostedEntry,
+}
+
+/// Post a RECEIPT movement referencing the receipt and record it as a receipt line
 pub async fn create_receipt_ledger_entry<C: ConnectionTrait>(
     db: &C,
+    receipt: &receipt::Model,
     item_id: i32,
     location_id: i32,
     quantity: i32,
-    receipt_id: &str,
-) -> Result<i32, AppError> {
+    po_line_id: Option<i32>,
+) -> Result<ReceivedLine, AppError> {
     if quantity <= 0 {
         return Err(AppError::bad_request(
             "Quantity must be positive for receipts",
         ));
     }
 
-    let current_on_hand =
-        crate::services::inventory::calculate_on_hand(db, item_id, location_id).await?;
-
-    let balance_after = current_on_hand + quantity;
-
-    // Convert receipt_id (UUID string) to i32 for reference_id
-    // We'll use a hash-based approach to convert UUID to i32
-    // This is a simple approach - in production you might want a separate receipt table
-    let receipt_id_hash = receipt_id
-        .as_bytes()
-        .iter()
-        .fold(0i32, |acc, &b| acc.wrapping_mul(31).wrapping_add(b as i32));
+    let posted = ledger::post_entry(
+        db,
+        NewLedgerEntry {
+            item_id,
+            location_id,
+            quantity_change: quantity,
+            reason_type: "RECEIPT".to_string(),
+            reference_type: Some("receipt".to_string()),
+            reference_id: Some(receipt.id),
+        },
+    )
+    .await?;
 
-    let mut active_model = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    let mut active_model = <receipt_line::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    active_model.receipt_id = Set(receipt.id);
     active_model.item_id = Set(item_id);
     active_model.location_id = Set(location_id);
-    active_model.quantity_change = Set(quantity);
-    active_model.balance_after = Set(Some(balance_after));
-    active_model.reason_type = Set("RECEIPT".to_string());
-    active_model.reference_type = Set(Some("receipt".to_string()));
-    active_model.reference_id = Set(Some(receipt_id_hash));
+    active_model.quantity = Set(quantity);
+    active_model.ledger_entry_id = Set(posted.ledger_entry_id());
+    active_model.held_movement_id = Set(posted.held_movement_id());
+    active_model.po_line_id = Set(po_line_id);
 
-    let ledger_entry = active_model
+    let line = active_model
         .insert(db)
         .await
-        .map_err(|e| AppError::internal(format!("Failed to create ledger entry: {}", e)))?;
+        .map_err(|e| AppError::internal(format!("Failed to create receipt line: {}", e)))?;
 
-    Ok(ledger_entry.id)
+    Ok(ReceivedLine { line, posted })
 }
 
-pub async fn process_bulk_receipt(
+/// Receive one item into a location under a new or existing receipt
+///
+/// Received into its warehouse's receiving location, the line also gets putaway tasks.
+pub async fn create_receipt(
     db: &DatabaseConnection,
-    rows: Vec<BulkReceiptRow>,
-) -> Result<BulkReceiptResult, AppError> {
-    let receipt_id = Uuid::new_v4().to_string();
-    let total_rows = rows.len();
-    let mut successful_rows = 0;
-    let mut errors = Vec::new();
-
+    header: &ReceiptHeader,
+    item_id: i32,
+    location_id: i32,
+    quantity: i32,
+) -> Result<(receipt::Model, ReceivedLine, Vec<putaway_task::Model>), AppError> {
     let txn = db
         .begin()
         .await
         .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
 
-    for (index, row) in rows.iter().enumerate() {
-        let row_number = index + 1;
+    let receipt = find_or_create_receipt(&txn, header).await?;
+    let received =
+        create_receipt_ledger_entry(&txn, &receipt, item_id, location_id, quantity, None).await?;
+    let tasks = putaway::create_tasks(&txn, std::slice::from_ref(&received.line)).await?;
 
-        if row.quantity <= 0 {
-            errors.push(BulkReceiptError {
-                row: row_number,
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok((receipt, received, tasks))
+}
+
+/// Filters for listing receipts; all are optional
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptFilter {
+    pub status: Option<String>,
+    pub supplier: Option<String>,
+    pub from: Option<DateTimeWithTimeZone>, // received_at, inclusive
+    pub to: Option<DateTimeWithTimeZone>,   // received_at, exclusive
+}
+
+/// A page of receipts in id order
+#[derive(Debug)]
+pub struct ReceiptPage {
+    pub receipts: Vec<receipt::Model>,
+    pub next_cursor: Option<i32>, // pass as `after` to fetch the next page
+}
+
+/// List receipt headers with id greater than `after`, oldest first
+pub async fn list_receipts<C: ConnectionTrait>(
+    db: &C,
+    filter: &ReceiptFilter,
+    after: Option<i32>,
+    limit: u64,
+) -> Result<ReceiptPage, AppError> {
+    let mut query = receipt::Entity::find();
+
+    if let Some(after) = after {
+        query = query.filter(receipt::Column::Id.gt(after));
+    }
+    if let Some(status) = &filter.status {
+        query = query.filter(receipt::Column::Status.eq(status.clone()));
+    }
+    if let Some(supplier) = &filter.supplier {
+        query = query.filter(receipt::Column::Supplier.eq(supplier.clone()));
+    }
+    if let Some(from) = filter.from {
+        query = query.filter(receipt::Column::ReceivedAt.gte(from));
+    }
+    if let Some(to) = filter.to {
+        query = query.filter(receipt::Column::ReceivedAt.lt(to));
+    }
+
+    // Fetch one extra row to know whether there is another page
+    let mut receipts = query
+        .order_by_asc(receipt::Column::Id)
+        .limit(limit + 1)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipts: {}", e)))?;
+
+    let next_cursor = if receipts.len() as u64 > limit {
+        receipts.truncate(limit as usize);
+        receipts.last().map(|receipt| receipt.id)
+    } else {
+        None
+    };
+
+    Ok(ReceiptPage {
+        receipts,
+        next_cursor,
+    })
+}
+
+/// Receipt with its lines and the held movements of lines posted while a location was frozen
+#[derive(Debug)]
+pub struct ReceiptWithLines {
+    pub receipt: receipt::Model,
+    pub lines: Vec<receipt_line::Model>,
+    pub held_movements: Vec<held_movement::Model>,
+}
+
+impl ReceiptWithLines {
+    /// The ledger entry a line posted, including held movements that have since been applied
+    pub fn ledger_entry_id(&self, line: &receipt_line::Model) -> Option<i32> {
+        line.ledger_entry_id.or_else(|| {
+            self.held_movement(line)
+                .and_then(|held| held.ledger_entry_id)
+        })
+    }
+
+    pub fn held_movement(&self, line: &receipt_line::Model) -> Option<&held_movement::Model> {
+        line.held_movement_id
+            .and_then(|id| self.held_movements.iter().find(|held| held.id == id))
+    }
+}
+
+async fn find_receipt_lines<C: ConnectionTrait>(
+    db: &C,
+    receipt: receipt::Model,
+) -> Result<ReceiptWithLines, AppError> {
+    let lines = receipt_line::Entity::find()
+        .filter(receipt_line::Column::ReceiptId.eq(receipt.id))
+        .order_by_asc(receipt_line::Column::Id)
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt lines: {}", e)))?;
+
+    let held_ids: Vec<i32> = lines
+        .iter()
+        .filter_map(|line| line.held_movement_id)
+        .collect();
+    let held_movements = if held_ids.is_empty() {
+        Vec::new()
+    } else {
+        held_movement::Entity::find()
+            .filter(held_movement::Column::Id.is_in(held_ids))
+            .all(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch held movements: {}", e)))?
+    };
+
+    Ok(ReceiptWithLines {
+        receipt,
+        lines,
+        held_movements,
+    })
+}
+
+pub async fn get_receipt(
+    db: &DatabaseConnection,
+    receipt_id: i32,
+) -> Result<ReceiptWithLines, AppError> {
+    let receipt = receipt::Entity::find_by_id(receipt_id)
+        .one(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Receipt with id {} not found", receipt_id)))?;
+
+    find_receipt_lines(db, receipt).await
+}
+
+/// Rows a bulk receipt resolves and posts together
+pub const BULK_RECEIPT_BATCH_SIZE: usize = 1000;
+
+/// A bulk receipt being imported a batch of rows at a time
+///
+/// The whole import is one transaction, committed by finish. Each batch resolves its SKUs and
+/// location codes with a query each, creates the missing items and locations together and stages
+/// its rows as receipt lines with a multi-row insert. If any of that fails the batch is undone and
+/// staged again row by row, each row in its own savepoint, so the error lands on the row that
+/// caused it. Rows at a location frozen by an open cycle count always go row by row through
+/// ledger::post_entry. Checking for counts takes the locations FOR SHARE, so no count opens on
+/// them before the import commits and the staged lines can all be posted.
+///
+/// The staged lines' RECEIPT movements are only posted when the import commits, so the ledger
+/// chains of the warehouses involved are locked for as long as posting takes, not for as long as
+/// staging takes. Callers hand in rows they already have, such as the rows an import job stored
+/// while reading its file, so the transaction does not wait on a file being read.
+///
+/// By default a failing row rolls back the whole import. In partial mode the rows that went
+/// through are committed; the receipt is only kept if at least one did. Rows posted into their
+/// warehouse's receiving location get putaway tasks when the import commits.
+pub struct BulkReceiptImport {
+    txn: DatabaseTransaction,
+    receipt: receipt::Model,
+    last_line_id: i32, // the receipt's last line before this import
+    partial: bool,
+    total_rows: usize,
+    successful_rows: usize,
+    errors: Vec<BulkReceiptError>,
+}
+
+impl BulkReceiptImport {
+    pub async fn begin(
+        db: &DatabaseConnection,
+        header: &ReceiptHeader,
+        partial: bool,
+    ) -> Result<Self, AppError> {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+        let receipt = find_or_create_receipt(&txn, header).await?;
+        let last_line_id = receipt_line::Entity::find()
+            .select_only()
+            .column_as(receipt_line::Column::Id.max(), "last_line_id")
+            .filter(receipt_line::Column::ReceiptId.eq(receipt.id))
+            .into_tuple::<Option<i32>>()
+            .one(&txn)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch receipt lines: {}", e)))?
+            .flatten()
+            .unwrap_or(0);
+
+        Ok(Self {
+            txn,
+            receipt,
+            last_line_id,
+            partial,
+            total_rows: 0,
+            successful_rows: 0,
+            errors: Vec::new(),
+        })
+    }
+
+    /// Count a row rejected before it reached the import, such as one that could not be parsed
+    pub fn reject(&mut self, error: BulkReceiptError) {
+        self.total_rows += 1;
+        self.errors.push(error);
+    }
+
+    /// Post a batch of rows and return the errors of the ones that failed, in row order
+    pub async fn add_batch(
+        &mut self,
+        rows: Vec<BulkReceiptRow>,
+    ) -> Result<&[BulkReceiptError], AppError> {
+        let first_error = self.errors.len();
+        self.total_rows += rows.len();
+
+        let (rows, invalid): (Vec<_>, Vec<_>) = rows.into_iter().partition(|row| row.quantity > 0);
+        self.errors
+            .extend(invalid.into_iter().map(|row| BulkReceiptError {
+                row: row.line,
                 error: "Quantity must be positive".to_string(),
+            }));
+
+        if !rows.is_empty() {
+            let savepoint =
+                self.txn.begin().await.map_err(|e| {
+                    AppError::internal(format!("Failed to create savepoint: {}", e))
+                })?;
+
+            match stage_bulk_batch(&savepoint, &self.receipt, &rows).await {
+                Ok(errors) => {
+                    savepoint.commit().await.map_err(|e| {
+                        AppError::internal(format!("Failed to release savepoint: {}", e))
+                    })?;
+                    self.successful_rows += rows.len() - errors.len();
+                    self.errors.extend(errors);
+                }
+                Err(_) => {
+                    savepoint.rollback().await.map_err(|e| {
+                        AppError::internal(format!("Failed to rollback savepoint: {}", e))
+                    })?;
+                    for row in &rows {
+                        match receive_bulk_row_in_savepoint(&self.txn, &self.receipt, row).await? {
+                            Ok(()) => self.successful_rows += 1,
+                            Err(error) => self.errors.push(BulkReceiptError {
+                                row: row.line,
+                                error,
+                            }),
+                        }
+                    }
+                }
+            }
+        }
+
+        let errors = &mut self.errors[first_error..];
+        errors.sort_by_key(|e| e.row);
+        Ok(errors)
+    }
+
+    /// Commit the rows that went through, or roll everything back if none did or, outside partial
+    /// mode, if any row failed
+    pub async fn finish(self) -> Result<BulkReceiptResult, AppError> {
+        self.finish_recording(async |_, _| Ok(())).await
+    }
+
+    /// Like finish, but `record` runs in the transaction just before it commits, so that what
+    /// it writes about the result is committed with the receipt or not at all
+    pub async fn finish_recording<F>(self, record: F) -> Result<BulkReceiptResult, AppError>
+    where
+        F: AsyncFnOnce(&DatabaseTransaction, &BulkReceiptResult) -> Result<(), AppError>,
+    {
+        let commit = self.successful_rows > 0 && (self.partial || self.errors.is_empty());
+        let result = BulkReceiptResult {
+            receipt: commit.then(|| self.receipt.clone()),
+            receipt_number: self.receipt.receipt_number,
+            total_rows: self.total_rows,
+            successful_rows: self.successful_rows,
+            errors: self.errors,
+        };
+
+        if commit {
+            post_staged_lines(&self.txn, self.receipt.id, self.last_line_id).await?;
+            putaway::create_tasks_for_receipt(&self.txn, self.receipt.id, self.last_line_id)
+                .await?;
+            record(&self.txn, &result).await?;
+            self.txn
+                .commit()
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+        } else {
+            self.txn.rollback().await.map_err(|e| {
+                AppError::internal(format!("Failed to rollback transaction: {}", e))
+            })?;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Resolve and stage a batch of bulk receipt rows with set-based queries
+///
+/// Returns the errors of rows that could not be staged; an Err means the batch has to be undone.
+/// Must be called on a savepoint.
+async fn stage_bulk_batch(
+    db: &DatabaseTransaction,
+    receipt: &receipt::Model,
+    rows: &[BulkReceiptRow],
+) -> Result<Vec<BulkReceiptError>, AppError> {
+    let mut skus: Vec<&str> = rows.iter().map(|row| row.sku.as_str()).collect();
+    skus.sort_unstable();
+    skus.dedup();
+    let mut items = find_items_by_sku(db, &skus).await?;
+
+    let mut new_skus = HashSet::new();
+    let new_items: Vec<item::ActiveModel> = rows
+        .iter()
+        .filter(|row| !items.contains_key(&row.sku) && new_skus.insert(row.sku.as_str()))
+        .map(|row| {
+            new_item_model(
+                &row.sku,
+                row.name.clone(),
+                row.unit_of_measure.clone(),
+                row.barcode.clone(),
+                row.is_serialized,
+            )
+        })
+        .collect();
+    if !new_items.is_empty() {
+        item::Entity::insert_many(new_items)
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create items: {}", e)))?;
+        let new_skus: Vec<&str> = new_skus.into_iter().collect();
+        items.extend(find_items_by_sku(db, &new_skus).await?);
+    }
+
+    let mut codes: Vec<&str> = rows.iter().map(|row| row.location_code.as_str()).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    let mut locations = find_locations_by_code(db, &codes).await?;
+
+    let mut new_codes = HashSet::new();
+    let new_locations: Vec<location::ActiveModel> = rows
+        .iter()
+        .filter(|row| !locations.contains_key(&row.location_code))
+        .filter_map(|row| row.warehouse_id.map(|warehouse_id| (row, warehouse_id)))
+        .filter(|(row, _)| new_codes.insert(row.location_code.as_str()))
+        .map(|(row, warehouse_id)| {
+            new_location_model(
+                &row.location_code,
+                warehouse_id,
+                row.aisle.clone(),
+                row.bin.clone(),
+                row.shelf.clone(),
+                row.is_pickable,
+                row.is_bulk,
+            )
+        })
+        .collect();
+    if !new_locations.is_empty() {
+        location::Entity::insert_many(new_locations)
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create locations: {}", e)))?;
+        let new_codes: Vec<&str> = new_codes.into_iter().collect();
+        locations.extend(find_locations_by_code(db, &new_codes).await?);
+    }
+
+    let location_ids: Vec<i32> = locations.values().map(|location| location.id).collect();
+    let frozen: HashSet<i32> = cycle_count::find_open_cycle_counts(db, &location_ids)
+        .await?
+        .into_iter()
+        .map(|count| count.location_id)
+        .collect();
+
+    let mut errors = Vec::new();
+    let mut lines = Vec::new();
+    for row in rows {
+        let item = items.get(&row.sku).ok_or_else(|| {
+            AppError::internal(format!("Item with SKU '{}' was not created", row.sku))
+        })?;
+        let Some(location) = locations.get(&row.location_code) else {
+            let error = AppError::bad_request(format!(
+                "warehouse_id is required when creating new location with code: {}",
+                row.location_code
+            ));
+            errors.push(BulkReceiptError {
+                row: row.line,
+                error: format!("Failed to find/create location: {}", error),
             });
             continue;
-        }
+        };
 
-        let item = match find_or_create_item_by_sku(
-            &txn,
-            &row.sku,
-            row.name.clone(),
-            row.unit_of_measure.clone(),
-            row.barcode.clone(),
-            row.is_serialized,
-        )
-        .await
-        {
-            Ok(item) => item,
-            Err(e) => {
+        if frozen.contains(&location.id) {
+            if let Err(error) = receive_bulk_row_in_savepoint(db, receipt, row).await? {
                 errors.push(BulkReceiptError {
-                    row: row_number,
-                    error: format!("Failed to find/create item: {}", e),
+                    row: row.line,
+                    error,
                 });
-                continue;
             }
+            continue;
+        }
+
+        lines.push(staged_line_model(
+            receipt,
+            item.id,
+            location.id,
+            row.quantity,
+        ));
+    }
+
+    if !lines.is_empty() {
+        receipt_line::Entity::insert_many(lines)
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to create receipt lines: {}", e)))?;
+    }
+
+    Ok(errors)
+}
+
+/// A receipt line whose movement is posted when its bulk import commits
+fn staged_line_model(
+    receipt: &receipt::Model,
+    item_id: i32,
+    location_id: i32,
+    quantity: i32,
+) -> receipt_line::ActiveModel {
+    let mut line = <receipt_line::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    line.receipt_id = Set(receipt.id);
+    line.item_id = Set(item_id);
+    line.location_id = Set(location_id);
+    line.quantity = Set(quantity);
+    line.ledger_entry_id = Set(None);
+    line.held_movement_id = Set(None);
+    line.po_line_id = Set(None);
+    line
+}
+
+/// Post the RECEIPT movements of the lines a bulk import staged after `after_line_id`
+///
+/// Goes a batch of lines at a time, each with one chain lock per warehouse, one balance upsert
+/// and one multi-row ledger insert. Their locations were found unfrozen when they were staged
+/// and have been locked against a count opening since.
+async fn post_staged_lines(
+    db: &DatabaseTransaction,
+    receipt_id: i32,
+    after_line_id: i32,
+) -> Result<(), AppError> {
+    let mut after = after_line_id;
+    loop {
+        let lines = receipt_line::Entity::find()
+            .filter(receipt_line::Column::ReceiptId.eq(receipt_id))
+            .filter(receipt_line::Column::Id.gt(after))
+            .filter(receipt_line::Column::LedgerEntryId.is_null())
+            .filter(receipt_line::Column::HeldMovementId.is_null())
+            .order_by_asc(receipt_line::Column::Id)
+            .limit(BULK_RECEIPT_BATCH_SIZE as u64)
+            .all(db)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch receipt lines: {}", e)))?;
+        let Some(last) = lines.last() else {
+            return Ok(());
         };
+        after = last.id;
 
-        let location = match find_or_create_location_by_code(
-            &txn,
-            &row.location_code,
-            row.warehouse_id,
-            row.aisle.clone(),
-            row.bin.clone(),
-            row.shelf.clone(),
-            row.is_pickable,
-            row.is_bulk,
-        )
+        let entry = |line: &receipt_line::Model| NewLedgerEntry {
+            item_id: line.item_id,
+            location_id: line.location_id,
+            quantity_change: line.quantity,
+            reason_type: "RECEIPT".to_string(),
+            reference_type: Some("receipt".to_string()),
+            reference_id: Some(receipt_id),
+        };
+
+        let entries: Vec<NewLedgerEntry> = lines.iter().map(entry).collect();
+        let entry_ids = ledger::insert_entries(db, &entries).await?;
+
+        let mut placeholders = Vec::with_capacity(lines.len());
+        let mut values: Vec<sea_orm::Value> = Vec::with_capacity(lines.len() * 2);
+        for (index, (line, entry_id)) in lines.iter().zip(entry_ids).enumerate() {
+            placeholders.push(format!("(${}, ${})", index * 2 + 1, index * 2 + 2));
+            values.extend([line.id.into(), entry_id.into()]);
+        }
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                r#"
+                UPDATE receipt_line SET ledger_entry_id = posted.ledger_entry_id
+                FROM (VALUES {}) AS posted (id, ledger_entry_id)
+                WHERE receipt_line.id = posted.id
+                "#,
+                placeholders.join(", ")
+            ),
+            values,
+        ))
         .await
-        {
-            Ok(location) => location,
-            Err(e) => {
-                errors.push(BulkReceiptError {
-                    row: row_number,
-                    error: format!("Failed to find/create location: {}", e),
+        .map_err(|e| AppError::internal(format!("Failed to update receipt lines: {}", e)))?;
+    }
+}
+
+/// Stage one bulk receipt row in its own savepoint, returning the row's error message on failure
+async fn receive_bulk_row_in_savepoint(
+    txn: &DatabaseTransaction,
+    receipt: &receipt::Model,
+    row: &BulkReceiptRow,
+) -> Result<Result<(), String>, AppError> {
+    let savepoint = txn
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create savepoint: {}", e)))?;
+
+    let result = receive_bulk_row(&savepoint, receipt, row).await;
+    if result.is_ok() {
+        savepoint
+            .commit()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to release savepoint: {}", e)))?;
+    } else {
+        savepoint
+            .rollback()
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to rollback savepoint: {}", e)))?;
+    }
+
+    Ok(result)
+}
+
+/// Resolve one bulk receipt row and stage it, returning the row's error message on failure
+///
+/// A row at a frozen location is posted straight away instead, so it is held or rejected now.
+async fn receive_bulk_row<C: ConnectionTrait>(
+    db: &C,
+    receipt: &receipt::Model,
+    row: &BulkReceiptRow,
+) -> Result<(), String> {
+    if row.quantity <= 0 {
+        return Err("Quantity must be positive".to_string());
+    }
+
+    let item = find_or_create_item_by_sku(
+        db,
+        &row.sku,
+        row.name.clone(),
+        row.unit_of_measure.clone(),
+        row.barcode.clone(),
+        row.is_serialized,
+    )
+    .await
+    .map_err(|e| format!("Failed to find/create item: {}", e))?;
+
+    let location = find_or_create_location_by_code(
+        db,
+        &row.location_code,
+        row.warehouse_id,
+        row.aisle.clone(),
+        row.bin.clone(),
+        row.shelf.clone(),
+        row.is_pickable,
+        row.is_bulk,
+    )
+    .await
+    .map_err(|e| format!("Failed to find/create location: {}", e))?;
+
+    let frozen = cycle_count::find_open_cycle_count(db, location.id)
+        .await
+        .map_err(|e| format!("Failed to create ledger entry: {}", e))?;
+    if frozen.is_some() {
+        create_receipt_ledger_entry(db, receipt, item.id, location.id, row.quantity, None)
+            .await
+            .map_err(|e| format!("Failed to create ledger entry: {}", e))?;
+        return Ok(());
+    }
+
+    staged_line_model(receipt, item.id, location.id, row.quantity)
+        .insert(db)
+        .await
+        .map_err(|e| format!("Failed to create receipt line: {}", e))?;
+
+    Ok(())
+}
+
+/// What a bulk receipt would do, worked out a batch of rows at a time without writing anything
+///
+/// Rows are resolved the way BulkReceiptImport resolves them, but SKUs and location codes that do
+/// not exist yet are only collected as items and locations that would be created. Rows at a
+/// location frozen by an open cycle count are reported as errors or, in QUEUE mode, as rows whose
+/// movement would be held.
+#[derive(Debug)]
+pub struct BulkReceiptPreview {
+    pub receipt: Option<receipt::Model>, // the receipt lines would be added to, if it exists
+    pub total_rows: usize,
+    pub valid_rows: usize,
+    pub errors: Vec<BulkReceiptError>,
+    pub held_rows: Vec<usize>, // rows whose movement would be held by a cycle count
+    pub items_to_create: Vec<String>,
+    pub locations_to_create: Vec<PlannedLocation>,
+    planned_skus: HashSet<String>,
+    planned_codes: HashSet<String>,
+    warehouses: HashMap<i32, Option<warehouse::Model>>,
+}
+
+impl BulkReceiptPreview {
+    pub async fn begin(db: &DatabaseConnection, header: &ReceiptHeader) -> Result<Self, AppError> {
+        let receipt = match header.receipt_number.as_deref() {
+            Some(receipt_number) => receipt::Entity::find()
+                .filter(receipt::Column::ReceiptNumber.eq(receipt_number))
+                .one(db)
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to fetch receipt: {}", e)))?,
+            None => None,
+        };
+        if let Some(ref receipt) = receipt {
+            check_receipt_accepts_lines(receipt, header)?;
+        }
+
+        Ok(Self {
+            receipt,
+            total_rows: 0,
+            valid_rows: 0,
+            errors: Vec::new(),
+            held_rows: Vec::new(),
+            items_to_create: Vec::new(),
+            locations_to_create: Vec::new(),
+            planned_skus: HashSet::new(),
+            planned_codes: HashSet::new(),
+            warehouses: HashMap::new(),
+        })
+    }
+
+    /// Count a row rejected before it reached the preview, such as one that could not be parsed
+    pub fn reject(&mut self, error: BulkReceiptError) {
+        self.total_rows += 1;
+        self.errors.push(error);
+    }
+
+    pub async fn add_batch(
+        &mut self,
+        db: &DatabaseConnection,
+        rows: &[BulkReceiptRow],
+    ) -> Result<(), AppError> {
+        self.total_rows += rows.len();
+
+        let skus: Vec<&str> = rows.iter().map(|row| row.sku.as_str()).collect();
+        let items = find_items_by_sku(db, &skus).await?;
+        let codes: Vec<&str> = rows.iter().map(|row| row.location_code.as_str()).collect();
+        let locations = find_locations_by_code(db, &codes).await?;
+        let location_ids: Vec<i32> = locations.values().map(|location| location.id).collect();
+        let open_counts: HashMap<i32, i32> = cycle_count::find_open_cycle_counts(db, &location_ids)
+            .await?
+            .into_iter()
+            .map(|count| (count.location_id, count.id))
+            .collect();
+
+        // A new location is created from the first row of the batch that gives a warehouse_id
+        let mut new_location_warehouses: HashMap<&str, i32> = HashMap::new();
+        for row in rows.iter().filter(|row| row.quantity > 0) {
+            if let Some(warehouse_id) = row.warehouse_id {
+                new_location_warehouses
+                    .entry(row.location_code.as_str())
+                    .or_insert(warehouse_id);
+            }
+        }
+
+        for row in rows {
+            if row.quantity <= 0 {
+                self.errors.push(BulkReceiptError {
+                    row: row.line,
+                    error: "Quantity must be positive".to_string(),
                 });
                 continue;
             }
-        };
 
-        match create_receipt_ledger_entry(&txn, item.id, location.id, row.quantity, &receipt_id)
-            .await
-        {
-            Ok(_) => {
-                successful_rows += 1;
+            if !items.contains_key(&row.sku) && self.planned_skus.insert(row.sku.clone()) {
+                self.items_to_create.push(row.sku.clone());
             }
-            Err(e) => {
-                errors.push(BulkReceiptError {
-                    row: row_number,
-                    error: format!("Failed to create ledger entry: {}", e),
-                });
+
+            let Some(location) = locations.get(&row.location_code) else {
+                if !self.planned_codes.contains(&row.location_code) {
+                    let Some(&warehouse_id) =
+                        new_location_warehouses.get(row.location_code.as_str())
+                    else {
+                        self.errors.push(BulkReceiptError {
+                            row: row.line,
+                            error: format!(
+                                "warehouse_id is required when creating new location with code: {}",
+                                row.location_code
+                            ),
+                        });
+                        continue;
+                    };
+                    if self.find_warehouse(db, warehouse_id).await?.is_none() {
+                        self.errors.push(BulkReceiptError {
+                            row: row.line,
+                            error: format!("Warehouse with id {} not found", warehouse_id),
+                        });
+                        continue;
+                    }
+                    self.planned_codes.insert(row.location_code.clone());
+                    self.locations_to_create.push(PlannedLocation {
+                        code: row.location_code.clone(),
+                        warehouse_id,
+                    });
+                }
+                self.valid_rows += 1;
+                continue;
+            };
+
+            if let Some(&cycle_count_id) = open_counts.get(&location.id) {
+                let freeze_mode = self
+                    .find_warehouse(db, location.warehouse_id)
+                    .await?
+                    .map(|w| w.count_freeze_mode.as_str())
+                    .unwrap_or("REJECT");
+                if freeze_mode != "QUEUE" {
+                    self.errors.push(BulkReceiptError {
+                        row: row.line,
+                        error: format!(
+                            "Location '{}' is frozen by open cycle count {}",
+                            location.code, cycle_count_id
+                        ),
+                    });
+                    continue;
+                }
+                self.held_rows.push(row.line);
             }
+
+            self.valid_rows += 1;
         }
+
+        Ok(())
     }
 
-    if !errors.is_empty() {
-        txn.rollback()
-            .await
-            .map_err(|e| AppError::internal(format!("Failed to rollback transaction: {}", e)))?;
+    async fn find_warehouse(
+        &mut self,
+        db: &DatabaseConnection,
+        warehouse_id: i32,
+    ) -> Result<Option<&warehouse::Model>, AppError> {
+        let warehouse = match self.warehouses.entry(warehouse_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let warehouse = warehouse::Entity::find_by_id(warehouse_id)
+                    .one(db)
+                    .await
+                    .map_err(|e| AppError::internal(format!("Failed to fetch warehouse: {}", e)))?;
+                entry.insert(warehouse)
+            }
+        };
+
+        Ok(warehouse.as_ref())
+    }
+}
+
+/// Reverse every movement a POSTED receipt's lines posted and mark it REVERSED
+///
+/// The entries are found through the receipt lines rather than by reference_id, which for
+/// receipts migrated from before the receipt table still holds a hash of the old receipt UUID.
+/// A receipt against a purchase order also comes off the order's received quantities, and its
+/// putaway tasks are cancelled or, once completed, reversed too.
+pub async fn reverse_receipt(
+    db: &DatabaseConnection,
+    receipt_id: i32,
+) -> Result<ReversedReceipt, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    // Lock the purchase order before the receipt, in the order receiving takes them
+    let purchase_order_id = receipt::Entity::find_by_id(receipt_id)
+        .select_only()
+        .column(receipt::Column::PurchaseOrderId)
+        .into_tuple::<Option<i32>>()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Receipt with id {} not found", receipt_id)))?;
+    let purchase_order = match purchase_order_id {
+        Some(id) => Some(purchase_order::find_purchase_order(&txn, id, true).await?),
+        None => None,
+    };
+
+    let receipt = receipt::Entity::find_by_id(receipt_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch receipt: {}", e)))?
+        .ok_or_else(|| AppError::not_found(format!("Receipt with id {} not found", receipt_id)))?;
+
+    if receipt.status != "POSTED" {
+        return Err(AppError::bad_request(format!(
+            "Receipt with id {} is not in POSTED status (current status: {})",
+            receipt_id, receipt.status
+        )));
+    }
 
-        return Ok(BulkReceiptResult {
-            receipt_id,
-            total_rows,
-            successful_rows,
-            errors,
-        });
+    let document = find_receipt_lines(&txn, receipt).await?;
+
+    let held = document
+        .held_movements
+        .iter()
+        .filter(|held| held.status == "HELD")
+        .count();
+    if held > 0 {
+        return Err(AppError::bad_request(format!(
+            "Receipt with id {} has {} movement(s) held by an open cycle count",
+            receipt_id, held
+        )));
+    }
+
+    let entry_ids: Vec<i32> = document
+        .lines
+        .iter()
+        .filter_map(|line| document.ledger_entry_id(line))
+        .collect();
+    let entries = ledger_entity::Entity::find()
+        .filter(ledger_entity::Column::Id.is_in(entry_ids))
+        .order_by_asc(ledger_entity::Column::Id)
+        .all(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch ledger entries: {}", e)))?;
+
+    // Stock already put away comes back to the receiving location first
+    let stock_keys: Vec<(i32, i32)> = entries
+        .iter()
+        .map(|entry| (entry.item_id, entry.location_id))
+        .collect();
+    let mut reversals = putaway::reverse_tasks_for_receipt(&txn, receipt_id, &stock_keys).await?;
+    reversals.extend(ledger::reverse_entries(&txn, &entries).await?);
+
+    if let Some(purchase_order) = purchase_order {
+        purchase_order::unreceive(&txn, purchase_order, &document.lines).await?;
     }
 
+    let mut receipt_update: receipt::ActiveModel = document.receipt.into();
+    receipt_update.status = Set("REVERSED".to_string());
+    receipt_update.updated_at = Set(chrono::Utc::now().into());
+
+    let updated_receipt = receipt_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update receipt status: {}", e)))?;
+
     txn.commit()
         .await
         .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
 
-    Ok(BulkReceiptResult {
-        receipt_id,
-        total_rows,
-        successful_rows,
-        errors,
+    Ok(ReversedReceipt {
+        receipt: updated_receipt,
+        reversals,
     })
 }
 
-/// Represents a single row in a bulk receipt CSV
-#[derive(Debug, Clone)]
+/// Reversed receipt with the REVERSAL entries that undid it
+#[derive(Debug)]
+pub struct ReversedReceipt {
+    pub receipt: receipt::Model,
+    pub reversals: Vec<ledger_entity::Model>,
+}
+
+/// Represents a single row of a bulk receipt file
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BulkReceiptRow {
+    pub line: usize, // CSV line number, the header being line 1
     pub sku: String,
     pub location_code: String,
     pub quantity: i32,
@@ -284,7 +1223,8 @@ pub struct BulkReceiptRow {
 /// Result of processing a bulk receipt
 #[derive(Debug)]
 pub struct BulkReceiptResult {
-    pub receipt_id: String,
+    pub receipt: Option<receipt::Model>, // None when the import was rolled back
+    pub receipt_number: String,
     pub total_rows: usize,
     pub successful_rows: usize,
     pub errors: Vec<BulkReceiptError>,
@@ -296,3 +1236,10 @@ pub struct BulkReceiptError {
     pub row: usize,
     pub error: String,
 }
+
+/// Location a bulk receipt would create for an unknown location code
+#[derive(Debug)]
+pub struct PlannedLocation {
+    pub code: String,
+    pub warehouse_id: i32,
+}
@@ -0,0 +1,1074 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+
+use calamine::{Reader as _, Xlsx};
+use csv::ReaderBuilder;
+use serde::de::{Deserializer as _, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::services::receipt::{BULK_RECEIPT_BATCH_SIZE, BulkReceiptRow};
+use crate::utils::error::AppError;
+
+/// The fields of a receipt row, and the columns they are read from unless a mapping profile says
+/// otherwise
+pub const RECEIPT_COLUMNS: [&str; 13] = [
+    "sku",
+    "location_code",
+    "quantity",
+    "name",
+    "unit_of_measure",
+    "barcode",
+    "is_serialized",
+    "warehouse_id",
+    "aisle",
+    "bin",
+    "shelf",
+    "is_pickable",
+    "is_bulk",
+];
+
+pub const REQUIRED_COLUMNS: [&str; 3] = ["sku", "location_code", "quantity"];
+
+/// A format receipt files may be sent in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Json,   // an array of row objects
+    Ndjson, // one row object per line
+    Xlsx,   // the first worksheet, with a header row
+}
+
+impl FileFormat {
+    /// The format named by a Content-Type, ignoring parameters such as charset
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+        match mime.to_lowercase().as_str() {
+            "text/csv" | "application/csv" => Some(FileFormat::Csv),
+            "application/json" => Some(FileFormat::Json),
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl" => {
+                Some(FileFormat::Ndjson)
+            }
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => {
+                Some(FileFormat::Xlsx)
+            }
+            _ => None,
+        }
+    }
+
+    /// The format named by a file's extension
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, extension) = file_name.rsplit_once('.')?;
+        match extension.to_lowercase().as_str() {
+            "csv" => Some(FileFormat::Csv),
+            "json" => Some(FileFormat::Json),
+            "ndjson" | "jsonl" => Some(FileFormat::Ndjson),
+            "xlsx" => Some(FileFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    /// The format of an uploaded file: its Content-Type if specific, else its extension. Files
+    /// that name neither are taken as CSV, the only format accepted before
+    pub fn detect(content_type: Option<&str>, file_name: Option<&str>) -> Self {
+        content_type
+            .and_then(Self::from_content_type)
+            .or_else(|| file_name.and_then(Self::from_file_name))
+            .unwrap_or(FileFormat::Csv)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => "CSV",
+            FileFormat::Json => "JSON",
+            FileFormat::Ndjson => "NDJSON",
+            FileFormat::Xlsx => "XLSX",
+        }
+    }
+
+    /// The format with the given name, as returned by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            FileFormat::Csv,
+            FileFormat::Json,
+            FileFormat::Ndjson,
+            FileFormat::Xlsx,
+        ]
+        .into_iter()
+        .find(|format| format.name() == name)
+    }
+}
+
+/// How one field of a receipt row is read from a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMapping {
+    pub field: String,                 // one of RECEIPT_COLUMNS
+    pub source_column: Option<String>, // matched like a header, ignoring case
+    pub default_value: Option<String>, // when the column is missing or the value empty
+    pub trim: bool,
+    pub uppercase: bool,
+    pub multiplier: Option<i32>, // quantity only, e.g. units per case
+}
+
+/// How the fields of a receipt row are read from a file's columns: the standard layout, or a
+/// mapping profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub profile: Option<String>,
+    pub fields: Vec<FieldMapping>,
+}
+
+impl ColumnMapping {
+    /// Every field read, trimmed, from the column of the same name
+    pub fn standard() -> Self {
+        Self {
+            profile: None,
+            fields: RECEIPT_COLUMNS
+                .iter()
+                .map(|col| FieldMapping {
+                    field: col.to_string(),
+                    source_column: Some(col.to_string()),
+                    default_value: None,
+                    trim: true,
+                    uppercase: false,
+                    multiplier: None,
+                })
+                .collect(),
+        }
+    }
+
+    /// The columns a JSON row is laid out in, as it has no header row: each source column
+    /// once, in the order they are mapped
+    fn source_columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = Vec::new();
+        for column in self.fields.iter().filter_map(|f| f.source_column.as_ref()) {
+            if !columns.iter().any(|c| c.eq_ignore_ascii_case(column)) {
+                columns.push(column.clone());
+            }
+        }
+        columns
+    }
+
+    /// Find each field's column among the headers. Fails if a required field has neither its
+    /// column nor a default
+    fn resolve(&self, headers: &[String]) -> Result<Layout, AppError> {
+        let columns: HashMap<String, usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(i, h)| (h.trim().to_lowercase(), i))
+            .collect();
+
+        let mut sources: Vec<FieldSource> = vec![FieldSource::default(); RECEIPT_COLUMNS.len()];
+        let mut quantity_multiplier = 1;
+        for mapping in &self.fields {
+            let Some(index) = RECEIPT_COLUMNS.iter().position(|col| *col == mapping.field) else {
+                continue;
+            };
+            sources[index] = FieldSource {
+                column: mapping
+                    .source_column
+                    .as_ref()
+                    .and_then(|col| columns.get(&col.trim().to_lowercase()).copied()),
+                default_value: mapping.default_value.clone(),
+                trim: mapping.trim,
+                uppercase: mapping.uppercase,
+            };
+            if mapping.field == "quantity" {
+                quantity_multiplier = mapping.multiplier.unwrap_or(1);
+            }
+        }
+
+        for col in REQUIRED_COLUMNS {
+            let index = RECEIPT_COLUMNS.iter().position(|c| *c == col).unwrap_or(0);
+            let source = &sources[index];
+            if source.column.is_some() || source.default_value.is_some() {
+                continue;
+            }
+
+            let found_headers: Vec<String> =
+                headers.iter().map(|h| h.trim().to_lowercase()).collect();
+            let source_column = self
+                .fields
+                .iter()
+                .find(|f| f.field == col)
+                .and_then(|f| f.source_column.as_deref())
+                .unwrap_or(col);
+            return Err(AppError::bad_request(match &self.profile {
+                Some(profile) => format!(
+                    "Missing column '{}' for field '{}' of mapping profile '{}'. Found columns: {:?}",
+                    source_column, col, profile, found_headers
+                ),
+                None => format!(
+                    "Missing required column: {}. Found columns: {:?}",
+                    col, found_headers
+                ),
+            }));
+        }
+
+        Ok(Layout {
+            columns,
+            sources,
+            quantity_multiplier,
+        })
+    }
+}
+
+/// Where each receipt field is read from in a particular file, in RECEIPT_COLUMNS order
+struct Layout {
+    columns: HashMap<String, usize>, // column index of each lowercased header
+    sources: Vec<FieldSource>,
+    quantity_multiplier: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FieldSource {
+    column: Option<usize>,
+    default_value: Option<String>,
+    trim: bool,
+    uppercase: bool,
+}
+
+impl Layout {
+    /// A field's value in a row, or its default if empty; transforms apply to either
+    fn get(&self, field: &str, fields: &[String]) -> Option<String> {
+        let index = RECEIPT_COLUMNS.iter().position(|col| *col == field)?;
+        let source = &self.sources[index];
+        let value = source
+            .column
+            .and_then(|idx| fields.get(idx))
+            .map(|value| if source.trim { value.trim() } else { value })
+            .filter(|value| !value.is_empty())
+            .or(source.default_value.as_deref())?;
+
+        Some(if source.uppercase {
+            value.to_uppercase()
+        } else {
+            value.to_string()
+        })
+    }
+}
+
+/// A data row of a receipt file: its line number, its fields as written, and the row or the
+/// reason it was rejected
+#[derive(Debug)]
+pub struct ParsedRow {
+    pub line: usize,
+    pub fields: Vec<String>,
+    pub row: Result<BulkReceiptRow, String>,
+}
+
+/// The rows of a receipt file, handed over in batches as the file is parsed
+pub struct RowBatches {
+    pub headers: Vec<String>,
+    batches: mpsc::Receiver<Result<Vec<ParsedRow>, AppError>>,
+}
+
+impl RowBatches {
+    /// The next batch of up to BULK_RECEIPT_BATCH_SIZE rows, or None at the end of the file.
+    /// Fails if the rest of the file cannot be read, as with malformed JSON
+    pub async fn next_batch(&mut self) -> Result<Option<Vec<ParsedRow>>, AppError> {
+        self.batches.recv().await.transpose()
+    }
+}
+
+/// Parse a receipt file whose bytes arrive in chunks on `chunks`, reading its rows' fields as
+/// `mapping` says
+///
+/// The parser runs on a blocking thread, reading chunks as they arrive and sending back
+/// batches of rows. Both channels are bounded, so however large the file only a few chunks and
+/// batches are in memory at once; XLSX files are the exception, as they can only be read whole.
+/// Fails if the header row cannot be read or lacks a required column; a bad data row is only
+/// rejected on its own.
+///
+/// Rows are numbered by CSV line and worksheet row, counting the header as 1, by NDJSON line,
+/// and by position in a JSON array, starting at 1.
+pub async fn parse_file(
+    format: FileFormat,
+    mapping: ColumnMapping,
+    chunks: mpsc::Receiver<Vec<u8>>,
+) -> Result<RowBatches, AppError> {
+    let (headers_tx, headers_rx) = oneshot::channel();
+    let (batches_tx, batches_rx) = mpsc::channel(2);
+
+    tokio::task::spawn_blocking(move || {
+        let reader = ChunkReader::new(chunks);
+        let sender = BatchSender::new(batches_tx);
+        match format {
+            FileFormat::Csv => read_csv(reader, &mapping, headers_tx, sender),
+            FileFormat::Json => read_json(reader, &mapping, headers_tx, sender),
+            FileFormat::Ndjson => read_ndjson(reader, &mapping, headers_tx, sender),
+            FileFormat::Xlsx => read_xlsx(reader, &mapping, headers_tx, sender),
+        }
+    });
+
+    let headers = headers_rx.await.map_err(|_| {
+        AppError::internal(format!(
+            "{} parser stopped before reading the headers",
+            format.name()
+        ))
+    })??;
+
+    Ok(RowBatches {
+        headers,
+        batches: batches_rx,
+    })
+}
+
+type HeadersSender = oneshot::Sender<Result<Vec<String>, AppError>>;
+
+fn read_csv(
+    reader: ChunkReader,
+    mapping: &ColumnMapping,
+    headers_tx: HeadersSender,
+    mut sender: BatchSender,
+) {
+    // Values are trimmed by the mapping, which a profile may turn off
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::Headers)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::bad_request(format!("Failed to read CSV headers: {}", e)))
+        .map(|headers| headers.iter().map(|h| h.to_string()).collect::<Vec<_>>());
+    let Some(layout) = send_headers(headers_tx, mapping, headers) else {
+        return;
+    };
+
+    for (row_index, result) in reader.records().enumerate() {
+        let line = row_index + 2;
+        let parsed = match result {
+            Ok(record) => {
+                let fields: Vec<String> = record.iter().map(|field| field.to_string()).collect();
+                ParsedRow {
+                    line,
+                    row: row_from_fields(&layout, &fields, line),
+                    fields,
+                }
+            }
+            Err(e) => ParsedRow {
+                line,
+                fields: Vec::new(),
+                row: Err(format!("CSV parse error: {}", e)),
+            },
+        };
+        if !sender.push(parsed) {
+            return;
+        }
+    }
+    sender.finish();
+}
+
+fn read_json(
+    reader: ChunkReader,
+    mapping: &ColumnMapping,
+    headers_tx: HeadersSender,
+    mut sender: BatchSender,
+) {
+    let Some(layout) = send_headers(headers_tx, mapping, Ok(mapping.source_columns())) else {
+        return;
+    };
+
+    // Each element is parsed and handed on as it is read, rather than the array as a whole
+    struct RowsVisitor<'a> {
+        layout: &'a Layout,
+        sender: &'a mut BatchSender,
+    }
+
+    impl<'de> Visitor<'de> for RowsVisitor<'_> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a JSON array of receipt rows")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+            let mut line = 0;
+            while let Some(value) = seq.next_element::<Value>()? {
+                line += 1;
+                if !self.sender.push(row_from_json(self.layout, value, line)) {
+                    break;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    let result = deserializer
+        .deserialize_seq(RowsVisitor {
+            layout: &layout,
+            sender: &mut sender,
+        })
+        .and_then(|()| deserializer.end());
+    match result {
+        Ok(()) => sender.finish(),
+        Err(e) => sender.fail(AppError::bad_request(format!(
+            "Failed to read JSON file: {}",
+            e
+        ))),
+    }
+}
+
+fn read_ndjson(
+    reader: ChunkReader,
+    mapping: &ColumnMapping,
+    headers_tx: HeadersSender,
+    mut sender: BatchSender,
+) {
+    let Some(layout) = send_headers(headers_tx, mapping, Ok(mapping.source_columns())) else {
+        return;
+    };
+
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    let mut line = 0;
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => line += 1,
+            Err(e) => {
+                sender.fail(AppError::bad_request(format!(
+                    "Failed to read NDJSON file: {}",
+                    e
+                )));
+                return;
+            }
+        }
+        if buf.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let parsed = match serde_json::from_slice::<Value>(&buf) {
+            Ok(value) => row_from_json(&layout, value, line),
+            Err(e) => ParsedRow {
+                line,
+                fields: Vec::new(),
+                row: Err(format!("JSON parse error: {}", e)),
+            },
+        };
+        if !sender.push(parsed) {
+            return;
+        }
+    }
+    sender.finish();
+}
+
+fn read_xlsx(
+    mut reader: ChunkReader,
+    mapping: &ColumnMapping,
+    headers_tx: HeadersSender,
+    mut sender: BatchSender,
+) {
+    let mut data = Vec::new();
+    if let Err(e) = reader.read_to_end(&mut data) {
+        let _ = headers_tx.send(Err(AppError::bad_request(format!(
+            "Failed to read XLSX file: {}",
+            e
+        ))));
+        return;
+    }
+
+    let range = Xlsx::new(Cursor::new(data))
+        .map_err(|e| e.to_string())
+        .and_then(|mut workbook| match workbook.worksheet_range_at(0) {
+            Some(range) => range.map_err(|e| e.to_string()),
+            None => Err("the workbook has no worksheets".to_string()),
+        })
+        .map_err(|e| AppError::bad_request(format!("Failed to read XLSX file: {}", e)));
+    let range = match range {
+        Ok(range) => range,
+        Err(e) => {
+            let _ = headers_tx.send(Err(e));
+            return;
+        }
+    };
+
+    // Row numbers as shown in the spreadsheet, which need not start at row 1
+    let header_line = range.start().map(|(row, _)| row as usize + 1).unwrap_or(1);
+    let mut rows = range.rows();
+    let headers = rows
+        .next()
+        .map(|cells| cells.iter().map(|cell| cell.to_string()).collect())
+        .unwrap_or_default();
+    let Some(layout) = send_headers(headers_tx, mapping, Ok(headers)) else {
+        return;
+    };
+
+    for (row_index, cells) in rows.enumerate() {
+        let line = header_line + row_index + 1;
+        let fields: Vec<String> = cells.iter().map(|cell| cell.to_string()).collect();
+        // Blank rows inside the sheet's used range are skipped, as blank CSV lines are
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let parsed = ParsedRow {
+            line,
+            row: row_from_fields(&layout, &fields, line),
+            fields,
+        };
+        if !sender.push(parsed) {
+            return;
+        }
+    }
+    sender.finish();
+}
+
+/// Hand the headers back to the caller, returning where each field is read from. None if the
+/// headers were refused or the caller has given up
+fn send_headers(
+    headers_tx: HeadersSender,
+    mapping: &ColumnMapping,
+    headers: Result<Vec<String>, AppError>,
+) -> Option<Layout> {
+    match headers.and_then(|headers| Ok((mapping.resolve(&headers)?, headers))) {
+        Ok((layout, headers)) => {
+            headers_tx.send(Ok(headers)).ok()?;
+            Some(layout)
+        }
+        Err(e) => {
+            let _ = headers_tx.send(Err(e));
+            None
+        }
+    }
+}
+
+/// Lay a JSON row object out in the mapping's source columns. Keys are matched like CSV
+/// headers, and other keys are ignored
+fn row_from_json(layout: &Layout, value: Value, line: usize) -> ParsedRow {
+    let Value::Object(object) = value else {
+        return ParsedRow {
+            line,
+            fields: Vec::new(),
+            row: Err("Row must be a JSON object".to_string()),
+        };
+    };
+
+    let mut fields = vec![String::new(); layout.columns.len()];
+    let mut invalid = None;
+    for (key, value) in object {
+        let key = key.trim().to_lowercase();
+        let Some(&idx) = layout.columns.get(&key) else {
+            continue;
+        };
+        fields[idx] = match value {
+            Value::Null => String::new(),
+            Value::String(s) => s,
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Array(_) | Value::Object(_) => {
+                invalid.get_or_insert(format!(
+                    "Field '{}' must be a string, number or boolean",
+                    key
+                ));
+                continue;
+            }
+        };
+    }
+
+    let row = match invalid {
+        Some(error) => Err(error),
+        None => row_from_fields(layout, &fields, line),
+    };
+    ParsedRow { line, fields, row }
+}
+
+fn row_from_fields(
+    layout: &Layout,
+    fields: &[String],
+    line: usize,
+) -> Result<BulkReceiptRow, String> {
+    let get_field = |col: &str| layout.get(col, fields);
+
+    let sku = get_field("sku").ok_or("Missing required field 'sku'")?;
+    let location_code =
+        get_field("location_code").ok_or("Missing required field 'location_code'")?;
+    let quantity = get_field("quantity").ok_or("Missing required field 'quantity'")?;
+    let quantity = quantity
+        .parse::<i32>()
+        .ok()
+        .and_then(|units| units.checked_mul(layout.quantity_multiplier))
+        .ok_or_else(|| format!("Invalid quantity '{}'", quantity))?;
+
+    Ok(BulkReceiptRow {
+        line,
+        sku,
+        location_code,
+        quantity,
+        name: get_field("name"),
+        unit_of_measure: get_field("unit_of_measure"),
+        barcode: get_field("barcode"),
+        is_serialized: get_field("is_serialized").and_then(|s| s.parse::<bool>().ok()),
+        warehouse_id: get_field("warehouse_id").and_then(|s| s.parse::<i32>().ok()),
+        aisle: get_field("aisle"),
+        bin: get_field("bin"),
+        shelf: get_field("shelf"),
+        is_pickable: get_field("is_pickable").and_then(|s| s.parse::<bool>().ok()),
+        is_bulk: get_field("is_bulk").and_then(|s| s.parse::<bool>().ok()),
+    })
+}
+
+/// Collects parsed rows into batches and sends each on once full
+struct BatchSender {
+    batches: mpsc::Sender<Result<Vec<ParsedRow>, AppError>>,
+    batch: Vec<ParsedRow>,
+}
+
+impl BatchSender {
+    fn new(batches: mpsc::Sender<Result<Vec<ParsedRow>, AppError>>) -> Self {
+        Self {
+            batches,
+            batch: Vec::with_capacity(BULK_RECEIPT_BATCH_SIZE),
+        }
+    }
+
+    /// Add a row. False if the receiving side has given up on the file
+    fn push(&mut self, row: ParsedRow) -> bool {
+        self.batch.push(row);
+        self.batch.len() < BULK_RECEIPT_BATCH_SIZE
+            || self
+                .batches
+                .blocking_send(Ok(std::mem::take(&mut self.batch)))
+                .is_ok()
+    }
+
+    fn finish(self) {
+        if !self.batch.is_empty() {
+            let _ = self.batches.blocking_send(Ok(self.batch));
+        }
+    }
+
+    /// Give up on the rest of the file; rows not yet sent are dropped with it
+    fn fail(self, error: AppError) {
+        let _ = self.batches.blocking_send(Err(error));
+    }
+}
+
+/// Blocking reader over chunks sent from async code, dropping a leading UTF-8 BOM
+struct ChunkReader {
+    chunks: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+    at_start: bool,
+}
+
+impl ChunkReader {
+    fn new(chunks: mpsc::Receiver<Vec<u8>>) -> Self {
+        Self {
+            chunks,
+            chunk: Vec::new(),
+            pos: 0,
+            at_start: true,
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.at_start {
+            self.at_start = false;
+            // The BOM can be split over chunks, so gather its length in bytes before checking
+            while self.chunk.len() < 3 {
+                match self.chunks.blocking_recv() {
+                    Some(chunk) => self.chunk.extend_from_slice(&chunk),
+                    None => break,
+                }
+            }
+            if self.chunk.starts_with(&[0xEF, 0xBB, 0xBF]) {
+                self.pos = 3;
+            }
+        }
+
+        while self.pos == self.chunk.len() {
+            match self.chunks.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn chunk_channel(chunks: &[&[u8]]) -> mpsc::Receiver<Vec<u8>> {
+        let (tx, rx) = mpsc::channel(chunks.len().max(1));
+        for chunk in chunks {
+            tx.try_send(chunk.to_vec()).unwrap();
+        }
+        rx
+    }
+
+    fn read_chunks(chunks: &[&[u8]]) -> Vec<u8> {
+        let mut data = Vec::new();
+        ChunkReader::new(chunk_channel(chunks))
+            .read_to_end(&mut data)
+            .unwrap();
+        data
+    }
+
+    /// Parse a file sent in chunks of `chunk_size` bytes, returning its headers and every row
+    async fn parse(
+        format: FileFormat,
+        mapping: ColumnMapping,
+        data: &[u8],
+        chunk_size: usize,
+    ) -> Result<(Vec<String>, Vec<ParsedRow>), AppError> {
+        let chunks: Vec<&[u8]> = data.chunks(chunk_size).collect();
+        let mut batches = parse_file(format, mapping, chunk_channel(&chunks)).await?;
+        let mut rows = Vec::new();
+        while let Some(batch) = batches.next_batch().await? {
+            rows.extend(batch);
+        }
+        Ok((batches.headers, rows))
+    }
+
+    fn field(field: &str, source_column: &str) -> FieldMapping {
+        FieldMapping {
+            field: field.to_string(),
+            source_column: Some(source_column.to_string()),
+            default_value: None,
+            trim: true,
+            uppercase: false,
+            multiplier: None,
+        }
+    }
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn fields(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    /// A workbook whose first worksheet holds `rows` as inline strings, starting at A1
+    fn xlsx(rows: &[&[&str]]) -> Vec<u8> {
+        let mut sheet_data = String::new();
+        for (r, row) in rows.iter().enumerate() {
+            sheet_data.push_str(&format!("<row r=\"{}\">", r + 1));
+            for (c, value) in row.iter().enumerate() {
+                let cell = format!("{}{}", (b'A' + c as u8) as char, r + 1);
+                sheet_data.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t>{}</t></is></c>",
+                    cell, value
+                ));
+            }
+            sheet_data.push_str("</row>");
+        }
+
+        let files = [
+            (
+                "[Content_Types].xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#.to_string(),
+            ),
+            (
+                "_rels/.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/workbook.xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Receipts" sheetId="1" r:id="rId1"/></sheets></workbook>"#.to_string(),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{}</sheetData></worksheet>"#,
+                    sheet_data
+                ),
+            ),
+        ];
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in files {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn chunk_reader_drops_a_leading_bom() {
+        assert_eq!(read_chunks(&[b"\xEF\xBB\xBFsku,qty"]), b"sku,qty");
+    }
+
+    #[test]
+    fn chunk_reader_drops_a_bom_split_over_chunks() {
+        assert_eq!(
+            read_chunks(&[b"\xEF", b"\xBB", b"\xBFsku", b",qty"]),
+            b"sku,qty"
+        );
+    }
+
+    #[test]
+    fn chunk_reader_keeps_a_bom_after_the_start() {
+        assert_eq!(read_chunks(&[b"sku", b"\xEF\xBB\xBF"]), b"sku\xEF\xBB\xBF");
+    }
+
+    #[test]
+    fn chunk_reader_reads_short_and_empty_input() {
+        assert_eq!(read_chunks(&[b"ab"]), b"ab");
+        assert_eq!(read_chunks(&[]), b"");
+    }
+
+    #[test]
+    fn standard_mapping_reads_every_column_by_name() {
+        let mapping = ColumnMapping::standard();
+        assert_eq!(mapping.fields.len(), RECEIPT_COLUMNS.len());
+        assert!(mapping.fields.iter().all(|f| f.trim && !f.uppercase));
+
+        let layout = mapping
+            .resolve(&headers(&["Quantity", " SKU ", "location_code", "name"]))
+            .unwrap();
+        let row = row_from_fields(&layout, &fields(&["5", " ABC-1 ", "A1", ""]), 2).unwrap();
+        assert_eq!(row.sku, "ABC-1");
+        assert_eq!(row.location_code, "A1");
+        assert_eq!(row.quantity, 5);
+        assert_eq!(row.name, None);
+    }
+
+    #[test]
+    fn standard_mapping_requires_the_required_columns() {
+        let error = ColumnMapping::standard()
+            .resolve(&headers(&["sku", "quantity"]))
+            .err()
+            .unwrap();
+        assert!(
+            error
+                .to_string()
+                .contains("Missing required column: location_code")
+        );
+    }
+
+    #[test]
+    fn mapping_profile_renames_transforms_and_defaults_fields() {
+        let mapping = ColumnMapping {
+            profile: Some("supplier".to_string()),
+            fields: vec![
+                FieldMapping {
+                    uppercase: true,
+                    ..field("sku", "Item Code")
+                },
+                FieldMapping {
+                    default_value: Some("DOCK-1".to_string()),
+                    ..field("location_code", "Bin")
+                },
+                FieldMapping {
+                    multiplier: Some(12),
+                    ..field("quantity", "Cases")
+                },
+                FieldMapping {
+                    trim: false,
+                    ..field("name", "Description")
+                },
+            ],
+        };
+
+        let layout = mapping
+            .resolve(&headers(&["item code", "cases", "description"]))
+            .unwrap();
+        let row = row_from_fields(&layout, &fields(&["abc-1", "3", " Widget "]), 2).unwrap();
+        assert_eq!(row.sku, "ABC-1");
+        assert_eq!(row.location_code, "DOCK-1");
+        assert_eq!(row.quantity, 36);
+        assert_eq!(row.name.as_deref(), Some(" Widget "));
+    }
+
+    #[test]
+    fn mapping_profile_names_the_missing_source_column() {
+        let mapping = ColumnMapping {
+            profile: Some("supplier".to_string()),
+            fields: vec![
+                field("sku", "Item Code"),
+                field("location_code", "Bin"),
+                field("quantity", "Cases"),
+            ],
+        };
+
+        let error = mapping
+            .resolve(&headers(&["item code", "bin"]))
+            .err()
+            .unwrap();
+        assert!(
+            error.to_string().contains(
+                "Missing column 'Cases' for field 'quantity' of mapping profile 'supplier'"
+            )
+        );
+    }
+
+    #[test]
+    fn quantity_multiplier_overflow_rejects_the_row() {
+        let mapping = ColumnMapping {
+            profile: None,
+            fields: vec![
+                field("sku", "sku"),
+                field("location_code", "location_code"),
+                FieldMapping {
+                    multiplier: Some(1_000_000),
+                    ..field("quantity", "quantity")
+                },
+            ],
+        };
+
+        let layout = mapping
+            .resolve(&headers(&["sku", "location_code", "quantity"]))
+            .unwrap();
+        let error = row_from_fields(&layout, &fields(&["A", "B", "5000"]), 2).unwrap_err();
+        assert_eq!(error, "Invalid quantity '5000'");
+    }
+
+    #[test]
+    fn source_columns_lists_each_column_once() {
+        let mapping = ColumnMapping {
+            profile: None,
+            fields: vec![
+                field("sku", "code"),
+                field("barcode", "CODE"),
+                field("quantity", "qty"),
+            ],
+        };
+        assert_eq!(mapping.source_columns(), headers(&["code", "qty"]));
+    }
+
+    #[tokio::test]
+    async fn csv_rows_are_numbered_by_line() {
+        let data = b"\xEF\xBB\xBFsku,location_code,quantity\nA-1,L1,4\nA-2,L1,x\n\"A-3\",L2,7\n";
+        let (headers, rows) = parse(FileFormat::Csv, ColumnMapping::standard(), data, 5)
+            .await
+            .unwrap();
+
+        assert_eq!(headers, fields(&["sku", "location_code", "quantity"]));
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].row.as_ref().unwrap().quantity, 4);
+        assert_eq!(rows[1].row.as_ref().unwrap_err(), "Invalid quantity 'x'");
+        assert_eq!(rows[2].line, 4);
+        assert_eq!(rows[2].row.as_ref().unwrap().sku, "A-3");
+    }
+
+    #[tokio::test]
+    async fn csv_without_a_required_column_fails() {
+        let data = b"sku,quantity\nA-1,4\n";
+        let error = parse(FileFormat::Csv, ColumnMapping::standard(), data, 64)
+            .await
+            .err()
+            .unwrap();
+        assert!(
+            error
+                .to_string()
+                .contains("Missing required column: location_code")
+        );
+    }
+
+    #[tokio::test]
+    async fn json_rows_are_numbered_by_position() {
+        let data = br#"[
+            {"SKU": "A-1", "location_code": "L1", "quantity": 4, "is_bulk": true},
+            "not a row",
+            {"sku": "A-2", "location_code": "L1", "quantity": 2, "name": {"en": "x"}}
+        ]"#;
+        let (_, rows) = parse(FileFormat::Json, ColumnMapping::standard(), data, 7)
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        let first = rows[0].row.as_ref().unwrap();
+        assert_eq!((rows[0].line, first.sku.as_str()), (1, "A-1"));
+        assert_eq!(first.is_bulk, Some(true));
+        assert_eq!(
+            rows[1].row.as_ref().unwrap_err(),
+            "Row must be a JSON object"
+        );
+        assert_eq!(
+            rows[2].row.as_ref().unwrap_err(),
+            "Field 'name' must be a string, number or boolean"
+        );
+    }
+
+    #[tokio::test]
+    async fn malformed_json_fails_the_file() {
+        let data = br#"[{"sku": "A-1", "location_code": "L1", "quantity": 4},"#;
+        let error = parse(FileFormat::Json, ColumnMapping::standard(), data, 64)
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("Failed to read JSON file"));
+    }
+
+    #[tokio::test]
+    async fn ndjson_skips_blank_lines_and_rejects_bad_ones() {
+        let data = b"{\"sku\":\"A-1\",\"location_code\":\"L1\",\"quantity\":4}\n\n{oops\n{\"sku\":\"A-2\",\"location_code\":\"L2\",\"quantity\":\"3\"}";
+        let (_, rows) = parse(FileFormat::Ndjson, ColumnMapping::standard(), data, 9)
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].line, 1);
+        assert_eq!(rows[1].line, 3);
+        assert!(
+            rows[1]
+                .row
+                .as_ref()
+                .unwrap_err()
+                .starts_with("JSON parse error")
+        );
+        assert_eq!(rows[2].line, 4);
+        assert_eq!(rows[2].row.as_ref().unwrap().quantity, 3);
+    }
+
+    #[tokio::test]
+    async fn xlsx_rows_are_numbered_by_worksheet_row() {
+        let data = xlsx(&[
+            &["sku", "location_code", "quantity"],
+            &["A-1", "L1", "4"],
+            &["", "", ""],
+            &["A-2", "L2", "6"],
+        ]);
+        let (headers, rows) = parse(FileFormat::Xlsx, ColumnMapping::standard(), &data, 100)
+            .await
+            .unwrap();
+
+        assert_eq!(headers, fields(&["sku", "location_code", "quantity"]));
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[1].line, 4);
+        assert_eq!(rows[1].row.as_ref().unwrap().quantity, 6);
+    }
+
+    #[tokio::test]
+    async fn unreadable_xlsx_fails_the_file() {
+        let error = parse(
+            FileFormat::Xlsx,
+            ColumnMapping::standard(),
+            b"not a zip",
+            64,
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("Failed to read XLSX file"));
+    }
+}
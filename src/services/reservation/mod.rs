@@ -57,6 +57,32 @@ pub async fn release_reservations_for_pick_wave<C: ConnectionTrait>(
     Ok(result.rows_affected)
 }
 
+/// Release a pick wave's reservations of one item at one location
+/// Used when a pick held by a cycle count is finally applied or discarded
+pub async fn release_reservations_for_pick_line<C: ConnectionTrait>(
+    db: &C,
+    pick_wave_id: i32,
+    item_id: i32,
+    location_id: i32,
+) -> Result<u64, AppError> {
+    let reason_pattern = format!("pick_wave:{}", pick_wave_id);
+
+    let result = reservation::Entity::delete_many()
+        .filter(reservation::Column::Reason.eq(reason_pattern))
+        .filter(reservation::Column::ItemId.eq(item_id))
+        .filter(reservation::Column::LocationId.eq(location_id))
+        .exec(db)
+        .await
+        .map_err(|e| {
+            AppError::internal(format!(
+                "Failed to release reservations for pick wave {}: {}",
+                pick_wave_id, e
+            ))
+        })?;
+
+    Ok(result.rows_affected)
+}
+
 /// Calculate total reserved quantity for a pick wave
 pub async fn calculate_reserved_for_pick_wave(
     db: &DatabaseConnection,
@@ -1,16 +1,23 @@
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set, TransactionTrait};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect, Set,
+    TransactionTrait,
+};
 
 use crate::db::DatabaseConnection;
-use crate::entities::ledger;
+use crate::entities::ledger as ledger_entity;
 use crate::entities::location;
 use crate::entities::transfer;
 use crate::entities::transfer_line;
+use crate::entities::warehouse;
+use crate::services::cycle_count;
 use crate::services::inventory;
+use crate::services::ledger::{self, NewLedgerEntry, PostedEntry};
 use crate::services::receipt;
 use crate::utils::error::AppError;
 
 /// Create a transfer with DRAFT status and transfer lines
-/// Validates stock availability before creating the transfer
+/// Validates stock availability before creating the transfer. A DRAFT transfer does not hold stock,
+/// so availability is checked again when the transfer is completed
 pub async fn create_transfer(
     db: &DatabaseConnection,
     from_location_id: i32,
@@ -45,7 +52,7 @@ pub async fn create_transfer(
         ));
     }
 
-    let mut transfer_lines = Vec::new();
+    let mut requested_items = Vec::new();
     for item_request in &items {
         if item_request.quantity <= 0 {
             return Err(AppError::bad_request(format!(
@@ -60,15 +67,31 @@ pub async fn create_transfer(
                 AppError::not_found(format!("Item with SKU '{}' not found", item_request.sku))
             })?;
 
-        let available = inventory::calculate_available(db, item.id, from_location_id).await?;
-        if available < item_request.quantity {
-            return Err(AppError::bad_request(format!(
-                "Insufficient stock for item '{}' (SKU: {}). Available: {}, Requested: {}",
-                item.name, item_request.sku, available, item_request.quantity
-            )));
+        requested_items.push((item, item_request.quantity));
+    }
+
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let stock_keys: Vec<(i32, i32)> = requested_items
+        .iter()
+        .map(|(item, _)| (item.id, from_location_id))
+        .collect();
+    inventory::lock_stock(&txn, &stock_keys).await?;
+
+    let mut transfer_lines = Vec::new();
+    for (item, quantity) in requested_items {
+        let available = inventory::calculate_available(&txn, item.id, from_location_id).await?;
+        if available < quantity {
+            let shortfall =
+                ledger::stock_shortfall(&txn, item.id, from_location_id, available, quantity)
+                    .await?;
+            return Err(AppError::insufficient_stock(shortfall));
         }
 
-        transfer_lines.push((item.id, item_request.quantity));
+        transfer_lines.push((item.id, quantity));
     }
 
     let mut transfer_model = <transfer::ActiveModel as sea_orm::ActiveModelTrait>::default();
@@ -77,7 +100,7 @@ pub async fn create_transfer(
     transfer_model.status = Set("DRAFT".to_string());
 
     let transfer = transfer_model
-        .insert(db)
+        .insert(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to create transfer: {}", e)))?;
 
@@ -90,13 +113,17 @@ pub async fn create_transfer(
         line_model.quantity = Set(quantity);
 
         let line = line_model
-            .insert(db)
+            .insert(&txn)
             .await
             .map_err(|e| AppError::internal(format!("Failed to create transfer line: {}", e)))?;
 
         created_lines.push(line);
     }
 
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
     Ok(TransferWithLines {
         transfer,
         lines: created_lines,
@@ -113,6 +140,7 @@ pub async fn complete_transfer(
         .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
 
     let transfer = transfer::Entity::find_by_id(transfer_id)
+        .lock_exclusive()
         .one(&txn)
         .await
         .map_err(|e| AppError::internal(format!("Failed to fetch transfer: {}", e)))?
@@ -140,43 +168,61 @@ pub async fn complete_transfer(
         )));
     }
 
-    let mut ledger_entries = Vec::new();
+    let mut stock_keys = Vec::new();
     for line in &lines {
-        let source_on_hand =
-            inventory::calculate_on_hand(&txn, line.item_id, transfer.from_location_id).await?;
-        let source_balance_after = source_on_hand - line.quantity;
-
-        let mut source_ledger = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
-        source_ledger.item_id = Set(line.item_id);
-        source_ledger.location_id = Set(transfer.from_location_id);
-        source_ledger.quantity_change = Set(-line.quantity);
-        source_ledger.balance_after = Set(Some(source_balance_after));
-        source_ledger.reason_type = Set("TRANSFER".to_string());
-        source_ledger.reference_type = Set(Some("transfer".to_string()));
-        source_ledger.reference_id = Set(Some(transfer.id));
-
-        let source_entry = source_ledger.insert(&txn).await.map_err(|e| {
-            AppError::internal(format!("Failed to create source ledger entry: {}", e))
-        })?;
+        stock_keys.push((line.item_id, transfer.from_location_id));
+        stock_keys.push((line.item_id, transfer.to_location_id));
+    }
+    inventory::lock_stock(&txn, &stock_keys).await?;
+    ledger::lock_chains(&txn, &[transfer.from_location_id, transfer.to_location_id]).await?;
+    ensure_legs_held_together(&txn, &transfer).await?;
 
-        let dest_on_hand =
-            inventory::calculate_on_hand(&txn, line.item_id, transfer.to_location_id).await?;
-        let dest_balance_after = dest_on_hand + line.quantity;
-
-        let mut dest_ledger = <ledger::ActiveModel as sea_orm::ActiveModelTrait>::default();
-        dest_ledger.item_id = Set(line.item_id);
-        dest_ledger.location_id = Set(transfer.to_location_id);
-        dest_ledger.quantity_change = Set(line.quantity);
-        dest_ledger.balance_after = Set(Some(dest_balance_after));
-        dest_ledger.reason_type = Set("TRANSFER".to_string());
-        dest_ledger.reference_type = Set(Some("transfer".to_string()));
-        dest_ledger.reference_id = Set(Some(transfer.id));
-
-        let dest_entry = dest_ledger.insert(&txn).await.map_err(|e| {
-            AppError::internal(format!("Failed to create destination ledger entry: {}", e))
-        })?;
+    // Stock may have moved since the transfer was drafted
+    for line in &lines {
+        let available =
+            inventory::calculate_available(&txn, line.item_id, transfer.from_location_id).await?;
+        if available < line.quantity {
+            let shortfall = ledger::stock_shortfall(
+                &txn,
+                line.item_id,
+                transfer.from_location_id,
+                available,
+                line.quantity,
+            )
+            .await?;
+            return Err(AppError::insufficient_stock(shortfall));
+        }
+    }
 
-        ledger_entries.push((source_entry.id, dest_entry.id));
+    let mut ledger_entries = Vec::new();
+    for line in &lines {
+        let source_entry = ledger::post_entry(
+            &txn,
+            NewLedgerEntry {
+                item_id: line.item_id,
+                location_id: transfer.from_location_id,
+                quantity_change: -line.quantity,
+                reason_type: "TRANSFER".to_string(),
+                reference_type: Some("transfer".to_string()),
+                reference_id: Some(transfer.id),
+            },
+        )
+        .await?;
+
+        let dest_entry = ledger::post_entry(
+            &txn,
+            NewLedgerEntry {
+                item_id: line.item_id,
+                location_id: transfer.to_location_id,
+                quantity_change: line.quantity,
+                reason_type: "TRANSFER".to_string(),
+                reference_type: Some("transfer".to_string()),
+                reference_id: Some(transfer.id),
+            },
+        )
+        .await?;
+
+        ledger_entries.push((source_entry, dest_entry));
     }
 
     let mut transfer_update: transfer::ActiveModel = transfer.clone().into();
@@ -198,6 +244,99 @@ pub async fn complete_transfer(
     })
 }
 
+/// Reject a transfer completion that would hold only one of its legs
+///
+/// A leg at a frozen location is held until the count closes. Stock must not reach the
+/// destination while it is still on hand at the source, or leave the source without arriving, so
+/// the legs are either both posted or both held: with only one location frozen, or a warehouse
+/// that rejects frozen movements, the completion is rejected with LOCATION_FROZEN.
+async fn ensure_legs_held_together<C: ConnectionTrait>(
+    db: &C,
+    transfer: &transfer::Model,
+) -> Result<(), AppError> {
+    let location_ids = [transfer.from_location_id, transfer.to_location_id];
+    let open_counts = cycle_count::find_open_cycle_counts(db, &location_ids).await?;
+    let Some(open_count) = open_counts.first() else {
+        return Ok(());
+    };
+
+    let locations = location::Entity::find()
+        .filter(location::Column::Id.is_in(location_ids))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch locations: {}", e)))?;
+    let warehouses = warehouse::Entity::find()
+        .filter(warehouse::Column::Id.is_in(locations.iter().map(|location| location.warehouse_id)))
+        .all(db)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch warehouses: {}", e)))?;
+
+    let queued = warehouses
+        .iter()
+        .all(|warehouse| warehouse.count_freeze_mode == "QUEUE");
+    if queued && open_counts.len() == location_ids.len() {
+        return Ok(());
+    }
+
+    let code = locations
+        .iter()
+        .find(|location| location.id == open_count.location_id)
+        .map(|location| location.code.clone())
+        .unwrap_or_default();
+    Err(AppError::location_frozen(format!(
+        "Location '{}' is frozen by open cycle count {}",
+        code, open_count.id
+    )))
+}
+
+/// Reverse a COMPLETED transfer by moving its stock back and mark it REVERSED
+pub async fn reverse_transfer(
+    db: &DatabaseConnection,
+    transfer_id: i32,
+) -> Result<ReversedTransfer, AppError> {
+    let txn = db
+        .begin()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to start transaction: {}", e)))?;
+
+    let transfer = transfer::Entity::find_by_id(transfer_id)
+        .lock_exclusive()
+        .one(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to fetch transfer: {}", e)))?
+        .ok_or_else(|| {
+            AppError::not_found(format!("Transfer with id {} not found", transfer_id))
+        })?;
+
+    if transfer.status != "COMPLETED" {
+        return Err(AppError::bad_request(format!(
+            "Transfer with id {} is not in COMPLETED status (current status: {})",
+            transfer_id, transfer.status
+        )));
+    }
+
+    let entries = ledger::find_posted_entries(&txn, "TRANSFER", "transfer", transfer_id).await?;
+    let reversals = ledger::reverse_entries(&txn, &entries).await?;
+
+    let mut transfer_update: transfer::ActiveModel = transfer.into();
+    transfer_update.status = Set("REVERSED".to_string());
+    transfer_update.updated_at = Set(chrono::Utc::now().into());
+
+    let updated_transfer = transfer_update
+        .update(&txn)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to update transfer status: {}", e)))?;
+
+    txn.commit()
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to commit transaction: {}", e)))?;
+
+    Ok(ReversedTransfer {
+        transfer: updated_transfer,
+        reversals,
+    })
+}
+
 /// Request item for transfer creation
 #[derive(Debug, Clone)]
 pub struct TransferItem {
@@ -217,5 +356,12 @@ pub struct TransferWithLines {
 pub struct CompletedTransfer {
     pub transfer: transfer::Model,
     pub lines: Vec<transfer_line::Model>,
-    pub ledger_entries: Vec<(i32, i32)>, // (source_ledger_id, dest_ledger_id)
+    pub ledger_entries: Vec<(PostedEntry, PostedEntry)>, // (source, destination)
+}
+
+/// Reversed transfer with the REVERSAL entries that undid it
+#[derive(Debug)]
+pub struct ReversedTransfer {
+    pub transfer: transfer::Model,
+    pub reversals: Vec<ledger_entity::Model>,
 }
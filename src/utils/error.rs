@@ -2,18 +2,45 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use serde::Serialize;
 use serde_json::json;
 use std::fmt;
 use validator::ValidationErrors;
 
 #[derive(Debug)]
 pub enum AppError {
-    Internal(String),     // 500
-    NotFound(String),     // 404
-    BadRequest(String),   // 400
-    Unauthorized(String), // 401
-    Forbidden(String),    // 403
-    Validation(String),   // 422
+    Internal(String),                  // 500
+    NotFound(String),                  // 404
+    BadRequest(String),                // 400
+    Unauthorized(String),              // 401
+    Forbidden(String),                 // 403
+    Validation(String),                // 422
+    LocationFrozen(String),            // 409
+    InsufficientStock(StockShortfall), // 409
+    IdempotencyInProgress(String),     // 409
+    IdempotencyKeyReused(String),      // 422
+}
+
+/// A ledger movement that would take on-hand below zero
+#[derive(Debug, Clone, Serialize)]
+pub struct StockShortfall {
+    pub item_id: i32,
+    pub sku: String,
+    pub location_id: i32,
+    pub location_code: String,
+    pub on_hand: i32,
+    pub requested: i32,
+    pub shortfall: i32,
+}
+
+impl fmt::Display for StockShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Insufficient stock: Item '{}' at location '{}' has {} on hand, {} requested (short by {})",
+            self.sku, self.location_code, self.on_hand, self.requested, self.shortfall
+        )
+    }
 }
 
 impl fmt::Display for AppError {
@@ -25,6 +52,10 @@ impl fmt::Display for AppError {
             AppError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
             AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
             AppError::Validation(msg) => write!(f, "Validation error: {}", msg),
+            AppError::LocationFrozen(msg) => write!(f, "Location frozen: {}", msg),
+            AppError::InsufficientStock(shortfall) => write!(f, "{}", shortfall),
+            AppError::IdempotencyInProgress(msg) => write!(f, "Idempotency conflict: {}", msg),
+            AppError::IdempotencyKeyReused(msg) => write!(f, "Idempotency key reused: {}", msg),
         }
     }
 }
@@ -33,6 +64,12 @@ impl std::error::Error for AppError {}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        // Structured errors also carry their fields so clients need not parse the message
+        let details = match &self {
+            AppError::InsufficientStock(shortfall) => Some(json!(shortfall)),
+            _ => None,
+        };
+
         let (status, error_message, error_code) = match self {
             AppError::Internal(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -46,14 +83,31 @@ impl IntoResponse for AppError {
             AppError::Validation(msg) => {
                 (StatusCode::UNPROCESSABLE_ENTITY, msg, "VALIDATION_ERROR")
             }
+            AppError::LocationFrozen(msg) => (StatusCode::CONFLICT, msg, "LOCATION_FROZEN"),
+            AppError::InsufficientStock(shortfall) => (
+                StatusCode::CONFLICT,
+                shortfall.to_string(),
+                "INSUFFICIENT_STOCK",
+            ),
+            AppError::IdempotencyInProgress(msg) => {
+                (StatusCode::CONFLICT, msg, "IDEMPOTENCY_IN_PROGRESS")
+            }
+            AppError::IdempotencyKeyReused(msg) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                msg,
+                "IDEMPOTENCY_KEY_REUSED",
+            ),
         };
 
-        let body = axum::Json(json!({
-            "error": {
-                "code": error_code,
-                "message": error_message
-            }
-        }));
+        let mut error = json!({
+            "code": error_code,
+            "message": error_message
+        });
+        if let Some(details) = details {
+            error["details"] = details;
+        }
+
+        let body = axum::Json(json!({ "error": error }));
 
         (status, body).into_response()
     }
@@ -84,6 +138,22 @@ impl AppError {
         Self::Validation(msg.into())
     }
 
+    pub fn location_frozen<S: Into<String>>(msg: S) -> Self {
+        Self::LocationFrozen(msg.into())
+    }
+
+    pub fn insufficient_stock(shortfall: StockShortfall) -> Self {
+        Self::InsufficientStock(shortfall)
+    }
+
+    pub fn idempotency_in_progress<S: Into<String>>(msg: S) -> Self {
+        Self::IdempotencyInProgress(msg.into())
+    }
+
+    pub fn idempotency_key_reused<S: Into<String>>(msg: S) -> Self {
+        Self::IdempotencyKeyReused(msg.into())
+    }
+
     /// Collect and format validation errors from validator into a user-friendly string
     pub fn collect_validation_errors(errors: &ValidationErrors) -> String {
         let mut error_messages = Vec::new();
@@ -1,4 +1,5 @@
 use chrono_tz::Tz;
+use sea_orm::prelude::Decimal;
 use std::borrow::Cow;
 use std::str::FromStr;
 use validator::ValidationError;
@@ -32,3 +33,80 @@ pub fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
         }
     }
 }
+
+/// Validate that a monetary amount (e.g. unit cost) is not negative
+pub fn validate_non_negative_amount(amount: &Decimal) -> Result<(), ValidationError> {
+    if amount.is_sign_negative() && !amount.is_zero() {
+        let mut err = ValidationError::new("negative_amount");
+        err.message = Some(Cow::Borrowed("Amount cannot be negative"));
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Validate a warehouse count freeze mode
+///
+/// Accepts:
+/// - "REJECT" - movements into a location with an open cycle count are rejected
+/// - "QUEUE" - such movements are held and applied when the count closes
+pub fn validate_count_freeze_mode(mode: &str) -> Result<(), ValidationError> {
+    if mode == "REJECT" || mode == "QUEUE" {
+        return Ok(());
+    }
+
+    let mut err = ValidationError::new("invalid_count_freeze_mode");
+    err.message = Some(Cow::Borrowed(
+        "Count freeze mode must be either 'REJECT' or 'QUEUE'",
+    ));
+    Err(err)
+}
+
+/// Validate a bulk receipt mode ("all" or "partial")
+pub fn validate_bulk_receipt_mode(mode: &str) -> Result<(), ValidationError> {
+    if mode == "all" || mode == "partial" {
+        return Ok(());
+    }
+
+    let mut err = ValidationError::new("invalid_bulk_receipt_mode");
+    err.message = Some(Cow::Borrowed("Mode must be either 'all' or 'partial'"));
+    Err(err)
+}
+
+/// Validate a comma-separated list of working days (e.g. "MON,TUE,WED,THU,FRI")
+pub fn validate_working_days(working_days: &str) -> Result<(), ValidationError> {
+    let days: Vec<&str> = working_days.split(',').map(|d| d.trim()).collect();
+
+    let all_valid = days.iter().all(|day| {
+        matches!(
+            day.to_uppercase().as_str(),
+            "MON" | "TUE" | "WED" | "THU" | "FRI" | "SAT" | "SUN"
+        )
+    });
+
+    if working_days.trim().is_empty() || !all_valid {
+        let mut err = ValidationError::new("invalid_working_days");
+        err.message = Some(Cow::Borrowed(
+            "Working days must be a comma-separated list of MON, TUE, WED, THU, FRI, SAT, SUN",
+        ));
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Validate an inventory adjustment reason code
+///
+/// Accepts: DAMAGED, FOUND, LOST, EXPIRED, DATA_ENTRY_ERROR, OTHER
+pub fn validate_adjustment_reason_code(reason_code: &str) -> Result<(), ValidationError> {
+    if matches!(
+        reason_code,
+        "DAMAGED" | "FOUND" | "LOST" | "EXPIRED" | "DATA_ENTRY_ERROR" | "OTHER"
+    ) {
+        return Ok(());
+    }
+
+    let mut err = ValidationError::new("invalid_reason_code");
+    err.message = Some(Cow::Borrowed(
+        "Reason code must be one of DAMAGED, FOUND, LOST, EXPIRED, DATA_ENTRY_ERROR, OTHER",
+    ));
+    Err(err)
+}
@@ -0,0 +1,125 @@
+//! Concurrency checks against a real database.
+//!
+//! These tests need a migrated Postgres database in `DATABASE_URL` and are ignored by default:
+//!
+//! ```sh
+//! DATABASE_URL=postgres://... cargo test --test concurrent_allocation -- --ignored
+//! ```
+
+use sea_orm::{ActiveModelTrait, Set};
+use warehouse_api::db::{self, DatabaseConnection};
+use warehouse_api::entities::warehouse;
+use warehouse_api::services::{inventory, pick, receipt};
+
+const CONTENDERS: usize = 8;
+const ROUNDS: usize = 5;
+
+async fn connect() -> DatabaseConnection {
+    dotenvy::dotenv().ok();
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    db::create_connection(&database_url)
+        .await
+        .expect("Failed to connect to database")
+}
+
+/// Create a fresh item and location holding `quantity` units, so runs never share stock
+async fn seed_stock(db: &DatabaseConnection, quantity: i32) -> (String, String, i32, i32) {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let sku = format!("CONC-{}", &suffix[..12]);
+    let location_code = format!("CONC-{}", &suffix[..12]);
+
+    let mut warehouse_model = <warehouse::ActiveModel as sea_orm::ActiveModelTrait>::default();
+    warehouse_model.name = Set(format!("Concurrency {}", &suffix[..12]));
+    warehouse_model.address = Set("Test".to_string());
+    warehouse_model.timezone = Set("UTC".to_string());
+    warehouse_model.is_active = Set(true);
+    warehouse_model.count_freeze_mode = Set("REJECT".to_string());
+    warehouse_model.working_days = Set("MON,TUE,WED,THU,FRI".to_string());
+    let warehouse = warehouse_model
+        .insert(db)
+        .await
+        .expect("Failed to create warehouse");
+
+    let item = receipt::find_or_create_item_by_sku(db, &sku, None, None, None, None)
+        .await
+        .expect("Failed to create item");
+    let location = receipt::find_or_create_location_by_code(
+        db,
+        &location_code,
+        Some(warehouse.id),
+        None,
+        None,
+        None,
+        Some(true),
+        None,
+    )
+    .await
+    .expect("Failed to create location");
+
+    receipt::create_receipt(
+        db,
+        &receipt::ReceiptHeader::default(),
+        item.id,
+        location.id,
+        quantity,
+    )
+    .await
+    .expect("Failed to receive stock");
+
+    (sku, location_code, item.id, location.id)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore = "requires DATABASE_URL pointing at a migrated database"]
+async fn concurrent_allocations_do_not_oversell() {
+    let db = connect().await;
+
+    // The race window is small, so run a few rounds on fresh stock
+    for _ in 0..ROUNDS {
+        allocate_all_at_once(&db).await;
+    }
+}
+
+async fn allocate_all_at_once(db: &DatabaseConnection) {
+    let (sku, location_code, item_id, location_id) = seed_stock(db, 10).await;
+
+    // Several waves that each want all of the stock, allocated at the same time
+    let mut wave_ids = Vec::new();
+    for _ in 0..CONTENDERS {
+        let wave = pick::create_pick_wave(
+            db,
+            vec![pick::PickItem {
+                sku: sku.clone(),
+                quantity: 10,
+                location_code: location_code.clone(),
+            }],
+        )
+        .await
+        .expect("Failed to create pick wave");
+        wave_ids.push(wave.wave.id);
+    }
+
+    let mut allocations = tokio::task::JoinSet::new();
+    for wave_id in wave_ids {
+        let db = db.clone();
+        allocations.spawn(async move { pick::allocate_pick_wave(&db, wave_id).await });
+    }
+
+    let mut allocated = 0;
+    while let Some(result) = allocations.join_next().await {
+        if result.expect("Allocation task panicked").is_ok() {
+            allocated += 1;
+        }
+    }
+    assert_eq!(allocated, 1, "exactly one allocation should succeed");
+
+    let reserved = inventory::calculate_reserved(db, item_id, location_id)
+        .await
+        .expect("Failed to read reserved stock");
+    assert_eq!(reserved, 10);
+
+    let available = inventory::calculate_available(db, item_id, location_id)
+        .await
+        .expect("Failed to read available stock");
+    assert_eq!(available, 0);
+}